@@ -0,0 +1,104 @@
+use sleppa_cargo::errors::CargoError;
+use sleppa_changelog::errors::ChangelogError;
+use sleppa_configuration::errors::ConfigurationError;
+use sleppa_primitives::plugins::{errors::PluginError, Step};
+use sleppa_primitives::repositories::errors::RepositoryError;
+use sleppa_versioner::errors::VersionerError;
+
+/// Exit code returned when the pipeline succeeds, even if no release is needed
+pub const EXIT_SUCCESS: u8 = 0;
+/// Exit code returned when an unexpected error occurs
+pub const EXIT_FAILURE: u8 = 1;
+/// Exit code returned when the configuration file can't be loaded, or the release conditions are not met
+pub const EXIT_CONFIGURATION: u8 = 3;
+/// Exit code returned when the repository can't be reached or read
+pub const EXIT_REPOSITORY: u8 = 4;
+/// Exit code returned when the last tag can't be parsed as a version
+pub const EXIT_VERSION: u8 = 5;
+/// Exit code returned when the changelog can't be written
+pub const EXIT_CHANGELOG: u8 = 6;
+/// Exit code returned when the release can't be published
+pub const EXIT_PUBLICATION: u8 = 7;
+/// Exit code returned when a commit message doesn't follow the release rules
+pub const EXIT_LINT: u8 = 8;
+
+/// Enumerates errors that could occur while running the release pipeline.
+///
+/// This list is a central structure aiming to gather the errors of every Sleppa crate
+/// used by the command-line tool.
+#[derive(thiserror::Error, Debug)]
+pub enum SleppaError {
+    /// Chained errors occurring when loading the configuration file
+    #[error(transparent)]
+    ConfigurationError(#[from] ConfigurationError),
+
+    /// Chained errors occurring when working with the repository
+    #[error(transparent)]
+    RepositoryError(#[from] RepositoryError),
+
+    /// Chained errors occurring when running a step of the release
+    #[error(transparent)]
+    PluginError(#[from] PluginError),
+
+    /// Chained errors occurring when listing the packages of the workspace
+    #[error(transparent)]
+    CargoError(#[from] CargoError),
+
+    /// Chained errors occurring when regenerating the changelog from the tag history
+    #[error(transparent)]
+    ChangelogError(#[from] ChangelogError),
+
+    /// Chained errors occurring when parsing the tag format
+    #[error(transparent)]
+    VersionerError(#[from] VersionerError),
+
+    /// Chained I/O errors occurring when writing the analysis report
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    /// Chained errors occurring when serializing the analysis report
+    #[error(transparent)]
+    SerializationError(#[from] serde_json::Error),
+
+    /// The tag to lint the commits from is not found
+    #[error("The tag {0} is not found in the repository")]
+    UnknownTag(String),
+
+    /// Commit messages don't follow the release rules, out of the linted ones
+    #[error("{0} out of {1} commit messages don't follow the release rules")]
+    LintFailure(usize, usize),
+
+    /// The repository is not formed as `owner/name`
+    #[error("The repository must be formed as 'owner/name'. Found : {0}")]
+    InvalidRepository(String),
+}
+
+impl SleppaError {
+    /// Returns the process exit code associated with the error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            SleppaError::ConfigurationError(_) | SleppaError::CargoError(_) => EXIT_CONFIGURATION,
+            SleppaError::RepositoryError(_) | SleppaError::InvalidRepository(_) | SleppaError::UnknownTag(_) => {
+                EXIT_REPOSITORY
+            }
+            SleppaError::LintFailure(..) => EXIT_LINT,
+            SleppaError::VersionerError(VersionerError::IncorrectTagFormat(_)) => EXIT_CONFIGURATION,
+            SleppaError::VersionerError(_) => EXIT_VERSION,
+            SleppaError::ChangelogError(_) => EXIT_CHANGELOG,
+            SleppaError::PluginError(PluginError::StepFailure { step, .. }) => match step {
+                Step::VerifyConditions => EXIT_CONFIGURATION,
+                Step::AnalyzeCommits => EXIT_VERSION,
+                Step::GenerateNotes | Step::Prepare => EXIT_CHANGELOG,
+                Step::Publish | Step::Success => EXIT_PUBLICATION,
+            },
+            SleppaError::PluginError(_) | SleppaError::IoError(_) | SleppaError::SerializationError(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+/// Definition of the command-line tool result
+pub type SleppaResult<R> = Result<R, SleppaError>;
+
+#[cfg(test)]
+/// Result type alias returned by function in unit tests.
+pub type TestResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
@@ -0,0 +1,253 @@
+//! Sleppa command-line tool
+//!
+//! This binary runs the Sleppa release pipeline on a GitHub repository, leveraging on the `sleppa.toml`
+//! configuration file. The following subcommands are available :
+//! - `next-version`: prints the next version without releasing,
+//! - `changelog`: bumps the Cargo manifests, writes and commits the changelog of the next version,
+//! - `lint`: verifies the commit messages since the last tag, or a message read from the standard input, follow the
+//!   Conventional Commits specification and the release rules, exiting with a non-zero code otherwise,
+//! - `release`: bumps the Cargo manifests, writes the changelog and publishes the release of the next version, and
+//!   publishes the crates to a Cargo registry if the configuration file has a `[publish]` section,
+//! - `backfill`: regenerates the whole changelog from the tag history, each release being dated with its tag's
+//!   commit.
+//!
+//! The repository is given as `owner/name`, either with the `--repository` option or with the
+//! `GITHUB_REPOSITORY` environment variable set by GitHub Actions. Publishing a release needs a `GITHUB_TOKEN`,
+//! or the `GITHUB_APP_ID`, `GITHUB_APP_INSTALLATION_ID` and `GITHUB_APP_PRIVATE_KEY` of a GitHub App. The API of a
+//! GitHub Enterprise server is given by `GITHUB_API_URL`.
+//! With `--forge gitlab`, the repository is the path of a GitLab project, e.g. `group/project`, on gitlab.com or on
+//! the instance given by `CI_API_V4_URL`, and publishing a release needs a `GITLAB_TOKEN` or the `CI_JOB_TOKEN`.
+//! With `--forge gitea`, the repository is formed as `owner/name` on the Gitea or Forgejo instance given by
+//! `GITEA_URL`, and publishing a release needs a `GITEA_TOKEN`.
+//! The tags and the commits can be read offline from a local git repository with the `--local` option.
+//! The `--branch` option, or the `GITHUB_REF_NAME` environment variable, gives the released branch : if the
+//! configuration file maps it to a channel, a pre-release of this channel is released.
+//!
+//! When the configuration file has a `[monorepo]` section, each package of the Cargo workspace is released
+//! independently with its own tags, and a release is printed for each released package.
+//!
+//! With the `--report <PATH>` option, the analysis of every commit is written to a JSON file : the rule it matched or
+//! the reason it was ignored, the number of commits per release action and the commit deciding the release.
+//!
+//! With the `--dry-run` option, the next version is computed and the changelog and the release are printed,
+//! without writing the changelog file, committing, pushing nor publishing anything.
+//!
+//! The process exits with `0` when it succeeds, even if no release is needed, `2` when the command line
+//! is incorrect, and with a dedicated code for each failing step otherwise (see the [errors] module).
+
+mod errors;
+mod monorepo;
+mod pipeline;
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use errors::{SleppaError, SleppaResult, EXIT_SUCCESS};
+use pipeline::{Forge, Pipeline, Release};
+use sleppa_changelog::constants::CHANGELOG_DEFAULT_PATH;
+use sleppa_commit_analyzer::CommitAnalyzerPlugin;
+use sleppa_configuration::try_parse;
+use sleppa_primitives::plugins::Step;
+use sleppa_primitives::Commit;
+
+/// Command-line arguments
+#[derive(Debug, Parser)]
+#[command(name = "sleppa", version, about = "Semantic release for Rust projects")]
+struct Cli {
+    /// Path to the configuration file
+    #[arg(short, long, default_value = "sleppa.toml")]
+    config: PathBuf,
+
+    /// Repository to release, formed as `owner/name` or as the path of a GitLab project, needed unless linting a
+    /// message from stdin
+    #[arg(short, long, env = "GITHUB_REPOSITORY", default_value = "")]
+    repository: String,
+
+    /// Forge hosting the repository, where the tags and the commits are read and the release is published
+    #[arg(long, value_enum, default_value = "github")]
+    forge: Forge,
+
+    /// Path to a local git repository to read the tags and the commits from, instead of GitHub
+    #[arg(short, long)]
+    local: Option<PathBuf>,
+
+    /// Branch to release, used to find its pre-release channel in the configuration file
+    #[arg(short, long, env = "GITHUB_REF_NAME", default_value = "")]
+    branch: String,
+
+    /// Computes and prints the release without writing, committing nor publishing anything
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Path to a JSON file where the analysis of every commit is written
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Step of the release pipeline to run
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Enumerates the available subcommands.
+#[derive(Debug, Subcommand, PartialEq)]
+enum Command {
+    /// Bumps the Cargo manifests, writes the changelog and publishes the release of the next version
+    Release {
+        /// Path to the changelog file
+        #[arg(long, default_value = CHANGELOG_DEFAULT_PATH)]
+        changelog: PathBuf,
+    },
+    /// Prints the next version without releasing
+    NextVersion,
+    /// Bumps the Cargo manifests, writes and commits the changelog of the next version
+    Changelog {
+        /// Path to the changelog file
+        #[arg(long, default_value = CHANGELOG_DEFAULT_PATH)]
+        changelog: PathBuf,
+    },
+    /// Verifies the commit messages follow the Conventional Commits specification and the release rules
+    Lint {
+        /// Lints a single message read from the standard input, e.g. in a `commit-msg` hook
+        #[arg(long)]
+        stdin: bool,
+        /// Tag from which the commits are linted, the last tag by default
+        #[arg(long, conflicts_with = "stdin")]
+        since: Option<String>,
+    },
+    /// Regenerates the whole changelog from the tag history, one release per tag
+    Backfill {
+        /// Path to the changelog file
+        #[arg(long, default_value = CHANGELOG_DEFAULT_PATH)]
+        changelog: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli).await {
+        Ok(()) => ExitCode::from(EXIT_SUCCESS),
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+/// Runs the subcommand given on the command line.
+///
+/// Information messages are printed on the standard error, so that the standard output only contains
+/// the new version and can be used by scripts.
+async fn run(cli: Cli) -> SleppaResult<()> {
+    if let Command::Lint { stdin, since } = &cli.command {
+        return lint(&cli, *stdin, since.as_deref()).await;
+    }
+
+    let mut pipeline = Pipeline::try_new(&cli.config, &cli.repository, cli.local, cli.forge)?;
+    pipeline.context.branch = cli.branch;
+    pipeline.context.dry_run = cli.dry_run;
+
+    let (last_step, changelog) = match &cli.command {
+        Command::NextVersion => (Step::AnalyzeCommits, PathBuf::from(CHANGELOG_DEFAULT_PATH)),
+        Command::Changelog { changelog } => (Step::Prepare, changelog.clone()),
+        Command::Release { changelog } => (Step::Success, changelog.clone()),
+        Command::Backfill { changelog } => return backfill(&pipeline, changelog, cli.dry_run).await,
+        Command::Lint { .. } => return Ok(()),
+    };
+    let releases = pipeline.run(last_step, changelog.clone()).await?;
+
+    if let Some(report) = &cli.report {
+        fs::write(report, serde_json::to_string_pretty(&pipeline.analyses)?)?;
+    }
+
+    if releases.is_empty() {
+        eprintln!("No commit matches a release rule since the last tag, no release is needed.");
+        return Ok(());
+    }
+
+    for Release {
+        new_tag,
+        notes,
+        reports,
+    } in releases
+    {
+        for report in reports {
+            eprintln!("{report}");
+        }
+
+        match cli.command {
+            Command::NextVersion => println!("{new_tag}"),
+            Command::Changelog { .. } => {
+                if cli.dry_run {
+                    print!("{notes}");
+                } else {
+                    eprintln!("Changelog of {new_tag} written to {}", changelog.display());
+                }
+            }
+            Command::Release { .. } => {
+                if cli.dry_run {
+                    print!("{notes}");
+                    eprintln!(
+                        "Dry run: release {new_tag} would be published to {}",
+                        pipeline.context.repository_url
+                    );
+                } else {
+                    eprintln!("Release {new_tag} published");
+                }
+            }
+            Command::Lint { .. } | Command::Backfill { .. } => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Lints the commit messages, printing a violation per line on the standard output.
+///
+/// The commits since a tag are linted, or a single message read from the standard input. In the latter case, the
+/// lines starting with `#` are removed as git does, and no repository is needed.
+async fn lint(cli: &Cli, stdin: bool, since: Option<&str>) -> SleppaResult<()> {
+    let (configuration, commits) = if stdin {
+        let mut message = String::new();
+        std::io::stdin().read_to_string(&mut message)?;
+        let message: Vec<&str> = message.lines().filter(|line| !line.starts_with('#')).collect();
+
+        (
+            try_parse(&cli.config)?,
+            vec![Commit::new("", message.join("\n").trim())],
+        )
+    } else {
+        let pipeline = Pipeline::try_new(&cli.config, &cli.repository, cli.local.clone(), cli.forge)?;
+        let commits = pipeline.commits_since(since).await?;
+        (pipeline.context.configuration, commits)
+    };
+
+    let violations = CommitAnalyzerPlugin.lint(&commits, &configuration.release_rules);
+    for violation in &violations {
+        println!("{violation}");
+    }
+
+    if !violations.is_empty() {
+        return Err(SleppaError::LintFailure(violations.len(), commits.len()));
+    }
+    eprintln!("The {} commit messages follow the release rules.", commits.len());
+    Ok(())
+}
+
+/// Regenerates the whole changelog from the tag history, printing it in dry-run mode.
+async fn backfill(pipeline: &Pipeline, changelog: &Path, dry_run: bool) -> SleppaResult<()> {
+    let changelog_text = pipeline.backfill(changelog).await?;
+    if dry_run {
+        print!("{changelog_text}");
+    } else {
+        eprintln!("Changelog regenerated from the tag history to {}", changelog.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
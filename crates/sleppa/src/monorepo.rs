@@ -0,0 +1,81 @@
+//! Monorepo releases
+//!
+//! This module attributes the commits of a workspace to its packages, so that each package is released
+//! independently with its own tags, e.g. `sleppa_versioner@v0.2.0` :
+//! - a commit belongs to the packages owning the files it changes, a file being owned by the package
+//!   whose directory is the longest prefix of its path,
+//! - a commit belongs to the package named by its scope, or aliased by its scope in the `[monorepo.scopes]`
+//!   section of the configuration file.
+//!
+//! The packages depending on a released package are released too, transitively.
+
+use std::collections::{HashMap, HashSet};
+
+use sleppa_cargo::WorkspacePackage;
+use sleppa_primitives::Commit;
+
+/// Returns the package owning a file, i.e. the package whose directory is the longest prefix of the file's path.
+pub fn owner<'a>(file: &str, packages: &'a [WorkspacePackage]) -> Option<&'a WorkspacePackage> {
+    packages
+        .iter()
+        .filter(|package| {
+            package.path.is_empty()
+                || file
+                    .strip_prefix(package.path.as_str())
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .max_by_key(|package| package.path.len())
+}
+
+/// Verifies if a commit belongs to a package, either by the files it changes or by its scope.
+///
+/// The scopes are aliases of the packages' names, given by the monorepo configuration.
+pub fn is_attributed(
+    commit: &Commit,
+    package: &WorkspacePackage,
+    packages: &[WorkspacePackage],
+    scopes: &HashMap<String, String>,
+) -> bool {
+    let by_files = commit
+        .files
+        .iter()
+        .any(|file| owner(file, packages).map_or(false, |owner| owner.name == package.name));
+    let by_scope =
+        !commit.scope.is_empty() && (commit.scope == package.name || scopes.get(&commit.scope) == Some(&package.name));
+
+    by_files || by_scope
+}
+
+/// Returns the names of the packages depending, directly or transitively, on the released packages.
+///
+/// The released packages are not part of the result. The names are ordered as the packages are.
+pub fn dependents(packages: &[WorkspacePackage], released: &HashSet<String>) -> Vec<String> {
+    let mut bumped = released.clone();
+    let mut dependents = vec![];
+
+    // Adds the packages depending on a bumped package until no new dependent is found
+    loop {
+        let new_dependents: Vec<String> = packages
+            .iter()
+            .filter(|package| !bumped.contains(&package.name))
+            .filter(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .any(|dependency| bumped.contains(dependency))
+            })
+            .map(|package| package.name.clone())
+            .collect();
+        if new_dependents.is_empty() {
+            break;
+        }
+        bumped.extend(new_dependents.iter().cloned());
+        dependents.extend(new_dependents);
+    }
+
+    packages
+        .iter()
+        .filter(|package| dependents.contains(&package.name))
+        .map(|package| package.name.clone())
+        .collect()
+}
@@ -0,0 +1,428 @@
+//! Release pipeline
+//!
+//! This module chains the Sleppa plugins into a [ReleasePipeline] to compute and publish a new release :
+//! - the commits since the last tag are retrieved from a [Repository],
+//! - the [CommitAnalyzerPlugin] determines the release action to apply,
+//! - the [VersionerPlugin] calculates the new tag,
+//! - the [CargoPlugin] bumps the version of the Cargo manifests, if the repository has a `Cargo.toml` file,
+//! - the [ChangelogPlugin] renders the release notes and writes the changelog file,
+//! - the [PublisherPlugin] publishes the crates to a Cargo registry, if the configuration file asks for it,
+//! - the [CodeArchiverPlugin] publishes the release on GitHub, the [GitlabReleasePlugin] on GitLab, or the
+//!   [GiteaReleasePlugin] on Gitea.
+//!
+//! In a monorepo, these steps are run for each released package of the workspace (see the [monorepo] module).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::Serialize;
+use sleppa_cargo::{dependency_order, publisher::PublisherPlugin, CargoPlugin, WorkspacePackage};
+use sleppa_changelog::ChangelogPlugin;
+use sleppa_code_archiver::{gitea::GiteaReleasePlugin, gitlab::GitlabReleasePlugin, CodeArchiverPlugin};
+use sleppa_commit_analyzer::{report::AnalysisReport, CommitAnalyzerPlugin};
+use sleppa_configuration::{try_parse, ReleaseAction};
+use sleppa_primitives::plugins::{ReleaseContext, ReleasePipeline, Step};
+use sleppa_primitives::repositories::{
+    gitea::GiteaRepository, github::GithubRepository, github_client::GithubClientBuilder, gitlab::GitlabRepository,
+    local::LocalGitRepository, Repository, RepositoryTag,
+};
+use sleppa_primitives::Commit;
+use sleppa_versioner::{find_last_tag, sort_tags, TagFormat, VersionerPlugin};
+
+use crate::errors::{SleppaError, SleppaResult};
+use crate::monorepo;
+
+/// Defines the release pipeline and its fields
+///
+/// The pipeline is built from the configuration file and the repository to release, hosted on GitHub, GitLab or
+/// Gitea.
+/// The tags and the commits are read from this repository, or from a local git repository if one is given.
+pub struct Pipeline {
+    /// The release context shared by the plugins
+    pub context: ReleaseContext,
+    /// The repository to release
+    pub repository: ForgeRepository,
+    /// The repository where the tags and the commits are read from
+    pub source: Box<dyn Repository + Send + Sync>,
+    /// The analyses of the commits, one per package in a monorepo, filled when the pipeline runs
+    pub analyses: Vec<Analysis>,
+}
+
+/// Enumerates the forges hosting the repository to release.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Forge {
+    /// GitHub, or a GitHub Enterprise server
+    #[default]
+    Github,
+    /// GitLab, or a self-hosted GitLab instance
+    Gitlab,
+    /// A Gitea or Forgejo instance
+    Gitea,
+}
+
+/// Enumerates the repositories of the forges, where the releases are published.
+#[derive(Debug, Clone)]
+pub enum ForgeRepository {
+    /// A repository hosted on GitHub
+    Github(GithubRepository),
+    /// A project hosted on GitLab
+    Gitlab(GitlabRepository),
+    /// A repository hosted on Gitea or Forgejo
+    Gitea(GiteaRepository),
+}
+
+/// Defines the analysis of the commits of a package and its fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Analysis {
+    /// The analyzed package in a monorepo, or `None` for the whole repository
+    pub package: Option<String>,
+    /// The analysis of the package's commits
+    #[serde(flatten)]
+    pub report: AnalysisReport,
+}
+
+/// Defines a release computed by the pipeline and its fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// The tag of the release
+    pub new_tag: String,
+    /// The release notes, empty if the changelog step is not run
+    pub notes: String,
+    /// The messages reported by the plugins, e.g. the crates published to the registry
+    pub reports: Vec<String>,
+}
+
+impl Pipeline {
+    /// Builds the pipeline from a configuration file path and a repository hosted on a forge.
+    ///
+    /// The repository is formed as `owner/name` on GitHub and Gitea, and as the path of the project on GitLab, e.g.
+    /// `group/subgroup/project`. The client of the forge is read from the environment (see
+    /// [GithubClientBuilder::from_env], [GitlabRepository::from_env] and [GiteaRepository::from_env]), and shared by
+    /// the repository and the release publication. The pull requests merged by the commits are found as the
+    /// `[pull_requests]` section of the configuration file defines.
+    /// If a local path is given, the tags and the commits are read from the git repository at this path.
+    /// The tag formats of the configuration are verified as they are parsed by [TagFormat::try_new].
+    pub fn try_new(
+        configuration_path: &Path,
+        repository: &str,
+        local_path: Option<PathBuf>,
+        forge: Forge,
+    ) -> SleppaResult<Self> {
+        let configuration = try_parse(configuration_path)?;
+        TagFormat::try_new(&configuration.tag_format(None))?;
+        if let Some(monorepo) = &configuration.monorepo {
+            TagFormat::try_new(&monorepo.tag_format)?;
+        }
+        let pull_requests = configuration.pull_requests.clone();
+        let repository = match forge {
+            Forge::Github => {
+                let mut repository = parse_repository(repository)?;
+                repository.client = GithubClientBuilder::from_env()?.build()?;
+                repository.pull_requests = pull_requests.clone();
+                ForgeRepository::Github(repository)
+            }
+            Forge::Gitlab => ForgeRepository::Gitlab(GitlabRepository {
+                pull_requests: pull_requests.clone(),
+                ..GitlabRepository::from_env(&parse_project(repository)?)
+            }),
+            Forge::Gitea => {
+                let repository = parse_repository(repository)?;
+                ForgeRepository::Gitea(GiteaRepository {
+                    pull_requests: pull_requests.clone(),
+                    ..GiteaRepository::from_env(&repository.owner, &repository.repo)?
+                })
+            }
+        };
+        let source: Box<dyn Repository + Send + Sync> = match (local_path, &repository) {
+            (Some(path), _) => Box::new(LocalGitRepository { path, pull_requests }),
+            (None, ForgeRepository::Github(repository)) => Box::new(repository.clone()),
+            (None, ForgeRepository::Gitlab(repository)) => Box::new(repository.clone()),
+            (None, ForgeRepository::Gitea(repository)) => Box::new(repository.clone()),
+        };
+
+        Ok(Pipeline {
+            context: ReleaseContext {
+                configuration,
+                repository_url: repository.url(),
+                ..Default::default()
+            },
+            repository,
+            source,
+            analyses: vec![],
+        })
+    }
+
+    /// Runs the release steps until the given last step, included, and returns the releases.
+    ///
+    /// The context is filled with the last tag and the commits since this tag before running the plugins.
+    /// The last tag is the highest release tag by SemVer precedence among the ancestors of the released branch.
+    /// If no commit matches a release rule, no new tag is set in the context, hence no release is needed.
+    /// In a monorepo, each package of the workspace is released independently (see [Pipeline::run_monorepo]).
+    pub async fn run(&mut self, last_step: Step, changelog_path: PathBuf) -> SleppaResult<Vec<Release>> {
+        if self.context.configuration.monorepo.is_some() {
+            return self.run_monorepo(last_step, changelog_path).await;
+        }
+
+        let tags = self.source.get_tags().await?;
+        self.context.last_tag = self.last_tag(tags, None).await?;
+        self.context.commits = self.source.get_inner_commits_since(&self.context.last_tag).await?;
+
+        let release_pipeline = ReleasePipeline::new().with_plugin(CommitAnalyzerPlugin);
+        self.release_pipeline(release_pipeline, last_step, changelog_path)
+            .run(&mut self.context, last_step)
+            .await?;
+        self.record_analysis();
+        Ok(self.release().into_iter().collect())
+    }
+
+    /// Returns the commits since the given tag, or since the last tag if none is given.
+    pub async fn commits_since(&self, tag: Option<&str>) -> SleppaResult<Vec<Commit>> {
+        let tag = match tag {
+            Some(identifier) => self
+                .source
+                .get_tags()
+                .await?
+                .into_iter()
+                .find(|tag| tag.identifier == identifier)
+                .ok_or_else(|| SleppaError::UnknownTag(identifier.to_string()))?,
+            None => self.last_tag(self.source.get_tags().await?, None).await?,
+        };
+
+        Ok(self.source.get_inner_commits_since(&tag).await?)
+    }
+
+    /// Regenerates the whole changelog from the tag history of the released branch, and returns it.
+    ///
+    /// The release tags matching the tag format, pre-releases left out, are sorted by SemVer precedence, and the ones
+    /// whose commit is an ancestor of the released branch are kept (see [ChangelogPlugin::backfill]). The changelog
+    /// file is replaced, unless in dry-run mode.
+    pub async fn backfill(&self, changelog_path: &Path) -> SleppaResult<String> {
+        let format = TagFormat::try_new(&self.context.configuration.tag_format(None))?;
+
+        let mut tags = vec![];
+        for tag in sort_tags(self.source.get_tags().await?, &format, None) {
+            if self.source.is_ancestor(&tag, &self.context.branch).await? {
+                tags.push(tag);
+            }
+        }
+        tags.reverse();
+
+        let mut changelog_plugin = ChangelogPlugin {
+            dry_run: self.context.dry_run,
+            ..ChangelogPlugin::new()
+        };
+        Ok(changelog_plugin
+            .backfill(
+                self.source.as_ref(),
+                &tags,
+                &self.context.configuration,
+                changelog_path,
+                &self.context.repository_url,
+            )
+            .await?)
+    }
+
+    /// Releases each package of the workspace with its own tags, until the given last step, included.
+    ///
+    /// The commits since the last tag of a package are attributed to it by the files they change or by their
+    /// scope, and analyzed to determine its release action. The packages depending on a released package are
+    /// released too, with a patch release at least. Then the release steps are run for each released package,
+    /// in dependency order so that a package is published after the packages it depends on.
+    pub async fn run_monorepo(&mut self, last_step: Step, changelog_path: PathBuf) -> SleppaResult<Vec<Release>> {
+        let scopes = match &self.context.configuration.monorepo {
+            Some(monorepo) => monorepo.scopes.clone(),
+            None => HashMap::new(),
+        };
+        let packages = dependency_order(&CargoPlugin::new().packages()?);
+        let tags = self.source.get_tags().await?;
+
+        // Analyzes the commits attributed to each package since its last tag
+        let mut analyses = vec![];
+        for package in &packages {
+            let last_tag = self.last_tag(tags.clone(), Some(&package.name)).await?;
+            let commits = self
+                .source
+                .get_inner_commits_since(&last_tag)
+                .await?
+                .into_iter()
+                .filter(|commit| monorepo::is_attributed(commit, package, &packages, &scopes))
+                .collect();
+
+            self.reset_context(package, last_tag, commits, None);
+            ReleasePipeline::new()
+                .with_plugin(CommitAnalyzerPlugin)
+                .run(&mut self.context, Step::AnalyzeCommits)
+                .await?;
+            self.record_analysis();
+            analyses.push((
+                std::mem::take(&mut self.context.last_tag),
+                std::mem::take(&mut self.context.commits),
+                self.context.release_action,
+            ));
+        }
+
+        // Releases the dependents of the released packages with a patch release
+        let released = packages
+            .iter()
+            .zip(&analyses)
+            .filter(|(_, (_, _, release_action))| release_action.is_some())
+            .map(|(package, _)| package.name.clone())
+            .collect();
+        let dependents = monorepo::dependents(&packages, &released);
+
+        let mut releases = vec![];
+        for (package, (last_tag, commits, release_action)) in packages.iter().zip(analyses) {
+            let release_action = match release_action {
+                Some(release_action) => release_action,
+                None if dependents.contains(&package.name) => ReleaseAction::Patch,
+                None => continue,
+            };
+
+            self.reset_context(package, last_tag, commits, Some(release_action));
+            self.release_pipeline(ReleasePipeline::new(), last_step, changelog_path.clone())
+                .run(&mut self.context, last_step)
+                .await?;
+            releases.extend(self.release());
+        }
+
+        Ok(releases)
+    }
+
+    /// Returns the last release tag among the given tags, or an empty tag if the package has not been released yet.
+    ///
+    /// The tags matching the tag format of the package are sorted by SemVer precedence, leaving out the pre-releases
+    /// unless the released branch is their channel. The last tag is the highest one whose commit is an ancestor of
+    /// the released branch, so that a tag on another branch is not taken for the last release (see [find_last_tag]).
+    async fn last_tag(&self, tags: Vec<RepositoryTag>, package: Option<&str>) -> SleppaResult<RepositoryTag> {
+        let format = TagFormat::try_new(&self.context.configuration.tag_format(package))?;
+        let channel = self.context.configuration.branches.get(&self.context.branch);
+        let branch = &self.context.branch;
+
+        Ok(find_last_tag(self.source.as_ref(), tags, &format, channel.map(String::as_str), branch).await?)
+    }
+
+    /// Appends the versioning, changelog and publication plugins needed until the last step to a pipeline.
+    fn release_pipeline(
+        &self,
+        release_pipeline: ReleasePipeline,
+        last_step: Step,
+        changelog_path: PathBuf,
+    ) -> ReleasePipeline {
+        let mut release_pipeline = release_pipeline.with_plugin(VersionerPlugin::default());
+        // The manifests are bumped before the changelog plugin commits the release, if the repository is a Cargo
+        // package or workspace.
+        let cargo_plugin = CargoPlugin::new();
+        if last_step >= Step::Prepare && cargo_plugin.manifest_path.is_file() {
+            release_pipeline = release_pipeline.with_plugin(cargo_plugin);
+        }
+        release_pipeline = release_pipeline.with_plugin(ChangelogPlugin {
+            changelog_path,
+            ..ChangelogPlugin::new()
+        });
+        if last_step >= Step::Publish && self.context.configuration.publish.is_some() {
+            release_pipeline = release_pipeline.with_plugin(PublisherPlugin::new());
+        }
+        if last_step >= Step::Publish {
+            release_pipeline = match &self.repository {
+                ForgeRepository::Github(repository) => release_pipeline.with_plugin(CodeArchiverPlugin {
+                    release_tag: RepositoryTag::default(),
+                    repository: repository.clone(),
+                    branch: "".to_string(),
+                    pre_release: false,
+                    dry_run: false,
+                }),
+                ForgeRepository::Gitlab(repository) => release_pipeline.with_plugin(GitlabReleasePlugin {
+                    repository: repository.clone(),
+                    dry_run: false,
+                }),
+                ForgeRepository::Gitea(repository) => release_pipeline.with_plugin(GiteaReleasePlugin {
+                    repository: repository.clone(),
+                    pre_release: false,
+                    dry_run: false,
+                }),
+            };
+        }
+        release_pipeline
+    }
+
+    /// Resets the context to release a package from its last tag, its commits and its release action.
+    fn reset_context(
+        &mut self,
+        package: &WorkspacePackage,
+        last_tag: RepositoryTag,
+        commits: Vec<Commit>,
+        release_action: Option<ReleaseAction>,
+    ) {
+        self.context.package = Some(package.name.clone());
+        self.context.last_tag = last_tag;
+        self.context.commits = commits;
+        self.context.release_action = release_action;
+        self.context.new_tag = None;
+        self.context.new_version = None;
+        self.context.notes = String::new();
+        self.context.reports = vec![];
+    }
+
+    /// Records the analysis of the commits of the context.
+    fn record_analysis(&mut self) {
+        let report = CommitAnalyzerPlugin.report(&self.context.commits, &self.context.configuration.release_rules);
+        self.analyses.push(Analysis {
+            package: self.context.package.clone(),
+            report,
+        });
+    }
+
+    /// Returns the release computed in the context, if any.
+    fn release(&self) -> Option<Release> {
+        self.context.new_tag.as_ref().map(|new_tag| Release {
+            new_tag: new_tag.clone(),
+            notes: self.context.notes.clone(),
+            reports: self.context.reports.clone(),
+        })
+    }
+}
+
+impl ForgeRepository {
+    /// Returns the URL of the repository's web page, e.g. `https://github.com/SofairOfficial/sleppa`.
+    pub fn url(&self) -> String {
+        match self {
+            ForgeRepository::Github(repository) => {
+                format!("https://github.com/{}/{}", repository.owner, repository.repo)
+            }
+            ForgeRepository::Gitlab(repository) => format!(
+                "{}/{}",
+                repository.client.base_url.trim_end_matches("/api/v4"),
+                repository.project
+            ),
+            ForgeRepository::Gitea(repository) => format!(
+                "{}/{}/{}",
+                repository.client.base_url.trim_end_matches("/api/v1"),
+                repository.owner,
+                repository.repo
+            ),
+        }
+    }
+}
+
+/// Builds a [GithubRepository] from a repository formed as `owner/name`, e.g. `SofairOfficial/sleppa`.
+pub fn parse_repository(repository: &str) -> SleppaResult<GithubRepository> {
+    match repository.split_once('/') {
+        Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => Ok(GithubRepository {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            ..Default::default()
+        }),
+        _ => Err(SleppaError::InvalidRepository(repository.to_string())),
+    }
+}
+
+/// Verifies the path of a GitLab project, formed as `group/project` or with subgroups, e.g. `group/subgroup/project`.
+pub fn parse_project(project: &str) -> SleppaResult<String> {
+    match project.split('/').collect::<Vec<&str>>() {
+        segments if segments.len() >= 2 && segments.iter().all(|segment| !segment.is_empty()) => {
+            Ok(project.to_string())
+        }
+        _ => Err(SleppaError::InvalidRepository(project.to_string())),
+    }
+}
@@ -0,0 +1,256 @@
+//! Unit tests
+//!
+//! This testing module implements the unit tests for testing the command-line tool.
+
+use super::{errors::*, pipeline::*, *};
+use sleppa_cargo::WorkspacePackage;
+use sleppa_primitives::{plugins::errors::PluginError, Commit};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Write;
+use tempfile::tempdir;
+
+// Tests the parsing of the command-line arguments.
+#[test]
+fn test_can_parse_command_line() -> TestResult<()> {
+    // Execution step
+    let cli = Cli::try_parse_from(["sleppa", "--repository", "SofairOfficial/sleppa", "next-version"])?;
+    let cli_changelog = Cli::try_parse_from([
+        "sleppa",
+        "-c",
+        "config/sleppa.toml",
+        "-r",
+        "SofairOfficial/sleppa",
+        "--local",
+        ".",
+        "--branch",
+        "next",
+        "--dry-run",
+        "--report",
+        "analysis.json",
+        "changelog",
+        "--changelog",
+        "CHANGELOG.md",
+    ])?;
+
+    // Asserts the arguments and their default values are correct
+    assert_eq!(cli.config, PathBuf::from("sleppa.toml"));
+    assert_eq!(cli.repository, "SofairOfficial/sleppa");
+    assert_eq!(cli.command, Command::NextVersion);
+    assert_eq!(cli.local, None);
+    assert!(!cli.dry_run);
+    assert_eq!(cli.report, None);
+    assert_eq!(cli.forge, Forge::Github);
+
+    assert_eq!(cli_changelog.config, PathBuf::from("config/sleppa.toml"));
+    assert_eq!(cli_changelog.local, Some(PathBuf::from(".")));
+    assert_eq!(cli_changelog.branch, "next");
+    assert!(cli_changelog.dry_run);
+    assert_eq!(cli_changelog.report, Some(PathBuf::from("analysis.json")));
+    assert_eq!(
+        cli_changelog.command,
+        Command::Changelog {
+            changelog: PathBuf::from("CHANGELOG.md")
+        }
+    );
+
+    // Asserts the lint subcommand reads from the standard input without repository, or from a tag
+    let cli_lint = Cli::try_parse_from(["sleppa", "lint", "--stdin"])?;
+    assert_eq!(cli_lint.repository, "");
+    assert_eq!(
+        cli_lint.command,
+        Command::Lint {
+            stdin: true,
+            since: None
+        }
+    );
+    assert!(Cli::try_parse_from(["sleppa", "lint", "--stdin", "--since", "v0.1.0"]).is_err());
+
+    // Asserts the backfill subcommand writes the default changelog unless another one is given
+    let cli_backfill = Cli::try_parse_from(["sleppa", "-r", "SofairOfficial/sleppa", "backfill"])?;
+    assert_eq!(
+        cli_backfill.command,
+        Command::Backfill {
+            changelog: PathBuf::from(CHANGELOG_DEFAULT_PATH)
+        }
+    );
+
+    // Asserts the forge can be GitLab
+    let cli_gitlab = Cli::try_parse_from(["sleppa", "--forge", "gitlab", "-r", "group/project", "next-version"])?;
+    assert_eq!(cli_gitlab.forge, Forge::Gitlab);
+    let cli_gitea = Cli::try_parse_from(["sleppa", "--forge", "gitea", "-r", "owner/repo", "next-version"])?;
+    assert_eq!(cli_gitea.forge, Forge::Gitea);
+
+    // Asserts an unknown subcommand is rejected
+    assert!(Cli::try_parse_from(["sleppa", "--repository", "SofairOfficial/sleppa", "deploy"]).is_err());
+
+    Ok(())
+}
+
+// Tests the conversion of a repository formed as `owner/name`.
+#[test]
+fn test_can_parse_repository() -> TestResult<()> {
+    // Execution step
+    let repository = parse_repository("SofairOfficial/sleppa")?;
+
+    // Asserts the owner and the name are correct
+    assert_eq!(repository.owner, "SofairOfficial");
+    assert_eq!(repository.repo, "sleppa");
+
+    // Asserts malformed repositories are rejected
+    assert!(parse_repository("sleppa").is_err());
+    assert!(parse_repository("/sleppa").is_err());
+    assert!(parse_repository("SofairOfficial/").is_err());
+    assert!(parse_repository("SofairOfficial/sleppa/main").is_err());
+
+    // Asserts the GitLab projects can be nested in subgroups
+    assert_eq!(parse_project("group/subgroup/project")?, "group/subgroup/project");
+    assert!(parse_project("project").is_err());
+    assert!(parse_project("group//project").is_err());
+
+    Ok(())
+}
+
+// Tests the exit code returned when the pipeline can't be built.
+#[tokio::test]
+async fn test_can_return_exit_codes() -> TestResult<()> {
+    // Unit test preparation
+    // Builds a correct configuration file for testing purpose, and another one whose tag format misses the version
+    // placeholder.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let incorrect_file_path = tmp_dir.path().join("incorrect.toml");
+    for (path, tag_format) in [(&file_path, "v{version}"), (&incorrect_file_path, "release")] {
+        let mut file = File::create(path)?;
+        writeln!(&mut file, r#"tag_format = "{tag_format}""#)?;
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+    }
+
+    // Execution step
+    let missing_configuration = Pipeline::try_new(
+        &tmp_dir.path().join("missing.toml"),
+        "SofairOfficial/sleppa",
+        None,
+        Forge::Github,
+    );
+    let incorrect_repository = Pipeline::try_new(&file_path, "sleppa", None, Forge::Github);
+    let incorrect_tag_format = Pipeline::try_new(&incorrect_file_path, "SofairOfficial/sleppa", None, Forge::Github);
+
+    // Asserts the exit codes match the failing step
+    assert_eq!(
+        missing_configuration.err().map(|err| err.exit_code()),
+        Some(EXIT_CONFIGURATION)
+    );
+    assert_eq!(
+        incorrect_repository.err().map(|err| err.exit_code()),
+        Some(EXIT_REPOSITORY)
+    );
+    assert_eq!(
+        incorrect_tag_format.err().map(|err| err.exit_code()),
+        Some(EXIT_CONFIGURATION)
+    );
+    assert!(Pipeline::try_new(&file_path, "SofairOfficial/sleppa", None, Forge::Github).is_ok());
+    let gitlab_pipeline = Pipeline::try_new(&file_path, "group/subgroup/project", None, Forge::Gitlab)?;
+    assert!(matches!(gitlab_pipeline.repository, ForgeRepository::Gitlab(_)));
+
+    // Asserts the exit code of a failing plugin matches its step
+    let step_failure = SleppaError::PluginError(PluginError::StepFailure {
+        step: Step::Publish,
+        plugin: "code archiver".to_string(),
+        source: Box::new(PluginError::MissingContext("new tag".to_string())),
+    });
+    assert_eq!(step_failure.exit_code(), EXIT_PUBLICATION);
+
+    // Asserts the exit code of a failing lint
+    assert_eq!(SleppaError::LintFailure(1, 3).exit_code(), EXIT_LINT);
+
+    Ok(())
+}
+
+// Builds the packages of a workspace where `second` depends on `first` and `third` on `second`.
+fn packages_constructor() -> Vec<WorkspacePackage> {
+    vec![
+        WorkspacePackage {
+            name: "first".to_string(),
+            path: "crates/first".to_string(),
+            dependencies: vec![],
+            publish: true,
+        },
+        WorkspacePackage {
+            name: "second".to_string(),
+            path: "crates/second".to_string(),
+            dependencies: vec!["first".to_string()],
+            publish: true,
+        },
+        WorkspacePackage {
+            name: "third".to_string(),
+            path: "crates/first/third".to_string(),
+            dependencies: vec!["second".to_string()],
+            publish: true,
+        },
+        WorkspacePackage {
+            name: "other".to_string(),
+            path: "crates/other".to_string(),
+            dependencies: vec![],
+            publish: true,
+        },
+    ]
+}
+
+// Tests the commits are attributed to the packages by the files they change or by their scope.
+#[test]
+fn test_can_attribute_commits() {
+    // Unit test preparation
+    let packages = packages_constructor();
+    let scopes = HashMap::from([("sec".to_string(), "second".to_string())]);
+    let by_file = Commit::new("1", "fix: correct the parsing").with_files(vec!["crates/first/src/lib.rs".to_string()]);
+    let by_nested_file =
+        Commit::new("2", "fix: correct the parsing").with_files(vec!["crates/first/third/src/lib.rs".to_string()]);
+    let by_scope = Commit::new("3", "feat(second): add a feature");
+    let by_alias = Commit::new("4", "feat(sec): add a feature");
+    let by_prefix = Commit::new("5", "fix: correct the parsing").with_files(vec!["crates/firstly/lib.rs".to_string()]);
+
+    // Asserts a file belongs to the package with the longest matching directory
+    assert!(monorepo::is_attributed(&by_file, &packages[0], &packages, &scopes));
+    assert!(!monorepo::is_attributed(
+        &by_nested_file,
+        &packages[0],
+        &packages,
+        &scopes
+    ));
+    assert!(monorepo::is_attributed(
+        &by_nested_file,
+        &packages[2],
+        &packages,
+        &scopes
+    ));
+
+    // Asserts the scope and its alias give the package
+    assert!(monorepo::is_attributed(&by_scope, &packages[1], &packages, &scopes));
+    assert!(monorepo::is_attributed(&by_alias, &packages[1], &packages, &scopes));
+    assert!(!monorepo::is_attributed(&by_alias, &packages[0], &packages, &scopes));
+
+    // Asserts a directory is not matched as a plain string prefix
+    assert!(monorepo::owner("crates/firstly/lib.rs", &packages).is_none());
+    assert!(!monorepo::is_attributed(&by_prefix, &packages[0], &packages, &scopes));
+}
+
+// Tests the dependents of the released packages are found transitively.
+#[test]
+fn test_can_find_dependents() {
+    // Unit test preparation
+    let packages = packages_constructor();
+
+    // Execution step
+    let from_first = monorepo::dependents(&packages, &HashSet::from(["first".to_string()]));
+    let from_second = monorepo::dependents(&packages, &HashSet::from(["second".to_string(), "third".to_string()]));
+    let from_other = monorepo::dependents(&packages, &HashSet::from(["other".to_string()]));
+
+    // Asserts the dependents are transitive and exclude the released packages
+    assert_eq!(from_first, vec!["second".to_string(), "third".to_string()]);
+    assert!(from_second.is_empty());
+    assert!(from_other.is_empty());
+}
@@ -0,0 +1,45 @@
+/// Enumerates errors that could occur while bumping the Cargo manifests and publishing the crates.
+///
+/// This list is a central structure aiming to define errors that can occur
+/// while reading and writing the manifests and the lock file, and while running cargo.
+#[derive(thiserror::Error, Debug)]
+pub enum CargoError {
+    // Chained I/O errors
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+
+    // Chained errors occurring when parsing a manifest or a lock file
+    #[error(transparent)]
+    TomlError(#[from] toml_edit::TomlError),
+
+    // Chained errors occurring when expanding the workspace members
+    #[error(transparent)]
+    PatternError(#[from] glob::PatternError),
+
+    // Chained errors occurring when reading a workspace member's path
+    #[error(transparent)]
+    GlobError(#[from] glob::GlobError),
+
+    // Chained errors occurring when reading the workspace's metadata or writing a registry index
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+
+    // A cargo command failed while publishing a package, with its error output
+    #[error("The package {0} can't be published : {1}")]
+    PublicationFailure(String, String),
+
+    // The package can't be found in the workspace's metadata
+    #[error("The package {0} is not found in the workspace")]
+    MissingPackage(String),
+
+    // The manifest can't be found
+    #[error("No Cargo manifest found at {0}")]
+    MissingManifest(String),
+}
+
+/// Definition of the cargo plugin result
+pub type CargoResult<R> = Result<R, CargoError>;
+
+#[cfg(test)]
+/// Result type alias returned by function in unit tests.
+pub type TestResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
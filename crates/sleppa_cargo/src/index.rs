@@ -0,0 +1,154 @@
+//! Local registry index
+//!
+//! This module writes the index of a local registry directory, so that the crates copied to it can be used by cargo
+//! with a `local-registry` source. The index follows the layout of the crates.io index : each crate has a file in
+//! `index/`, whose path depends on the length of its name, with a JSON line for each of its versions.
+//!
+//! The entry of a version is built from the package's metadata, given by `cargo metadata`, and from the checksum of
+//! its `.crate` file.
+
+use crate::errors::CargoResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Defines the metadata of a workspace, as given by `cargo metadata`
+#[derive(Debug, Deserialize)]
+pub struct Metadata {
+    /// The packages of the workspace
+    pub packages: Vec<MetadataPackage>,
+}
+
+/// Defines the metadata of a package and its fields
+#[derive(Debug, Deserialize)]
+pub struct MetadataPackage {
+    /// The name of the package
+    pub name: String,
+    /// The version of the package
+    pub version: String,
+    /// The dependencies of the package
+    pub dependencies: Vec<MetadataDependency>,
+    /// The features of the package
+    pub features: BTreeMap<String, Vec<String>>,
+    /// The native library the package links to, if any
+    pub links: Option<String>,
+}
+
+/// Defines the metadata of a dependency and its fields
+#[derive(Debug, Deserialize)]
+pub struct MetadataDependency {
+    /// The package name of the dependency
+    pub name: String,
+    /// The version requirement of the dependency
+    pub req: String,
+    /// The kind of the dependency, `None` for a normal dependency, `dev` or `build` otherwise
+    pub kind: Option<String>,
+    /// The dependency is optional
+    pub optional: bool,
+    /// The default features of the dependency are enabled
+    pub uses_default_features: bool,
+    /// The features enabled for the dependency
+    pub features: Vec<String>,
+    /// The target platform of the dependency, if any
+    pub target: Option<String>,
+    /// The name of the dependency in the manifest, if it is renamed
+    pub rename: Option<String>,
+}
+
+/// Defines an entry of a registry index, i.e. a version of a crate
+#[derive(Debug, Serialize)]
+struct IndexEntry<'a> {
+    /// The name of the crate
+    name: &'a str,
+    /// The version of the crate
+    vers: &'a str,
+    /// The dependencies of the version
+    deps: Vec<IndexDependency<'a>>,
+    /// The SHA256 checksum of the `.crate` file
+    cksum: String,
+    /// The features of the version
+    features: &'a BTreeMap<String, Vec<String>>,
+    /// The version is yanked
+    yanked: bool,
+    /// The native library the crate links to, if any
+    links: Option<&'a str>,
+}
+
+/// Defines a dependency of an index entry
+#[derive(Debug, Serialize)]
+struct IndexDependency<'a> {
+    /// The name of the dependency, which is renamed if `package` is given
+    name: &'a str,
+    /// The version requirement of the dependency
+    req: &'a str,
+    /// The features enabled for the dependency
+    features: &'a [String],
+    /// The dependency is optional
+    optional: bool,
+    /// The default features of the dependency are enabled
+    default_features: bool,
+    /// The target platform of the dependency, if any
+    target: Option<&'a str>,
+    /// The kind of the dependency, `normal` or `build`
+    kind: &'a str,
+    /// The package name of a renamed dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package: Option<&'a str>,
+}
+
+/// Returns the path of the index file of a crate, relative to the `index` directory, e.g. `se/rd/serde`.
+pub fn index_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => Path::new("1").join(&name),
+        2 => Path::new("2").join(&name),
+        3 => Path::new("3").join(&name[..1]).join(&name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+/// Adds the version of a package to the index of a local registry directory.
+///
+/// The `.crate` file of the version must already be in the directory, its checksum being part of the entry. The
+/// development dependencies are left out, as they are not needed to use the crate.
+pub fn write_entry(directory: &Path, package: &MetadataPackage) -> CargoResult<()> {
+    let crate_file = directory.join(format!("{}-{}.crate", package.name, package.version));
+    let cksum = format!("{:x}", Sha256::digest(fs::read(crate_file)?));
+
+    let deps = package
+        .dependencies
+        .iter()
+        .filter(|dependency| dependency.kind.as_deref() != Some("dev"))
+        .map(|dependency| IndexDependency {
+            name: dependency.rename.as_deref().unwrap_or(&dependency.name),
+            req: &dependency.req,
+            features: &dependency.features,
+            optional: dependency.optional,
+            default_features: dependency.uses_default_features,
+            target: dependency.target.as_deref(),
+            kind: dependency.kind.as_deref().unwrap_or("normal"),
+            package: dependency.rename.as_ref().map(|_| dependency.name.as_str()),
+        })
+        .collect();
+    let entry = IndexEntry {
+        name: &package.name,
+        vers: &package.version,
+        deps,
+        cksum,
+        features: &package.features,
+        yanked: false,
+        links: package.links.as_deref(),
+    };
+
+    let path = directory.join("index").join(index_path(&package.name));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+
+    Ok(())
+}
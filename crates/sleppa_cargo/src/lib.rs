@@ -0,0 +1,480 @@
+//! Sleppa cargo package
+//!
+//! This crate bumps the version of the Cargo manifests to the version of a new release, so that the published
+//! crates match the git tags. The following fields are updated :
+//! - the `package.version` field of the manifest,
+//! - the `workspace.package.version` field of a workspace manifest,
+//! - the `package.version` field of every workspace member, unless inherited with `version.workspace = true`,
+//! - the version requirement of the path dependencies between workspace members, e.g.
+//!   `sleppa_primitives = { version = "0.1.0", path = "crates/sleppa_primitives" }`,
+//! - the version of the workspace members in the `Cargo.lock` file, if any.
+//!
+//! The manifests are edited in place, keeping their formatting and their comments intact.
+//!
+//! In a monorepo, where the packages of a workspace are released independently, a single package is bumped, along
+//! with the version requirement of the dependencies on it. The packages of the workspace, their paths and their
+//! dependencies on each other are given by [CargoPlugin::packages]. A package inheriting the workspace's version
+//! can't be released independently, hence its version is left untouched.
+//!
+//! As a [Plugin], it bumps the manifests during the `prepare` step, before the changelog plugin commits the
+//! release. In dry-run mode, the manifests are left untouched.
+//!
+//! The [publisher::PublisherPlugin] publishes the released crates to a Cargo registry during the `publish` step.
+
+pub mod errors;
+pub mod index;
+pub mod publisher;
+
+use async_trait::async_trait;
+use errors::{CargoError, CargoResult};
+use semver::{Comparator, Op, Version};
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item, Value};
+
+/// Tables of a manifest declaring dependencies
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Defines the cargo plugin and its fields
+///
+/// This plugin bumps the version of the manifest found at `manifest_path`, and of its workspace members.
+pub struct CargoPlugin {
+    /// The path of the root manifest, e.g. `Cargo.toml`
+    pub manifest_path: PathBuf,
+    /// The only package to bump in a monorepo, or `None` to bump every package of the workspace
+    pub package: Option<String>,
+    /// In dry-run mode, the manifests are not written
+    pub dry_run: bool,
+}
+
+/// Defines a package of a workspace and its fields
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspacePackage {
+    /// The name of the package, e.g. `sleppa_versioner`
+    pub name: String,
+    /// The directory of the package relative to the workspace's root, e.g. `crates/sleppa_versioner`
+    pub path: String,
+    /// The names of the workspace's packages this package depends on, except for development
+    pub dependencies: Vec<String>,
+    /// The package can be published to a registry, i.e. it is not marked with `publish = false`
+    pub publish: bool,
+}
+
+/// Defines a Cargo manifest and its fields
+///
+/// The manifest is kept as a [Document] to be edited without altering its formatting.
+struct Manifest {
+    /// The path of the manifest
+    path: PathBuf,
+    /// The editable content of the manifest
+    document: Document,
+}
+
+impl CargoPlugin {
+    /// Implementation of the `new` method : `CargoPlugin::new()`.
+    pub fn new() -> Self {
+        CargoPlugin::default()
+    }
+
+    /// Bumps the version of the manifest, of its workspace members and of the lock file
+    ///
+    /// The version is a SemVer version without tag format, e.g. `3.2.1`.
+    /// If a package is set, only this package and the requirements of the dependencies on it are bumped.
+    pub fn bump(&self, version: &str) -> CargoResult<()> {
+        let (root_dir, mut manifests) = self.manifests()?;
+
+        // Names of the packages released together, whose path dependencies must be bumped
+        let names: HashSet<String> = match &self.package {
+            Some(package) => HashSet::from([package.to_string()]),
+            None => manifests.iter().filter_map(|manifest| manifest.name()).collect(),
+        };
+
+        for manifest in manifests.iter_mut() {
+            manifest.bump_version(version, &names, self.package.is_none());
+            manifest.bump_dependencies(version, &names);
+            manifest.save()?;
+        }
+
+        let lock_path = root_dir.join("Cargo.lock");
+        if lock_path.exists() {
+            bump_lock(&lock_path, version, &names)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the packages of the workspace, including the root package if any.
+    pub fn packages(&self) -> CargoResult<Vec<WorkspacePackage>> {
+        let (root_dir, manifests) = self.manifests()?;
+        let names: HashSet<String> = manifests.iter().filter_map(|manifest| manifest.name()).collect();
+
+        let mut packages = vec![];
+        for manifest in manifests.iter() {
+            let name = match manifest.name() {
+                Some(name) => name,
+                None => continue,
+            };
+            let path = match manifest.path.parent().and_then(|dir| dir.strip_prefix(&root_dir).ok()) {
+                Some(path) => path.to_string_lossy().replace('\\', "/"),
+                None => "".to_string(),
+            };
+            let mut dependencies: Vec<String> = manifest
+                .dependencies()
+                .into_iter()
+                .filter(|dependency| names.contains(dependency) && *dependency != name)
+                .collect();
+            dependencies.sort();
+            dependencies.dedup();
+
+            packages.push(WorkspacePackage {
+                name,
+                path,
+                dependencies,
+                publish: manifest.is_published(),
+            });
+        }
+
+        Ok(packages)
+    }
+
+    /// Loads the root manifest and the manifests of its workspace members, returning the workspace's root directory.
+    fn manifests(&self) -> CargoResult<(PathBuf, Vec<Manifest>)> {
+        let root = Manifest::try_load(&self.manifest_path)?;
+        let root_dir = match self.manifest_path.parent() {
+            Some(root_dir) => root_dir.to_path_buf(),
+            None => PathBuf::new(),
+        };
+
+        let mut manifests = root.members(&root_dir)?;
+        manifests.push(root);
+        Ok((root_dir, manifests))
+    }
+}
+
+impl Default for CargoPlugin {
+    /// Creates a cargo plugin for the manifest of the current directory.
+    fn default() -> Self {
+        CargoPlugin {
+            manifest_path: PathBuf::from("Cargo.toml"),
+            package: None,
+            dry_run: false,
+        }
+    }
+}
+
+impl Manifest {
+    /// Loads the manifest at the given path.
+    fn try_load(path: &Path) -> CargoResult<Self> {
+        if !path.is_file() {
+            return Err(CargoError::MissingManifest(path.display().to_string()));
+        }
+
+        Ok(Manifest {
+            path: path.to_path_buf(),
+            document: fs::read_to_string(path)?.parse::<Document>()?,
+        })
+    }
+
+    /// Returns the name of the manifest's package, or `None` for a virtual manifest.
+    fn name(&self) -> Option<String> {
+        self.document
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+            .map(|name| name.to_string())
+    }
+
+    /// Verifies if the manifest's package can be published, i.e. its `publish` field is not `false` nor empty.
+    fn is_published(&self) -> bool {
+        match self.document.get("package").and_then(|package| package.get("publish")) {
+            Some(Item::Value(Value::Boolean(publish))) => *publish.value(),
+            Some(Item::Value(Value::Array(registries))) => !registries.is_empty(),
+            _ => true,
+        }
+    }
+
+    /// Loads the manifests of the workspace members, found in the given root directory.
+    ///
+    /// The members are given as paths or glob patterns, e.g. `crates/*`, and the excluded ones are skipped.
+    fn members(&self, root_dir: &Path) -> CargoResult<Vec<Manifest>> {
+        let workspace = match self.document.get("workspace") {
+            Some(workspace) => workspace,
+            None => return Ok(vec![]),
+        };
+        let paths = |key: &str| -> Vec<&str> {
+            match workspace.get(key).and_then(|paths| paths.as_array()) {
+                Some(paths) => paths.iter().filter_map(|path| path.as_str()).collect(),
+                None => vec![],
+            }
+        };
+        let excluded: Vec<PathBuf> = paths("exclude").iter().map(|path| root_dir.join(path)).collect();
+
+        let mut members = vec![];
+        for pattern in paths("members") {
+            for member_dir in glob::glob(&root_dir.join(pattern).to_string_lossy())? {
+                let member_dir = member_dir?;
+                let manifest_path = member_dir.join("Cargo.toml");
+                if manifest_path.is_file() && !excluded.contains(&member_dir) && manifest_path != self.path {
+                    members.push(Manifest::try_load(&manifest_path)?);
+                }
+            }
+        }
+
+        Ok(members)
+    }
+
+    /// Bumps the `package.version` field if the package is one of the given ones, and the
+    /// `workspace.package.version` field if asked, when defined in this manifest.
+    fn bump_version(&mut self, version: &str, names: &HashSet<String>, with_workspace: bool) {
+        if self.name().map_or(false, |name| names.contains(&name)) {
+            let package_version = self
+                .document
+                .get_mut("package")
+                .and_then(|package| package.get_mut("version"));
+            set_version(package_version, version);
+        }
+
+        if with_workspace {
+            let workspace_version = self
+                .document
+                .get_mut("workspace")
+                .and_then(|workspace| workspace.get_mut("package"))
+                .and_then(|package| package.get_mut("version"));
+            set_version(workspace_version, version);
+        }
+    }
+
+    /// Returns the names of the local packages this manifest depends on, except for development.
+    ///
+    /// The local dependencies are declared with a path, or inherited from the workspace.
+    fn dependencies(&self) -> Vec<String> {
+        let mut tables: Vec<&Item> = vec![];
+        for table in ["dependencies", "build-dependencies"] {
+            tables.extend(self.document.get(table));
+            if let Some(targets) = self.document.get("target").and_then(|targets| targets.as_table_like()) {
+                tables.extend(targets.iter().filter_map(|(_, target)| target.get(table)));
+            }
+        }
+
+        let mut dependencies = vec![];
+        for table in tables.into_iter().filter_map(|table| table.as_table_like()) {
+            for (key, dependency) in table.iter() {
+                let dependency = match dependency.as_table_like() {
+                    Some(dependency) if dependency.contains_key("path") || dependency.contains_key("workspace") => {
+                        dependency
+                    }
+                    _ => continue,
+                };
+                dependencies.push(package_name(key, dependency.get("package")));
+            }
+        }
+        dependencies
+    }
+
+    /// Bumps the version requirement of the path dependencies on the given packages.
+    ///
+    /// The dependencies declared by the workspace, and for a specific target, are bumped as well.
+    fn bump_dependencies(&mut self, version: &str, names: &HashSet<String>) {
+        let mut tables: Vec<&mut Item> = vec![];
+        for (key, item) in self.document.iter_mut() {
+            match key.get() {
+                "workspace" => tables.extend(item.get_mut("dependencies")),
+                "target" => {
+                    if let Some(targets) = item.as_table_like_mut() {
+                        for (_, target) in targets.iter_mut() {
+                            if let Some(target) = target.as_table_like_mut() {
+                                tables.extend(
+                                    target
+                                        .iter_mut()
+                                        .filter(|(key, _)| DEPENDENCY_TABLES.contains(&key.get()))
+                                        .map(|(_, table)| table),
+                                );
+                            }
+                        }
+                    }
+                }
+                key if DEPENDENCY_TABLES.contains(&key) => tables.push(item),
+                _ => (),
+            }
+        }
+
+        for table in tables.into_iter().filter_map(|table| table.as_table_like_mut()) {
+            for (key, dependency) in table.iter_mut() {
+                let dependency = match dependency.as_table_like_mut() {
+                    Some(dependency) if dependency.contains_key("path") => dependency,
+                    _ => continue,
+                };
+
+                let name = package_name(key.get(), dependency.get("package"));
+                if !names.contains(&name) {
+                    continue;
+                }
+
+                if let Some(Item::Value(requirement @ Value::String(_))) = dependency.get_mut("version") {
+                    let bumped = bump_requirement(requirement.as_str().unwrap_or_default(), version);
+                    set_string(requirement, &bumped);
+                }
+            }
+        }
+    }
+
+    /// Writes the manifest back to its path.
+    fn save(&self) -> CargoResult<()> {
+        fs::write(&self.path, self.document.to_string())?;
+        Ok(())
+    }
+}
+
+/// Bumps the version of the given packages in a `Cargo.lock` file.
+///
+/// Only the packages without `source`, i.e. the local ones, are bumped.
+fn bump_lock(lock_path: &Path, version: &str, names: &HashSet<String>) -> CargoResult<()> {
+    let mut document = fs::read_to_string(lock_path)?.parse::<Document>()?;
+
+    if let Some(packages) = document
+        .get_mut("package")
+        .and_then(|packages| packages.as_array_of_tables_mut())
+    {
+        for package in packages.iter_mut() {
+            let is_bumped = !package.contains_key("source")
+                && package
+                    .get("name")
+                    .and_then(|name| name.as_str())
+                    .map_or(false, |name| names.contains(name));
+
+            if let (true, Some(Item::Value(value))) = (is_bumped, package.get_mut("version")) {
+                set_string(value, version);
+            }
+        }
+    }
+
+    fs::write(lock_path, document.to_string())?;
+    Ok(())
+}
+
+/// Sorts the packages of a workspace so that every package comes after the packages it depends on.
+///
+/// The order of the packages is kept as much as possible. A dependency cycle, which cargo rejects, is broken by
+/// keeping the remaining packages in their order.
+pub fn dependency_order(packages: &[WorkspacePackage]) -> Vec<WorkspacePackage> {
+    let mut remaining: Vec<&WorkspacePackage> = packages.iter().collect();
+    let mut sorted = vec![];
+
+    while !remaining.is_empty() {
+        let position = remaining
+            .iter()
+            .position(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .all(|dependency| !remaining.iter().any(|remaining| remaining.name == *dependency))
+            })
+            .unwrap_or(0);
+        sorted.push(remaining.remove(position).clone());
+    }
+
+    sorted
+}
+
+/// Returns the package name of a dependency from its key and its optional `package` field.
+///
+/// A dependency can be renamed, the package name is then given by the `package` field.
+fn package_name(key: &str, package: Option<&Item>) -> String {
+    match package.and_then(|package| package.as_str()) {
+        Some(package) => package.to_string(),
+        None => key.to_string(),
+    }
+}
+
+/// Replaces the version of a requirement, keeping its operator, e.g. from `^0.1` -> `^0.2.0`.
+///
+/// Only the lower bound of a compound requirement is bumped, a strict lower bound becoming an inclusive one so that
+/// the new version is still allowed, e.g. from `> 0.1` -> `>= 0.2.0`. An upper bound is kept, unless it excludes the
+/// new version, in which case it is dropped, e.g. from `>=0.1, <0.3` to `0.3.0` -> `>=0.3.0`. A comparator which
+/// can't be parsed is kept as is.
+fn bump_requirement(requirement: &str, version: &str) -> String {
+    let new_version = match Version::parse(version) {
+        Ok(new_version) => new_version,
+        Err(_) => return version.to_string(),
+    };
+
+    requirement
+        .split(',')
+        .filter_map(|text| {
+            let comparator = match Comparator::parse(text.trim()) {
+                Ok(comparator) => comparator,
+                Err(_) => return Some(text.to_string()),
+            };
+            let operator: String = text
+                .chars()
+                .take_while(|c| matches!(c, '^' | '~' | '=' | '>' | '<' | ' '))
+                .collect();
+            match comparator.op {
+                Op::Less | Op::LessEq if comparator.matches(&new_version) => Some(text.to_string()),
+                Op::Less | Op::LessEq => None,
+                Op::Greater => Some(format!("{}{version}", operator.replacen('>', ">=", 1))),
+                _ => Some(format!("{operator}{version}")),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+        .trim_start()
+        .to_string()
+}
+
+/// Replaces a version field by the given version, if defined.
+///
+/// An inherited version, e.g. `version.workspace = true`, is not a string and is kept.
+fn set_version(field: Option<&mut Item>, version: &str) {
+    if let Some(Item::Value(value @ Value::String(_))) = field {
+        set_string(value, version);
+    }
+}
+
+/// Replaces a string value, keeping its surrounding whitespaces and comments.
+fn set_string(value: &mut Value, content: &str) {
+    let decor = value.decor().clone();
+    *value = Value::from(content);
+    *value.decor_mut() = decor;
+}
+
+#[async_trait]
+impl Plugin for CargoPlugin {
+    fn name(&self) -> &str {
+        "cargo"
+    }
+
+    /// Verifies the manifest exists, unless in dry-run mode.
+    async fn verify_conditions(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run || self.manifest_path.is_file() {
+            return Ok(());
+        }
+
+        Err(PluginError::failure(CargoError::MissingManifest(
+            self.manifest_path.display().to_string(),
+        )))
+    }
+
+    /// Bumps the manifests to the new version, unless in dry-run mode.
+    ///
+    /// In a monorepo, only the released package is bumped.
+    async fn prepare(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run {
+            return Ok(());
+        }
+
+        if context.package.is_some() {
+            self.package = context.package.clone();
+        }
+
+        self.bump(context.new_version()?).map_err(PluginError::failure)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,305 @@
+//! Cargo publisher
+//!
+//! This module publishes the released crates to a Cargo registry, running `cargo publish` for each package of the
+//! workspace, in dependency order so that a package is published after the packages it depends on. The registry is
+//! given by the `[publish]` section of the configuration file :
+//! - crates.io, by default,
+//! - an alternative registry declared in the Cargo configuration, with its name,
+//! - a registry index, with its URL,
+//! - a local registry directory, where the crates packaged with `cargo package` are copied and added to its index
+//!   (see [crate::index]). It can be used as a `local-registry` source, which allows testing the publication without
+//!   uploading anything.
+//!
+//! A version which is already published to the registry is reported as skipped, rather than as a failure, so that
+//! a release can be run again after a later step failed.
+//!
+//! The packages with `publish = false` are never published. In a monorepo, only the released package is published.
+
+use crate::errors::{CargoError, CargoResult};
+use crate::index::{self, Metadata, MetadataPackage};
+use crate::{dependency_order, CargoPlugin};
+use async_trait::async_trait;
+use sleppa_configuration::PublishConfiguration;
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Messages printed by cargo when the version is already published
+const ALREADY_PUBLISHED: [&str; 2] = ["already exists", "already uploaded"];
+
+/// Defines the publisher plugin and its fields
+///
+/// This plugin publishes the packages of the workspace whose root manifest is found at `manifest_path`.
+pub struct PublisherPlugin {
+    /// The path of the root manifest, e.g. `Cargo.toml`
+    pub manifest_path: PathBuf,
+    /// The registry the crates are published to
+    pub registry: Registry,
+    /// In dry-run mode, the crates are not published
+    pub dry_run: bool,
+}
+
+/// Enumerates the registries the crates can be published to.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Registry {
+    /// The default registry
+    #[default]
+    CratesIo,
+    /// An alternative registry declared in the Cargo configuration, given by its name
+    Named(String),
+    /// A registry given by the URL of its index
+    Index(String),
+    /// A local directory where the packaged crates are copied
+    Directory(PathBuf),
+}
+
+/// Enumerates the outcomes of the publication of a package.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Publication {
+    /// The package is published
+    Published,
+    /// The version of the package was already published, hence it is skipped
+    Skipped,
+}
+
+impl PublisherPlugin {
+    /// Implementation of the `new` method : `PublisherPlugin::new()`.
+    pub fn new() -> Self {
+        PublisherPlugin::default()
+    }
+
+    /// Publishes the packages of the workspace at the given version, in dependency order.
+    ///
+    /// If a package is given, only this package is published. The outcome of each publication is returned along
+    /// with the name of its package.
+    pub fn run(&self, package: Option<&str>, version: &str) -> CargoResult<Vec<(String, Publication)>> {
+        let cargo = CargoPlugin {
+            manifest_path: self.manifest_path.clone(),
+            ..CargoPlugin::new()
+        };
+        let packages = dependency_order(&cargo.packages()?);
+
+        let mut publications = vec![];
+        for workspace_package in packages {
+            if !workspace_package.publish || package.map_or(false, |package| package != workspace_package.name) {
+                continue;
+            }
+            let publication = self.publish_package(&workspace_package.name, version)?;
+            publications.push((workspace_package.name, publication));
+        }
+
+        Ok(publications)
+    }
+
+    /// Publishes a package of the workspace at the given version.
+    pub fn publish_package(&self, name: &str, version: &str) -> CargoResult<Publication> {
+        let directory = match &self.registry {
+            Registry::Directory(directory) => directory,
+            registry => return self.upload(name, registry),
+        };
+
+        let crate_file = format!("{name}-{version}.crate");
+        if directory.join(&crate_file).exists() {
+            return Ok(Publication::Skipped);
+        }
+
+        // The crate is packaged along with the workspace's packages it depends on, so that cargo resolves them
+        // without a registry, into the workspace's target directory. It is then copied to the registry directory.
+        let target_dir = self.root_dir().join("target");
+        let mut command = self.cargo(&["package", "--no-verify", "--allow-dirty"]);
+        for package in self.local_dependencies(name)? {
+            command.args(["-p", &package]);
+        }
+        command
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .output()
+            .map_err(CargoError::from)
+            .and_then(|output| check_output(name, &output))?;
+
+        fs::create_dir_all(directory)?;
+        fs::copy(
+            target_dir.join("package").join(&crate_file),
+            directory.join(&crate_file),
+        )?;
+
+        // Adds the version to the registry's index, so that cargo can use the crate from the directory
+        let metadata = self.metadata(name)?;
+        index::write_entry(directory, &metadata)?;
+
+        Ok(Publication::Published)
+    }
+
+    /// Returns the name of a package of the workspace and of the workspace's packages it depends on, directly or not.
+    fn local_dependencies(&self, name: &str) -> CargoResult<Vec<String>> {
+        let cargo = CargoPlugin {
+            manifest_path: self.manifest_path.clone(),
+            ..CargoPlugin::new()
+        };
+        let packages = cargo.packages()?;
+
+        let mut names = vec![name.to_string()];
+        let mut position = 0;
+        while let Some(current) = names.get(position).cloned() {
+            if let Some(package) = packages.iter().find(|package| package.name == current) {
+                for dependency in package.dependencies.iter() {
+                    if !names.contains(dependency) {
+                        names.push(dependency.to_string());
+                    }
+                }
+            }
+            position += 1;
+        }
+
+        Ok(names)
+    }
+
+    /// Returns the metadata of a package of the workspace, given by `cargo metadata`.
+    fn metadata(&self, name: &str) -> CargoResult<MetadataPackage> {
+        let output = self
+            .cargo(&["metadata", "--no-deps", "--format-version", "1"])
+            .output()?;
+        check_output(name, &output)?;
+
+        let metadata: Metadata = serde_json::from_slice(&output.stdout)?;
+        metadata
+            .packages
+            .into_iter()
+            .find(|package| package.name == name)
+            .ok_or_else(|| CargoError::MissingPackage(name.to_string()))
+    }
+
+    /// Uploads a package to a registry with `cargo publish`.
+    fn upload(&self, name: &str, registry: &Registry) -> CargoResult<Publication> {
+        let mut command = self.cargo(&["publish", "-p", name]);
+        match registry {
+            Registry::Named(registry) => command.args(["--registry", registry]),
+            Registry::Index(index) => command.args(["--index", index]),
+            _ => &mut command,
+        };
+
+        let output = command.output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() && ALREADY_PUBLISHED.iter().any(|message| stderr.contains(message)) {
+            return Ok(Publication::Skipped);
+        }
+
+        check_output(name, &output)?;
+        Ok(Publication::Published)
+    }
+
+    /// Builds a cargo command for the workspace, with the given arguments.
+    ///
+    /// The cargo binary running Sleppa is used if known, `cargo` otherwise.
+    fn cargo(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+        command.args(args).arg("--manifest-path").arg(&self.manifest_path);
+        command
+    }
+
+    /// Returns the workspace's root directory.
+    fn root_dir(&self) -> &Path {
+        self.manifest_path.parent().unwrap_or_else(|| Path::new(""))
+    }
+}
+
+/// Verifies a cargo command succeeded, returning its error output otherwise.
+fn check_output(name: &str, output: &std::process::Output) -> CargoResult<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    Err(CargoError::PublicationFailure(
+        name.to_string(),
+        String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    ))
+}
+
+impl Default for PublisherPlugin {
+    /// Creates a publisher plugin for the manifest of the current directory, publishing to crates.io.
+    fn default() -> Self {
+        PublisherPlugin {
+            manifest_path: PathBuf::from("Cargo.toml"),
+            registry: Registry::default(),
+            dry_run: false,
+        }
+    }
+}
+
+impl From<&PublishConfiguration> for Registry {
+    /// Converts the publication configuration to a registry, crates.io if none is given.
+    fn from(configuration: &PublishConfiguration) -> Self {
+        if let Some(registry) = &configuration.registry {
+            Registry::Named(registry.to_string())
+        } else if let Some(index) = &configuration.index {
+            Registry::Index(index.to_string())
+        } else if let Some(directory) = &configuration.directory {
+            Registry::Directory(directory.to_path_buf())
+        } else {
+            Registry::CratesIo
+        }
+    }
+}
+
+impl fmt::Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Registry::CratesIo => write!(f, "crates.io"),
+            Registry::Named(registry) => write!(f, "the {registry} registry"),
+            Registry::Index(index) => write!(f, "the registry of {index}"),
+            Registry::Directory(directory) => write!(f, "{}", directory.display()),
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for PublisherPlugin {
+    fn name(&self) -> &str {
+        "publisher"
+    }
+
+    /// Verifies the manifest exists, unless in dry-run mode.
+    async fn verify_conditions(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run || self.manifest_path.is_file() {
+            return Ok(());
+        }
+
+        Err(PluginError::failure(CargoError::MissingManifest(
+            self.manifest_path.display().to_string(),
+        )))
+    }
+
+    /// Publishes the crates to the configured registry, unless in dry-run mode.
+    ///
+    /// Each publication is reported in the context, as well as the versions already published which are skipped.
+    async fn publish(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if let Some(configuration) = &context.configuration.publish {
+            self.registry = Registry::from(configuration);
+        }
+        let version = context.new_version()?.to_string();
+
+        if self.dry_run || context.dry_run {
+            context.reports.push(format!(
+                "Dry run: version {version} would be published to {}",
+                self.registry
+            ));
+            return Ok(());
+        }
+
+        let publications = self
+            .run(context.package.as_deref(), &version)
+            .map_err(PluginError::failure)?;
+        for (name, publication) in publications {
+            context.reports.push(match publication {
+                Publication::Published => format!("Published {name} {version} to {}", self.registry),
+                Publication::Skipped => format!("Skipped {name} {version}, already published to {}", self.registry),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+//! Unit tests
+//!
+//! This testing module implements the unit tests for bumping the Cargo manifests.
+
+use super::{errors::*, publisher::*, *};
+use semver::VersionReq;
+use std::process::Command;
+use tempfile::{tempdir, TempDir};
+
+// Builds a workspace with two members, a lock file and a manifest outside of the workspace.
+fn workspace_constructor() -> TestResult<TempDir> {
+    let tmp_dir = tempdir()?;
+    fs::create_dir_all(tmp_dir.path().join("crates/first"))?;
+    fs::create_dir_all(tmp_dir.path().join("crates/second"))?;
+    fs::create_dir_all(tmp_dir.path().join("crates/excluded"))?;
+
+    fs::write(
+        tmp_dir.path().join("Cargo.toml"),
+        r#"[workspace]
+members = ["crates/*"]
+exclude = ["crates/excluded"]
+
+# Global package attributes
+[workspace.package]
+version = "0.1.0" # The released version
+edition = "2021"
+
+[workspace.dependencies]
+first = { version = "0.1.0", path = "crates/first" }
+serde = { version = "^1.0" }
+"#,
+    )?;
+    fs::write(
+        tmp_dir.path().join("crates/first/Cargo.toml"),
+        r#"[package]
+name = "first"
+version.workspace = true
+"#,
+    )?;
+    fs::write(
+        tmp_dir.path().join("crates/second/Cargo.toml"),
+        r#"[package]
+name = "second"
+version = "0.1.0"
+publish = false
+
+[dependencies]
+# Local dependencies
+renamed = { package = "first", version = "^0.1", path = "../first" }
+other = { version = "0.3.0", path = "../other" }
+
+[target.'cfg(unix)'.dev-dependencies]
+first = { version = "=0.1.0", path = "../first" }
+"#,
+    )?;
+    fs::write(
+        tmp_dir.path().join("crates/excluded/Cargo.toml"),
+        r#"[package]
+name = "excluded"
+version = "0.1.0"
+"#,
+    )?;
+    fs::write(
+        tmp_dir.path().join("Cargo.lock"),
+        r#"# This file is automatically @generated by Cargo.
+version = 3
+
+[[package]]
+name = "first"
+version = "0.1.0"
+
+[[package]]
+name = "second"
+version = "0.1.0"
+dependencies = [
+ "first",
+]
+
+[[package]]
+name = "first"
+version = "0.1.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+    )?;
+
+    Ok(tmp_dir)
+}
+
+// Tests the manifests of a workspace are bumped, keeping their formatting.
+#[test]
+fn test_can_bump_workspace() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = workspace_constructor()?;
+    let cargo = CargoPlugin {
+        manifest_path: tmp_dir.path().join("Cargo.toml"),
+        ..CargoPlugin::new()
+    };
+
+    // Execution step
+    cargo.bump("0.2.0")?;
+
+    // Asserts the workspace version and the workspace dependency are bumped, keeping the comments
+    let root = fs::read_to_string(tmp_dir.path().join("Cargo.toml"))?;
+    assert!(root.contains(r#"version = "0.2.0" # The released version"#));
+    assert!(root.contains("# Global package attributes"));
+    assert!(root.contains(r#"first = { version = "0.2.0", path = "crates/first" }"#));
+    assert!(root.contains(r#"serde = { version = "^1.0" }"#));
+
+    // Asserts the inherited version is kept
+    let first = fs::read_to_string(tmp_dir.path().join("crates/first/Cargo.toml"))?;
+    assert!(first.contains("version.workspace = true"));
+
+    // Asserts the member version and the path dependencies on members are bumped, keeping their operator
+    let second = fs::read_to_string(tmp_dir.path().join("crates/second/Cargo.toml"))?;
+    assert!(second.contains(r#"version = "0.2.0""#));
+    assert!(second.contains(r#"renamed = { package = "first", version = "^0.2.0", path = "../first" }"#));
+    assert!(second.contains(r#"other = { version = "0.3.0", path = "../other" }"#));
+    assert!(second.contains(r#"first = { version = "=0.2.0", path = "../first" }"#));
+    assert!(second.contains("# Local dependencies"));
+
+    // Asserts the excluded member is not bumped
+    let excluded = fs::read_to_string(tmp_dir.path().join("crates/excluded/Cargo.toml"))?;
+    assert!(excluded.contains(r#"version = "0.1.0""#));
+
+    // Asserts only the local packages are bumped in the lock file
+    let lock = fs::read_to_string(tmp_dir.path().join("Cargo.lock"))?;
+    assert_eq!(lock.matches(r#"version = "0.2.0""#).count(), 2);
+    assert!(lock.contains("version = \"0.1.0\"\nsource = "));
+    assert!(lock.starts_with("# This file is automatically @generated by Cargo."));
+
+    Ok(())
+}
+
+// Tests the lower bound of a requirement is bumped, keeping its operator and the upper bounds allowing the new version.
+#[test]
+fn test_can_bump_requirement() -> TestResult<()> {
+    // Asserts a single comparator is bumped, keeping its operator
+    assert_eq!(bump_requirement("0.1.0", "0.2.0"), "0.2.0");
+    assert_eq!(bump_requirement("^0.1", "0.2.0"), "^0.2.0");
+    assert_eq!(bump_requirement("= 0.1.0", "0.2.0"), "= 0.2.0");
+
+    // Asserts the upper bound of a compound requirement is kept when it allows the new version
+    assert_eq!(bump_requirement(">=0.1, <0.3", "0.2.0"), ">=0.2.0, <0.3");
+
+    // Asserts a strict lower bound becomes an inclusive one
+    assert_eq!(bump_requirement("<= 0.3, > 0.1", "0.2.0"), "<= 0.3, >= 0.2.0");
+
+    // Asserts an upper bound excluding the new version is dropped
+    assert_eq!(bump_requirement(">=0.1, <0.3", "0.3.0"), ">=0.3.0");
+
+    // Asserts every bumped requirement is satisfied by the new version
+    for (requirement, version) in [
+        ("0.1.0", "0.2.0"),
+        ("^0.1", "0.2.0"),
+        ("= 0.1.0", "0.2.0"),
+        (">=0.1, <0.3", "0.2.0"),
+        ("<= 0.3, > 0.1", "0.2.0"),
+        (">=0.1, <0.3", "0.3.0"),
+        ("~0.1.2", "0.1.3"),
+    ] {
+        let bumped = VersionReq::parse(&bump_requirement(requirement, version))?;
+        assert!(bumped.matches(&Version::parse(version)?));
+    }
+
+    Ok(())
+}
+
+// Tests the manifest of a single package is bumped.
+#[test]
+fn test_can_bump_package() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let manifest_path = tmp_dir.path().join("Cargo.toml");
+    fs::write(&manifest_path, "[package]\nname = \"single\"\nversion = \"1.2.3\"\n")?;
+    let cargo = CargoPlugin {
+        manifest_path: manifest_path.clone(),
+        ..CargoPlugin::new()
+    };
+
+    // Execution step
+    cargo.bump("2.0.0-rc.1")?;
+
+    // Asserts the package version is bumped
+    assert_eq!(
+        fs::read_to_string(&manifest_path)?,
+        "[package]\nname = \"single\"\nversion = \"2.0.0-rc.1\"\n"
+    );
+
+    // Asserts a missing manifest is reported
+    let missing = CargoPlugin {
+        manifest_path: tmp_dir.path().join("missing/Cargo.toml"),
+        ..CargoPlugin::new()
+    };
+    assert!(matches!(missing.bump("2.0.0"), Err(CargoError::MissingManifest(_))));
+
+    Ok(())
+}
+
+// Tests the packages of a workspace are listed with their paths and their dependencies on each other.
+#[test]
+fn test_can_list_packages() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = workspace_constructor()?;
+    let cargo = CargoPlugin {
+        manifest_path: tmp_dir.path().join("Cargo.toml"),
+        ..CargoPlugin::new()
+    };
+
+    // Execution step
+    let mut packages = cargo.packages()?;
+    packages.sort_by(|package, other_package| package.name.cmp(&other_package.name));
+
+    // Asserts the excluded member is ignored, and the dependencies outside the workspace too
+    assert_eq!(
+        packages,
+        vec![
+            WorkspacePackage {
+                name: "first".to_string(),
+                path: "crates/first".to_string(),
+                dependencies: vec![],
+                publish: true,
+            },
+            WorkspacePackage {
+                name: "second".to_string(),
+                path: "crates/second".to_string(),
+                dependencies: vec!["first".to_string()],
+                publish: false,
+            },
+        ]
+    );
+
+    Ok(())
+}
+
+// Tests a single package of a workspace is bumped, along with the requirements of the dependencies on it.
+#[test]
+fn test_can_bump_single_package() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = workspace_constructor()?;
+    let cargo = CargoPlugin {
+        manifest_path: tmp_dir.path().join("Cargo.toml"),
+        package: Some("first".to_string()),
+        ..CargoPlugin::new()
+    };
+
+    // Execution step
+    cargo.bump("0.1.1")?;
+
+    // Asserts the workspace version is kept and the requirements on the package are bumped
+    let root = fs::read_to_string(tmp_dir.path().join("Cargo.toml"))?;
+    assert!(root.contains(r#"version = "0.1.0" # The released version"#));
+    assert!(root.contains(r#"first = { version = "0.1.1", path = "crates/first" }"#));
+
+    // Asserts the other package's version is kept
+    let second = fs::read_to_string(tmp_dir.path().join("crates/second/Cargo.toml"))?;
+    assert!(second.contains("name = \"second\"\nversion = \"0.1.0\""));
+    assert!(second.contains(r#"renamed = { package = "first", version = "^0.1.1", path = "../first" }"#));
+
+    // Asserts only the package is bumped in the lock file
+    let lock = fs::read_to_string(tmp_dir.path().join("Cargo.lock"))?;
+    assert!(lock.contains("name = \"first\"\nversion = \"0.1.1\""));
+    assert!(lock.contains("name = \"second\"\nversion = \"0.1.0\""));
+
+    Ok(())
+}
+
+// Tests the packages are sorted so that every package comes after its dependencies.
+#[test]
+fn test_can_sort_packages_by_dependencies() {
+    // Unit test preparation
+    let package = |name: &str, dependencies: &[&str]| WorkspacePackage {
+        name: name.to_string(),
+        path: format!("crates/{name}"),
+        dependencies: dependencies.iter().map(|dependency| dependency.to_string()).collect(),
+        publish: true,
+    };
+    let packages = vec![
+        package("cli", &["versioner", "primitives"]),
+        package("versioner", &["primitives", "serde"]),
+        package("changelog", &[]),
+        package("primitives", &[]),
+    ];
+
+    // Execution step
+    let sorted = dependency_order(&packages);
+
+    // Asserts the dependencies come first, the other packages keeping their order
+    let names: Vec<&str> = sorted.iter().map(|package| package.name.as_str()).collect();
+    assert_eq!(names, vec!["changelog", "primitives", "versioner", "cli"]);
+}
+
+// Tests the crates are published to a local registry directory, skipping the versions already published.
+#[test]
+fn test_can_publish_to_directory() -> TestResult<()> {
+    // Unit test preparation
+    // Builds a workspace with a package depending on another one, and a package which can't be published.
+    let tmp_dir = tempdir()?;
+    let registry_dir = tmp_dir.path().join("registry");
+    fs::write(
+        tmp_dir.path().join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )?;
+    for (name, extra) in [
+        (
+            "second",
+            "\n[dependencies]\nfirst = { version = \"0.2.0\", path = \"../first\" }\n",
+        ),
+        ("first", ""),
+        ("private", "publish = false\n"),
+    ] {
+        fs::create_dir_all(tmp_dir.path().join(format!("crates/{name}/src")))?;
+        fs::write(
+            tmp_dir.path().join(format!("crates/{name}/Cargo.toml")),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.2.0\"\nedition = \"2021\"\n{extra}"),
+        )?;
+        fs::write(tmp_dir.path().join(format!("crates/{name}/src/lib.rs")), "")?;
+    }
+    let publisher = PublisherPlugin {
+        manifest_path: tmp_dir.path().join("Cargo.toml"),
+        registry: Registry::Directory(registry_dir.clone()),
+        ..PublisherPlugin::new()
+    };
+
+    // Builds a crate out of the workspace, using the registry directory as its registry and depending on the
+    // published package.
+    let consumer_dir = tmp_dir.path().join("consumer");
+    fs::create_dir_all(consumer_dir.join(".cargo"))?;
+    fs::create_dir_all(consumer_dir.join("src"))?;
+    fs::write(
+        consumer_dir.join(".cargo/config.toml"),
+        format!(
+            "[source.crates-io]\nreplace-with = \"local\"\n\n[source.local]\nlocal-registry = {:?}\n",
+            registry_dir.display().to_string()
+        ),
+    )?;
+    fs::write(
+        consumer_dir.join("Cargo.toml"),
+        r#"[package]
+name = "consumer"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+second = "0.2"
+
+[workspace]
+"#,
+    )?;
+    fs::write(consumer_dir.join("src/lib.rs"), "")?;
+
+    // Execution step
+    let publications = publisher.run(None, "0.2.0")?;
+    let republications = publisher.run(Some("first"), "0.2.0")?;
+    let fetch = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+        .args(["fetch", "--offline"])
+        .current_dir(&consumer_dir)
+        .output()?;
+
+    // Asserts only the publishable packages are packaged into the registry directory, in dependency order
+    assert_eq!(
+        publications,
+        vec![
+            ("first".to_string(), Publication::Published),
+            ("second".to_string(), Publication::Published)
+        ]
+    );
+    assert!(registry_dir.join("first-0.2.0.crate").is_file());
+    assert!(registry_dir.join("second-0.2.0.crate").is_file());
+    assert!(!registry_dir.join("private-0.2.0.crate").exists());
+
+    // Asserts the index lists the published versions, along with their dependencies
+    let first_index = fs::read_to_string(registry_dir.join("index/fi/rs/first"))?;
+    let second_index = fs::read_to_string(registry_dir.join("index/se/co/second"))?;
+    assert_eq!(first_index.lines().count(), 1);
+    assert!(first_index.contains("\"name\":\"first\",\"vers\":\"0.2.0\",\"deps\":[]"));
+    assert!(second_index.contains("\"deps\":[{\"name\":\"first\",\"req\":\"^0.2.0\""));
+
+    // Asserts the version already published is skipped
+    assert_eq!(republications, vec![("first".to_string(), Publication::Skipped)]);
+
+    // Asserts cargo resolves and unpacks the published crates from the registry directory
+    assert!(fetch.status.success(), "{}", String::from_utf8_lossy(&fetch.stderr));
+    let lock = fs::read_to_string(consumer_dir.join("Cargo.lock"))?;
+    assert!(lock.contains("name = \"first\"\nversion = \"0.2.0\""));
+    assert!(lock.contains("name = \"second\"\nversion = \"0.2.0\""));
+
+    Ok(())
+}
@@ -1,4 +1,13 @@
-/// This module regroups all the constants used in the `sleppa_changelog` crate.
+//! This module regroups all the constants used in the `sleppa_changelog` crate.
 
 /// The default path for the changelog file.
 pub const CHANGELOG_DEFAULT_PATH: &str = "changelogs/CHANGELOG.md";
+
+/// The marker of a changelog file below which the new releases are inserted, its header being kept above.
+pub const INSERT_MARKER: &str = "<!-- sleppa:insert -->";
+
+/// The beginning of a release heading in a changelog file, e.g. `## [v4.0.0]`.
+pub const RELEASE_HEADING: &str = "## ";
+
+/// The title of the section of a changelog file listing the unreleased changes, e.g. `## [Unreleased]`.
+pub const UNRELEASED_TITLE: &str = "Unreleased";
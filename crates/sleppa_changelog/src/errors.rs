@@ -1,3 +1,5 @@
+use sleppa_primitives::repositories::errors::RepositoryError;
+
 /// Enumerates errors that could occur when generating changelog file.
 ///
 /// This list is a central structure aiming to define errors that can occur
@@ -15,6 +17,14 @@ pub enum ChangelogError {
     /// Chained time format error
     #[error(transparent)]
     InvalidFormat(#[from] time::error::Format),
+
+    /// Chained template errors, when the template is incorrect or fails to render
+    #[error(transparent)]
+    TemplateError(#[from] minijinja::Error),
+
+    /// Chained repository errors, when the tags or the commits of the history can't be read
+    #[error(transparent)]
+    RepositoryError(#[from] RepositoryError),
 }
 
 /// Result type alias returned by function.
@@ -8,10 +8,15 @@
 //! will be written.
 //! These sections represent the sorted type of commit.
 //!
-//! The changelog file looks like :
+//! The sections are titled, ordered and hidden by the `[changelog.sections]` table of the configuration file, the
+//! commit types being listed in alphabetical order by default. The breaking changes are always listed first, in
+//! their own section. The entries of each section can also be grouped by scope, as sub-bullets of the section.
+//!
+//! The release is rendered by a template (see the [template] module). With the built-in template, the changelog file
+//! looks like :
 //!
 //!```toml
-//! ### [v4.0.0](https://github.com/user/repo/compare/v3.2.1..v4.0.0) (2023-05-05)
+//! ## [v4.0.0](https://github.com/user/repo/compare/v3.2.1..v4.0.0) (2023-05-05)
 //!
 //! * **break**
 //!  * new breaking ([1ebdf43e](https://github.com/user/repo/commit/1ebdf43e8950d8f9dace2e554be5d387267575ef))
@@ -22,28 +27,49 @@
 //!  * new patch ([cd2fe770](https://github.com/user/repo/commit/cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a))
 //!```
 //!
+//! The new release is inserted below the header of an existing changelog file, i.e. below the `<!-- sleppa:insert -->`
+//! marker or else before the first release heading starting with `## `. The file is written atomically, through a
+//! temporary file renamed over it.
+//!
 //! While the file is written, it has to be automatically commited to the reposiroty with a message : `Release v4.0.0`
 //! where `v4.0.0` is the new tag.
+//!
+//! A project adopting Sleppa can regenerate its whole changelog from its tag history (see [ChangelogPlugin::backfill]),
+//! each release being dated with the date of its tag's commit.
+//!
+//! In dry-run mode, the changelog is only rendered in memory, leaving the filesystem and the repository untouched.
+//!
+//! As a [Plugin], it renders the release notes during the `generate_notes` step, then writes and commits the
+//! changelog file during the `prepare` step.
 
-mod constants;
-mod errors;
+pub mod constants;
+pub mod errors;
+pub mod template;
 
-use constants::CHANGELOG_DEFAULT_PATH;
+use async_trait::async_trait;
+use constants::{CHANGELOG_DEFAULT_PATH, INSERT_MARKER, RELEASE_HEADING, UNRELEASED_TITLE};
 use errors::{ChangelogError, ChangelogResult};
+use sleppa_configuration::{constants::VERSION_PLACEHOLDER, Configuration, ReleaseRuleHandler, SectionsConfiguration};
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
+use sleppa_primitives::repositories::{Repository, RepositoryTag};
 use sleppa_primitives::Commit;
 use std::collections::BTreeMap;
-use std::fs::{create_dir_all, File};
+use std::fs::{self, create_dir_all, File};
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use time::{format_description, OffsetDateTime};
+use template::{verify_template, TemplateRelease, DEFAULT_TEMPLATE};
+use time::macros::format_description;
+use time::OffsetDateTime;
 
 /// Defines the Changelog and its fields.
 ///
 /// Changelog structure contains mandatory elements to create the file, namely, the map between commit type and
 /// commit messages, the last tag, the new tag and the URL of the repository.
 /// The URL is used to write hlink in the changelog file, therefore using a String here is sufficient.
-#[derive(Default)]
 pub struct ChangelogPlugin {
     /// Sections are represented by the commit's type (the keys) associated with their [Commit]s (the value).
     /// As the order of the key is important, a [BTreeMap] is needed here.
@@ -52,8 +78,41 @@ pub struct ChangelogPlugin {
     pub last_tag: String,
     /// The repository's new tag
     pub new_tag: String,
+    /// The previous version, e.g. `3.2.1`, the previous tag being used if empty
+    pub last_version: String,
+    /// The new version, e.g. `4.0.0`, the new tag being used if empty
+    pub new_version: String,
+    /// The released package in a monorepo, or `None` for the whole repository
+    pub package: Option<String>,
     /// The repository's URL like `https://github.com/USER/REPO`
     pub repo_url: String,
+    /// In dry-run mode, the changelog is rendered without writing nor committing the file
+    pub dry_run: bool,
+    /// The path of the changelog file written when the plugin runs in a release pipeline
+    pub changelog_path: PathBuf,
+    /// The template rendering the release, the built-in [DEFAULT_TEMPLATE] by default
+    pub template: String,
+    /// The titles, the order and the hidden types of the sections, the types in alphabetical order by default
+    pub sections_configuration: SectionsConfiguration,
+}
+
+impl Default for ChangelogPlugin {
+    /// Creates an empty changelog plugin writing to the default changelog path.
+    fn default() -> Self {
+        ChangelogPlugin {
+            sections: BTreeMap::new(),
+            last_tag: "".to_string(),
+            new_tag: "".to_string(),
+            last_version: "".to_string(),
+            new_version: "".to_string(),
+            package: None,
+            repo_url: "".to_string(),
+            dry_run: false,
+            changelog_path: PathBuf::from(CHANGELOG_DEFAULT_PATH),
+            template: DEFAULT_TEMPLATE.to_string(),
+            sections_configuration: SectionsConfiguration::default(),
+        }
+    }
 }
 
 impl ChangelogPlugin {
@@ -93,21 +152,23 @@ impl ChangelogPlugin {
     /// Saves the changelog file contents to the filesystem
     ///
     /// This function writes the changelog file to a provided path from a configuration file.
-    /// It creates the file if it doesn't exist. It appends the new log to the file if it already exists.
+    /// It creates the file if it doesn't exist. It inserts the new log below the header of the file if it already
+    /// exists (see [insert_release]).
     /// The log are written in a reverse chronological order, hence the most recent at the top.
-    fn serialize(&self, changelog_path: &Path) -> ChangelogResult<()> {
+    ///
+    /// The release is the one already rendered by [ChangelogPlugin::render].
+    /// The file is written atomically, so that a failure never truncates the existing changelog.
+    fn serialize(&self, changelog_path: &Path, release: &str) -> ChangelogResult<()> {
         // Loads the path from the configuration file
         let path = Path::new(changelog_path);
 
-        let mut file: File;
         // Creates a buffer to keep the existing changelog
         let mut buffer = String::new();
 
         match path.try_exists() {
             Ok(true) => {
                 // The file exists: reads it and stores its content.
-                file = File::open(path)?;
-                file.read_to_string(&mut buffer)?;
+                File::open(path)?.read_to_string(&mut buffer)?;
             }
             Ok(false) => {
                 // The file doesn't exist: creates directory according the provided path.
@@ -116,41 +177,89 @@ impl ChangelogPlugin {
             Err(err) => return Err(ChangelogError::IoError(err)),
         }
 
-        // Creates or opens the file (overwrite mod) to write on it
-        file = File::create(path)?;
+        // Inserts the new release below the header of the previous changelog
+        let changelog = insert_release(&buffer, release);
 
-        // Writes the new tag and its link to compare repository between new and last tags
-        let version_tag = format!(
-            "[{}]({}/compare/{}..{})",
-            self.new_tag, self.repo_url, self.last_tag, self.new_tag,
-        );
-        let version_text = format!("## {version_tag}");
+        write_atomically(path, &changelog)
+    }
+
+    /// Renders the changelog of the new release
+    ///
+    /// The release is rendered in memory by the plugin's template. With the built-in template, it is rendered as a
+    /// header, made of the new tag, its compare link and the date, followed by one section per commit type.
+    fn render(&self) -> ChangelogResult<String> {
+        self.render_dated(OffsetDateTime::now_utc())
+    }
 
+    /// Renders the changelog of a release made at the given date
+    fn render_dated(&self, date: OffsetDateTime) -> ChangelogResult<String> {
         // Creates the date like `2023-02-01`
-        let now = OffsetDateTime::now_utc();
-        let date_format = format_description::parse("[year]-[month]-[day]")?;
-        let date = now.format(&date_format)?;
+        let date_format = format_description!("[year]-[month]-[day]");
+        let date = date.format(&date_format)?;
 
-        // Writes the tag, its compare link and the date as header.
-        writeln!(&mut file, "{version_text} ({date})\n",)?;
+        let or_tag = |version: &str, tag: &str| match version {
+            "" => tag.to_string(),
+            version => version.to_string(),
+        };
+        let release = TemplateRelease {
+            version: or_tag(&self.new_version, &self.new_tag),
+            previous_version: or_tag(&self.last_version, &self.last_tag),
+            tag: self.new_tag.clone(),
+            previous_tag: self.last_tag.clone(),
+            date,
+            repository_url: self.repo_url.clone(),
+            package: self.package.clone(),
+            ..Default::default()
+        };
 
-        // Loops over [ChangelogPlugin]'s sections field to write the file
-        for (commit_type, commits) in &self.sections {
-            writeln!(&mut file, "* **{commit_type}**")?;
+        release
+            .with_sections(self.ordered_sections(), &self.sections_configuration)
+            .render(&self.template)
+    }
 
-            for commit in commits {
-                let hash = &commit.hash;
-                let link = format!("{}/commit/{}", self.repo_url, hash);
-                writeln!(&mut file, " * {} ([{}]({}))", commit.message, &commit.hash[0..8], link)?;
-            }
-        }
+    /// Returns the sections of the release with their title and their commit type, in order
+    ///
+    /// The breaking changes are listed first in their own section, whose commit type is empty. The sections of the
+    /// types given by the configuration's order follow, then the other sections in alphabetical order. The sections of
+    /// the hidden types are left out, but not their breaking changes.
+    fn ordered_sections(&self) -> Vec<(String, String, Vec<&Commit>)> {
+        let configuration = &self.sections_configuration;
+        let ordered = configuration
+            .order
+            .iter()
+            .filter_map(|commit_type| self.sections.get_key_value(commit_type));
+        let others = self
+            .sections
+            .iter()
+            .filter(|(commit_type, _)| !configuration.order.contains(commit_type));
+        let ordered: Vec<(&String, &Vec<Commit>)> = ordered.chain(others).collect();
 
-        writeln!(&mut file, "\n")?;
+        let mut sections = vec![];
+        let breaking_changes: Vec<&Commit> = ordered
+            .iter()
+            .flat_map(|(_, commits)| commits.iter().filter(|commit| commit.breaking))
+            .collect();
+        if !breaking_changes.is_empty() {
+            sections.push((configuration.breaking_title.clone(), "".to_string(), breaking_changes));
+        }
 
-        // Appends the previous changelog to the file
-        file.write_all(buffer.as_bytes())?;
+        for (commit_type, commits) in ordered {
+            let commits: Vec<&Commit> = commits.iter().filter(|commit| !commit.breaking).collect();
+            if commits.is_empty() || configuration.hidden.contains(commit_type) {
+                continue;
+            }
+            let title = configuration.titles.get(commit_type).unwrap_or(commit_type);
+            sections.push((title.clone(), commit_type.clone(), commits));
+        }
+        sections
+    }
 
-        Ok(())
+    /// Loads the template file, whose syntax is verified.
+    pub fn with_template_file(&mut self, template_path: &Path) -> ChangelogResult<&Self> {
+        let template = fs::read_to_string(template_path)?;
+        verify_template(&template)?;
+        self.template = template;
+        Ok(self)
     }
 
     /// Commits the new changelog file and the new tag
@@ -184,6 +293,9 @@ impl ChangelogPlugin {
     /// provided path.
     /// The file is written using the commits messages as source of information. The changelog groups the
     /// commits using their type.
+    ///
+    /// The rendered changelog of the new release is returned. In dry-run mode, it is only rendered in memory:
+    /// the file is neither written nor committed.
     pub fn run(
         &mut self,
         changelog_path: &Path,
@@ -191,17 +303,239 @@ impl ChangelogPlugin {
         commits: Vec<Commit>,
         last_tag: &str,
         new_tag: &str,
-    ) -> ChangelogResult<()> {
+    ) -> ChangelogResult<String> {
         // Builds the [ChangelogPlugin] from commits, last tag and new tag
         self.with_commits(commits, last_tag, new_tag, repo_url);
 
+        // Renders the new release in memory
+        let release = self.render()?;
+        if self.dry_run {
+            return Ok(release);
+        }
+
         // Creates the changelog file
-        self.serialize(changelog_path)?;
+        self.serialize(changelog_path, &release)?;
 
         // Commits the changelog file to the repository
         self.commit_changelog()?;
+
+        Ok(release)
+    }
+
+    /// Regenerates the whole changelog from the history of the given release tags
+    ///
+    /// The tags are given from the oldest to the newest. For each tag, the commits since the previous tag are read
+    /// from the repository and classified with the release rules of the configuration, and the release is rendered
+    /// with the date of the tag's commit. The changelog lists the releases from the newest to the oldest, below the
+    /// header of the existing changelog file if any (see [insert_release]), and replaces the changelog file, unless
+    /// in dry-run mode. It is returned.
+    ///
+    /// The tags are the ones of the whole repository, the packages of a monorepo not being regenerated.
+    pub async fn backfill(
+        &mut self,
+        repository: &(dyn Repository + Send + Sync),
+        tags: &[RepositoryTag],
+        configuration: &Configuration,
+        changelog_path: &Path,
+        repo_url: &str,
+    ) -> ChangelogResult<String> {
+        if let Some(template_path) = &configuration.changelog.template {
+            self.with_template_file(template_path)?;
+        }
+        self.sections_configuration = configuration.changelog.sections.clone();
+        let tag_format = configuration.tag_format(None);
+
+        let mut releases = vec![];
+        let mut previous_tag = RepositoryTag::default();
+        for tag in tags {
+            let commits = repository.get_inner_commits_between(&previous_tag, tag).await?;
+
+            self.sections = BTreeMap::new();
+            self.with_commits(
+                release_commits(&commits, configuration),
+                &previous_tag.identifier,
+                &tag.identifier,
+                repo_url,
+            );
+            self.last_version = strip_tag_format(&previous_tag.identifier, &tag_format);
+            self.new_version = strip_tag_format(&tag.identifier, &tag_format);
+            releases.push(self.render_dated(repository.get_tag_date(tag).await?)?);
+
+            previous_tag = tag.clone();
+        }
+
+        // Keeps the header of the existing changelog above the regenerated releases
+        let existing = match changelog_path.try_exists()? {
+            true => fs::read_to_string(changelog_path)?,
+            false => String::new(),
+        };
+        let header = &existing[..insert_position(&existing)];
+        let releases: String = releases.into_iter().rev().collect();
+        let changelog = below_header(header, &releases);
+
+        if !self.dry_run {
+            create_dir_all(changelog_path.parent().unwrap_or(Path::new(CHANGELOG_DEFAULT_PATH)))?;
+            write_atomically(changelog_path, &changelog)?;
+        }
+        Ok(changelog)
+    }
+}
+
+#[async_trait]
+impl Plugin for ChangelogPlugin {
+    fn name(&self) -> &str {
+        "changelog"
+    }
+
+    /// Loads the template file given by the configuration, if any, so that an incorrect template is reported before
+    /// the release.
+    async fn verify_conditions(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if let Some(template_path) = &context.configuration.changelog.template {
+            self.with_template_file(template_path).map_err(PluginError::failure)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the changelog of the new release as release notes.
+    ///
+    /// Only the commits matching a release rule, or introducing a breaking change, are kept. The versions are the
+    /// tags without their tag format, e.g. `4.0.0` for `v4.0.0`.
+    async fn generate_notes(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        self.with_commits(
+            release_commits(&context.commits, &context.configuration),
+            &context.last_tag.identifier,
+            context.new_tag()?,
+            &context.repository_url,
+        );
+        let tag_format = context.configuration.tag_format(context.package.as_deref());
+        self.last_version = strip_tag_format(&context.last_tag.identifier, &tag_format);
+        self.new_version = strip_tag_format(context.new_tag()?, &tag_format);
+        self.package = context.package.clone();
+        self.sections_configuration = context.configuration.changelog.sections.clone();
+        context.notes = self.render().map_err(PluginError::failure)?;
         Ok(())
     }
+
+    /// Writes the release notes rendered by [ChangelogPlugin::generate_notes] to the changelog file and commits it,
+    /// unless in dry-run mode.
+    async fn prepare(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run {
+            return Ok(());
+        }
+
+        self.serialize(&self.changelog_path, &context.notes)
+            .map_err(PluginError::failure)?;
+        self.commit_changelog().map_err(PluginError::failure)?;
+        Ok(())
+    }
+}
+
+/// Returns the commits matching a release rule of the configuration, or introducing a breaking change.
+///
+/// As in the commit analyzer, the release rules are matched against the header of the commits.
+fn release_commits(commits: &[Commit], configuration: &Configuration) -> Vec<Commit> {
+    commits
+        .iter()
+        .filter(|commit| {
+            commit.breaking
+                || configuration
+                    .release_rules
+                    .values()
+                    .any(|release_rule| release_rule.handle(commit.header()).is_ok())
+        })
+        .cloned()
+        .collect()
+}
+
+/// Inserts a release into a changelog, below its header
+///
+/// The release is inserted right below the line of the [INSERT_MARKER] if the changelog has one, so that the marker
+/// and everything above it, e.g. a title, an introduction or an `Unreleased` section, stay at the top. Otherwise the
+/// release is inserted before the first release heading, i.e. the first line starting with `## ` which isn't the
+/// heading of an `Unreleased` section. A changelog with neither, e.g. a new changelog with no release yet, has no
+/// header : the release is inserted at its top, or below its `Unreleased` section, unless a marker is written below
+/// its header.
+pub fn insert_release(changelog: &str, release: &str) -> String {
+    let (header, releases) = changelog.split_at(insert_position(changelog));
+    below_header(header, release) + releases
+}
+
+/// Appends releases to the header of a changelog, on a new line
+fn below_header(header: &str, releases: &str) -> String {
+    let separator = match header.is_empty() || header.ends_with('\n') {
+        true => "",
+        false => "\n",
+    };
+    format!("{header}{separator}{releases}")
+}
+
+/// Returns the position in a changelog where a release is inserted, i.e. the end of its header
+///
+/// See [insert_release] for how the header is delimited.
+fn insert_position(changelog: &str) -> usize {
+    let mut offset = 0;
+    let mut position = None;
+    let mut has_unreleased = false;
+    for line in changelog.split_inclusive('\n') {
+        offset += line.len();
+        if line.contains(INSERT_MARKER) {
+            return offset;
+        }
+        if position.is_none() {
+            if let Some(title) = line.strip_prefix(RELEASE_HEADING) {
+                let title = title.trim().trim_start_matches('[').trim_end_matches(']');
+                match title.eq_ignore_ascii_case(UNRELEASED_TITLE) {
+                    true => has_unreleased = true,
+                    false => position = Some(offset - line.len()),
+                }
+            }
+        }
+    }
+
+    match (position, has_unreleased) {
+        (Some(position), _) => position,
+        (None, true) => changelog.len(),
+        (None, false) => 0,
+    }
+}
+
+/// Writes a file atomically
+///
+/// The contents are written to a temporary file next to the file, which is then given the permissions of the file and
+/// renamed over it. If the writing fails, the temporary file is removed and the file is left untouched.
+fn write_atomically(path: &Path, contents: &str) -> ChangelogResult<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temporary_path = path.with_file_name(format!(".{file_name}.tmp"));
+
+    let written = File::create(&temporary_path)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|_| match fs::metadata(path) {
+            // The permissions of an existing file are kept, the temporary file being created with the default ones.
+            Ok(metadata) => fs::set_permissions(&temporary_path, metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|_| fs::rename(&temporary_path, path));
+    if let Err(err) = written {
+        let _ = fs::remove_file(&temporary_path);
+        return Err(ChangelogError::IoError(err));
+    }
+    Ok(())
+}
+
+/// Returns the version of a tag, without the text surrounding the `{version}` placeholder of its tag format.
+///
+/// The tag is returned as is if it doesn't match the tag format.
+fn strip_tag_format(tag: &str, tag_format: &str) -> String {
+    match tag_format.split_once(VERSION_PLACEHOLDER) {
+        Some((prefix, suffix)) => match tag.strip_prefix(prefix).and_then(|tag| tag.strip_suffix(suffix)) {
+            Some(version) => version.to_string(),
+            None => tag.to_string(),
+        },
+        None => tag.to_string(),
+    }
 }
 
 #[cfg(test)]
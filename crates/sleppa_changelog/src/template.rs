@@ -0,0 +1,264 @@
+//! Changelog templates
+//!
+//! The release notes are rendered by a [MiniJinja](https://docs.rs/minijinja) template, with loops and conditionals
+//! over the release. The built-in template [DEFAULT_TEMPLATE] renders the default layout, and another template file
+//! can be given in the `[changelog]` section of the configuration file.
+//!
+//! The template is rendered with its block tags trimmed, i.e. the newline following a block tag like `{% for %}`
+//! and the whitespaces preceding it on its line are removed, so that a template can be written line by line. The
+//! values are not escaped, as the changelog is written in Markdown.
+//!
+//! The following variables are available in the template :
+//! - `version` and `previous_version`, the new version and the previous one, e.g. `4.0.0` and `3.2.1`,
+//! - `tag` and `previous_tag`, the new tag and the previous one, e.g. `v4.0.0` and `v3.2.1`,
+//! - `date`, the release date, e.g. `2023-05-05`,
+//! - `repository_url`, the repository's URL, e.g. `https://github.com/user/repo`,
+//! - `package`, the released package in a monorepo, or none for the whole repository,
+//! - `sections`, the commits grouped by type, each section having a `title`, a `commit_type`, its `commits` and its
+//!   `groups`, i.e. its commits grouped by `scope`. The breaking changes are listed first in their own section, whose
+//!   `commit_type` is empty,
+//! - `group_by_scope`, the entries of the sections are grouped by scope,
+//! - `commits`, all the released commits,
+//! - `scopes`, the scopes of the commits, with their aliases merged, sorted and deduplicated,
+//! - `breaking_changes`, the commits introducing a breaking change,
+//! - `contributors`, the authors and co-authors of the commits, sorted and deduplicated.
+//!
+//! Every commit has a `hash`, a `short_hash`, its `message`, its `header`, its `commit_type`, its `scope`, its
+//! `description`, its `body`, its `breaking` flag, its `author` and its changed `files`.
+//!
+//! For instance, a template listing the breaking changes first :
+//!
+//!```text
+//! ## {{ tag }} ({{ date }})
+//!
+//! {% if breaking_changes %}
+//! ### Breaking changes
+//! {% for commit in breaking_changes %}
+//! * {{ commit.description }}
+//! {% endfor %}
+//! {% endif %}
+//!```
+
+use minijinja::{AutoEscape, Environment};
+use serde::Serialize;
+use sleppa_configuration::SectionsConfiguration;
+use sleppa_primitives::Commit;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::errors::ChangelogResult;
+
+/// The built-in template, rendering the tag with its compare link and the date, then one section per commit type.
+///
+/// When the entries are grouped by scope, each scope is a sub-bullet of the section listing its commits.
+pub const DEFAULT_TEMPLATE: &str = r#"## [{{ tag }}]({{ repository_url }}/compare/{{ previous_tag }}..{{ tag }}) ({{ date }})
+
+{% for section in sections %}
+* **{{ section.title }}**
+{% if group_by_scope %}
+{% for group in section.groups %}
+ * **{{ group.scope }}**
+{% for commit in group.commits %}
+  * {{ commit.message }} ([{{ commit.short_hash }}]({{ repository_url }}/commit/{{ commit.hash }}))
+{% endfor %}
+{% endfor %}
+{% else %}
+{% for commit in section.commits %}
+ * {{ commit.message }} ([{{ commit.short_hash }}]({{ repository_url }}/commit/{{ commit.hash }}))
+{% endfor %}
+{% endif %}
+{% endfor %}
+
+
+"#;
+
+/// Defines the release given to a template and its fields
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct TemplateRelease {
+    /// The new version, e.g. `4.0.0`
+    pub version: String,
+    /// The previous version, e.g. `3.2.1`, empty for the first release
+    pub previous_version: String,
+    /// The new tag, e.g. `v4.0.0`
+    pub tag: String,
+    /// The previous tag, e.g. `v3.2.1`, empty for the first release
+    pub previous_tag: String,
+    /// The release date, e.g. `2023-05-05`
+    pub date: String,
+    /// The repository's URL like `https://github.com/USER/REPO`
+    pub repository_url: String,
+    /// The released package in a monorepo, or `None` for the whole repository
+    pub package: Option<String>,
+    /// The commits grouped by section
+    pub sections: Vec<TemplateSection>,
+    /// The entries of the sections are grouped by scope
+    pub group_by_scope: bool,
+    /// All the released commits
+    pub commits: Vec<TemplateCommit>,
+    /// The scopes of the commits, with their aliases merged, sorted and deduplicated
+    pub scopes: Vec<String>,
+    /// The commits introducing a breaking change
+    pub breaking_changes: Vec<TemplateCommit>,
+    /// The authors and co-authors of the commits, sorted and deduplicated
+    pub contributors: Vec<String>,
+}
+
+/// Defines a section of the release given to a template and its fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateSection {
+    /// The title of the section, e.g. `Features`
+    pub title: String,
+    /// The type of the section's commits, e.g. `feat`, empty for the breaking changes section
+    pub commit_type: String,
+    /// The commits of the section
+    pub commits: Vec<TemplateCommit>,
+    /// The commits of the section grouped by scope, in alphabetical order, the unscoped commits being last
+    pub groups: Vec<TemplateGroup>,
+}
+
+/// Defines a group of commits sharing a scope in a section and its fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateGroup {
+    /// The scope of the group, e.g. `network`, or the other scope for the unscoped commits
+    pub scope: String,
+    /// The commits of the group
+    pub commits: Vec<TemplateCommit>,
+}
+
+/// Defines a commit given to a template and its fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemplateCommit {
+    /// The commit's hash
+    pub hash: String,
+    /// The first 8 characters of the commit's hash
+    pub short_hash: String,
+    /// The commit's message
+    pub message: String,
+    /// The first line of the commit's message
+    pub header: String,
+    /// The commit's type, e.g. `feat`
+    pub commit_type: String,
+    /// The commit's scope, empty if not scoped
+    pub scope: String,
+    /// The commit's description, e.g. `a new feature` for `feat(github): a new feature`
+    pub description: String,
+    /// The commit's body, empty if none
+    pub body: String,
+    /// The commit introduces a breaking change
+    pub breaking: bool,
+    /// The commit's author, empty if unknown
+    pub author: String,
+    /// The paths of the files changed by the commit
+    pub files: Vec<String>,
+}
+
+impl From<&Commit> for TemplateCommit {
+    fn from(commit: &Commit) -> Self {
+        TemplateCommit {
+            hash: commit.hash.clone(),
+            short_hash: commit.hash.chars().take(8).collect(),
+            message: commit.message.clone(),
+            header: commit.header().to_string(),
+            commit_type: commit.commit_type.clone(),
+            scope: commit.scope.clone(),
+            description: commit.description.clone(),
+            body: commit.body.clone(),
+            breaking: commit.breaking,
+            author: commit.author.clone(),
+            files: commit.files.clone(),
+        }
+    }
+}
+
+impl TemplateRelease {
+    /// Fills the commits, the scopes, the breaking changes and the contributors of the release from its sections.
+    ///
+    /// The commits of each section are grouped by scope, the aliases of the configuration being merged into the scope
+    /// they alias, and the unscoped commits being grouped under the configuration's other scope.
+    ///
+    /// The contributors are the authors of the commits and the co-authors named by their `Co-authored-by` trailers,
+    /// e.g. `Sofair Maintainers` for `Co-authored-by: Sofair Maintainers <maintainers@sofair.io>`.
+    pub fn with_sections(
+        mut self,
+        sections: Vec<(String, String, Vec<&Commit>)>,
+        configuration: &SectionsConfiguration,
+    ) -> Self {
+        let mut scopes = BTreeSet::new();
+        let mut contributors = BTreeSet::new();
+        self.group_by_scope = configuration.group_by_scope;
+
+        for (title, commit_type, commits) in sections {
+            let mut groups: BTreeMap<&str, Vec<TemplateCommit>> = BTreeMap::new();
+            let mut others = vec![];
+            for commit in &commits {
+                match commit.scope.as_str() {
+                    "" => others.push(TemplateCommit::from(*commit)),
+                    scope => {
+                        let scope = configuration.group_scope(scope);
+                        scopes.insert(scope.to_string());
+                        groups.entry(scope).or_default().push(TemplateCommit::from(*commit));
+                    }
+                }
+                contributors.insert(commit.author.clone());
+                for footer in &commit.footers {
+                    if footer.token.eq_ignore_ascii_case("Co-authored-by") {
+                        let name = footer.value.split('<').next().unwrap_or_default();
+                        contributors.insert(name.trim().to_string());
+                    }
+                }
+                if commit.breaking {
+                    self.breaking_changes.push(TemplateCommit::from(*commit));
+                }
+            }
+
+            let mut groups: Vec<TemplateGroup> = groups
+                .into_iter()
+                .map(|(scope, commits)| TemplateGroup {
+                    scope: scope.to_string(),
+                    commits,
+                })
+                .collect();
+            if !others.is_empty() {
+                groups.push(TemplateGroup {
+                    scope: configuration.group_scope("").to_string(),
+                    commits: others,
+                });
+            }
+
+            let commits: Vec<TemplateCommit> = commits.into_iter().map(TemplateCommit::from).collect();
+            self.commits.extend(commits.iter().cloned());
+            self.sections.push(TemplateSection {
+                title,
+                commit_type,
+                commits,
+                groups,
+            });
+        }
+
+        self.scopes = scopes.into_iter().collect();
+        self.contributors = contributors.into_iter().filter(|name| !name.is_empty()).collect();
+        self
+    }
+
+    /// Renders the release with a template.
+    ///
+    /// A [crate::errors::ChangelogError] is returned if the template is incorrect or fails to render.
+    pub fn render(&self, template: &str) -> ChangelogResult<String> {
+        Ok(environment().render_str(template, self)?)
+    }
+}
+
+/// Verifies the syntax of a template, without rendering it.
+pub fn verify_template(template: &str) -> ChangelogResult<()> {
+    environment().template_from_str(template)?;
+    Ok(())
+}
+
+/// Returns the environment rendering the templates, with trimmed block tags and without escaping.
+fn environment() -> Environment<'static> {
+    let mut environment = Environment::new();
+    environment.set_trim_blocks(true);
+    environment.set_lstrip_blocks(true);
+    environment.set_keep_trailing_newline(true);
+    environment.set_auto_escape_callback(|_| AutoEscape::None);
+    environment
+}
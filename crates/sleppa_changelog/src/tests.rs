@@ -4,6 +4,9 @@
 
 use super::{errors::*, *};
 use rstest::*;
+use sleppa_configuration::{ReleaseAction, ReleaseRule, ReleaseRuleFormat};
+use sleppa_primitives::repositories::errors::RepositoryResult;
+use std::collections::HashMap;
 use tempfile::tempdir;
 
 // Use fixture to create a reusable list of commits
@@ -15,6 +18,7 @@ fn commits_constructor() -> Vec<Commit> {
         message: "break: new breaking".to_string(),
         commit_type: "break".to_string(),
         hash: "1ebdf43e8950d8f9dace2e554be5d387267575ef".to_string(),
+        ..Default::default()
     };
 
     // Type : feat
@@ -22,11 +26,13 @@ fn commits_constructor() -> Vec<Commit> {
         message: "feat: new feature".to_string(),
         commit_type: "feat".to_string(),
         hash: "172cd1589d0a29b56cd8261a888911201305b04d".to_string(),
+        ..Default::default()
     };
     let commit2_2 = Commit {
         message: "feat: another feature".to_string(),
         commit_type: "feat".to_string(),
         hash: "000cd1589d0a29b56cd8261a888911201305b04d".to_string(),
+        ..Default::default()
     };
 
     // Type : patch
@@ -34,16 +40,19 @@ fn commits_constructor() -> Vec<Commit> {
         message: "patch: new patch".to_string(),
         commit_type: "patch".to_string(),
         hash: "cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a".to_string(),
+        ..Default::default()
     };
     let commit3_2 = Commit {
         message: "patch: another patch".to_string(),
         commit_type: "patch".to_string(),
         hash: "000fe77015b7aa2ac666ec05e14b76c9ba3dfd0a".to_string(),
+        ..Default::default()
     };
     let commit3_3 = Commit {
         message: "patch: also a patch".to_string(),
         commit_type: "patch".to_string(),
         hash: "111fe77015b7aa2ac666ec05e14b76c9ba3dfd0a".to_string(),
+        ..Default::default()
     };
 
     // Constructs the vector of commits
@@ -99,7 +108,7 @@ fn test_can_serialize_file_exists(commits_constructor: Vec<Commit>) -> TestResul
 
     changelog_plugin.with_commits(commits, last_tag, new_tag, repo_url);
 
-    changelog_plugin.serialize(&file_path)?;
+    changelog_plugin.serialize(&file_path, &changelog_plugin.render()?)?;
 
     // Reads the file to assert equality
     let mut buffer = String::new();
@@ -108,7 +117,7 @@ fn test_can_serialize_file_exists(commits_constructor: Vec<Commit>) -> TestResul
 
     // Creates the date like `2023-02-01`
     let now = OffsetDateTime::now_utc();
-    let date_format = format_description::parse("[year]-[month]-[day]")?;
+    let date_format = format_description!("[year]-[month]-[day]");
     let date = now.format(&date_format)?;
 
     let test_file = format!("## [v4.0.0](https://github.com/user/repo/compare/v3.2.1..v4.0.0) ({date})") + "\n\n" +
@@ -149,7 +158,7 @@ fn test_can_serialize_file_not_exist(commits_constructor: Vec<Commit>) -> TestRe
 
     changelog_plugin.with_commits(commits, last_tag, new_tag, repo_url);
 
-    changelog_plugin.serialize(&file_path)?;
+    changelog_plugin.serialize(&file_path, &changelog_plugin.render()?)?;
 
     // Reads the file to assert equality
     let mut buffer = String::new();
@@ -158,7 +167,7 @@ fn test_can_serialize_file_not_exist(commits_constructor: Vec<Commit>) -> TestRe
 
     // Creates the date like `2023-02-01`
     let now = OffsetDateTime::now_utc();
-    let date_format = format_description::parse("[year]-[month]-[day]")?;
+    let date_format = format_description!("[year]-[month]-[day]");
     let date = now.format(&date_format)?;
 
     let test_file = format!("## [v4.0.0](https://github.com/user/repo/compare/v3.2.1..v4.0.0) ({date})") + "\n\n" +
@@ -178,3 +187,470 @@ fn test_can_serialize_file_not_exist(commits_constructor: Vec<Commit>) -> TestRe
 
     Ok(())
 }
+
+// Tests the changelog is only rendered in memory in dry-run mode.
+#[rstest]
+fn test_can_run_dry_run(commits_constructor: Vec<Commit>) -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("changelogs").join("CHANGELOG.md");
+
+    let mut changelog_plugin = ChangelogPlugin {
+        dry_run: true,
+        ..ChangelogPlugin::new()
+    };
+
+    // Execution step
+    let release = changelog_plugin.run(
+        &file_path,
+        "https://github.com/user/repo",
+        commits_constructor,
+        "v3.2.1",
+        "v4.0.0",
+    )?;
+
+    // Asserts the release is rendered and the file is not written
+    assert!(release.starts_with("## [v4.0.0](https://github.com/user/repo/compare/v3.2.1..v4.0.0)"));
+    assert!(release.contains(
+        " * feat: new feature ([172cd158](https://github.com/user/repo/commit/172cd1589d0a29b56cd8261a888911201305b04d))\n"
+    ));
+    assert!(!file_path.exists());
+    assert!(!tmp_dir.path().join("changelogs").exists());
+
+    Ok(())
+}
+
+// Tests the release is rendered by a template file, with loops and conditionals over the release.
+#[rstest]
+fn test_can_render_with_template(commits_constructor: Vec<Commit>) -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let template_path = tmp_dir.path().join("changelog.md.j2");
+    let template = r#"# {{ package }} {{ version }} (from {{ previous_version }})
+{% if breaking_changes %}
+## Breaking changes
+{% for commit in breaking_changes %}
+- {{ commit.description }} by {{ commit.author }}
+{% endfor %}
+{% endif %}
+{% for section in sections if section.commit_type %}
+## {{ section.title | upper }} ({{ section.commits | length }})
+{% endfor %}
+Scopes : {{ scopes | join(", ") }}
+Contributors : {{ contributors | join(", ") }}
+"#;
+    File::create(&template_path)?.write_all(template.as_bytes())?;
+
+    let mut commits = commits_constructor;
+    commits[0] = Commit::new(
+        "1ebdf43e8950d8f9dace2e554be5d387267575ef",
+        "feat(github)!: new breaking",
+    )
+    .with_author("jdoe");
+    commits[1].footers = Commit::new(
+        "172cd1589d0a29b56cd8261a888911201305b04d",
+        "feat(gitea): new feature\n\nCo-authored-by: Sofair Maintainers <maintainers@sofair.io>",
+    )
+    .footers;
+
+    let mut changelog_plugin = ChangelogPlugin {
+        dry_run: true,
+        last_version: "3.2.1".to_string(),
+        new_version: "4.0.0".to_string(),
+        package: Some("sleppa".to_string()),
+        ..ChangelogPlugin::new()
+    };
+    changelog_plugin.with_template_file(&template_path)?;
+
+    // Execution step
+    let release = changelog_plugin.run(
+        &tmp_dir.path().join("CHANGELOG.md"),
+        "https://github.com/user/repo",
+        commits,
+        "sleppa-v3.2.1",
+        "sleppa-v4.0.0",
+    )?;
+
+    // Asserts the release is rendered by the template
+    let expected_release = "# sleppa 4.0.0 (from 3.2.1)
+## Breaking changes
+- new breaking by jdoe
+## FEAT (2)
+## PATCH (3)
+Scopes : github
+Contributors : Sofair Maintainers, jdoe
+";
+    assert_eq!(release, expected_release);
+
+    Ok(())
+}
+
+// Tests an incorrect template file is rejected before rendering.
+#[test]
+fn test_cannot_load_incorrect_template() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let template_path = tmp_dir.path().join("changelog.md.j2");
+    File::create(&template_path)?.write_all(b"{% for section in sections %}* {{ section.title }}")?;
+
+    // Execution step
+    let mut changelog_plugin = ChangelogPlugin::new();
+    let result = changelog_plugin.with_template_file(&template_path);
+
+    // Asserts the template is rejected and the built-in template is kept
+    assert!(matches!(result, Err(ChangelogError::TemplateError(_))));
+    assert_eq!(changelog_plugin.template, DEFAULT_TEMPLATE);
+
+    Ok(())
+}
+
+// Tests the versions are the tags without their tag format.
+#[test]
+fn test_can_strip_tag_format() {
+    assert_eq!(strip_tag_format("v4.0.0", "v{version}"), "4.0.0");
+    assert_eq!(
+        strip_tag_format("sleppa-v4.0.0-final", "sleppa-v{version}-final"),
+        "4.0.0"
+    );
+    assert_eq!(strip_tag_format("4.0.0", "v{version}"), "4.0.0");
+    assert_eq!(strip_tag_format("", "v{version}"), "");
+}
+
+// Tests the sections are titled, ordered and hidden by the configuration, the breaking changes being listed first.
+#[rstest]
+fn test_can_order_sections(commits_constructor: Vec<Commit>) -> TestResult<()> {
+    // Unit test preparation
+    let mut commits = commits_constructor;
+    commits[4].breaking = true;
+    commits.push(Commit::new(
+        "222fe77015b7aa2ac666ec05e14b76c9ba3dfd0a",
+        "style: format the code",
+    ));
+    commits.push(Commit::new(
+        "333fe77015b7aa2ac666ec05e14b76c9ba3dfd0a",
+        "ci: a new workflow",
+    ));
+
+    let mut changelog_plugin = ChangelogPlugin {
+        dry_run: true,
+        sections_configuration: SectionsConfiguration {
+            titles: HashMap::from([
+                ("feat".to_string(), "Features".to_string()),
+                ("patch".to_string(), "Bug Fixes".to_string()),
+            ]),
+            order: vec!["patch".to_string(), "feat".to_string()],
+            hidden: vec!["break".to_string(), "style".to_string()],
+            ..Default::default()
+        },
+        template: "{% for section in sections %}\n\
+                   {{ section.title }}:{% for commit in section.commits %} {{ commit.short_hash }}{% endfor %}\n\n\
+                   {% endfor %}"
+            .to_string(),
+        ..ChangelogPlugin::new()
+    };
+
+    // Execution step
+    let release = changelog_plugin.run(
+        &PathBuf::from("CHANGELOG.md"),
+        "https://github.com/user/repo",
+        commits,
+        "v3.2.1",
+        "v4.0.0",
+    )?;
+
+    // Asserts the breaking changes come first, then the ordered sections and the others, without the hidden ones
+    let expected_release = "Breaking Changes: 000fe770\n\
+                            Bug Fixes: cd2fe770 111fe770\n\
+                            Features: 172cd158 000cd158\n\
+                            ci: 333fe770\n";
+    assert_eq!(release, expected_release);
+
+    Ok(())
+}
+
+// Tests the entries of each section are grouped by scope, the aliased scopes being merged.
+#[test]
+fn test_can_group_by_scope() -> TestResult<()> {
+    // Unit test preparation
+    let commits = vec![
+        Commit::new(
+            "1ebdf43e8950d8f9dace2e554be5d387267575ef",
+            "feat(network): a new protocol",
+        ),
+        Commit::new("172cd1589d0a29b56cd8261a888911201305b04d", "feat: a new feature"),
+        Commit::new(
+            "000cd1589d0a29b56cd8261a888911201305b04d",
+            "feat(net): a faster protocol",
+        ),
+        Commit::new("cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a", "feat(largo): a new command"),
+    ];
+
+    let mut changelog_plugin = ChangelogPlugin {
+        dry_run: true,
+        sections_configuration: SectionsConfiguration {
+            group_by_scope: true,
+            scopes: HashMap::from([("net".to_string(), "network".to_string())]),
+            ..Default::default()
+        },
+        ..ChangelogPlugin::new()
+    };
+
+    // Execution step
+    let release = changelog_plugin.run(
+        &PathBuf::from("CHANGELOG.md"),
+        "https://github.com/user/repo",
+        commits,
+        "v3.2.1",
+        "v4.0.0",
+    )?;
+
+    // Asserts the scopes are sub-bullets of the section, the unscoped commits being grouped last
+    let expected_section = "* **feat**\n".to_string() +
+        " * **largo**\n" +
+        "  * feat(largo): a new command ([cd2fe770](https://github.com/user/repo/commit/cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a))\n" +
+        " * **network**\n" +
+        "  * feat(network): a new protocol ([1ebdf43e](https://github.com/user/repo/commit/1ebdf43e8950d8f9dace2e554be5d387267575ef))\n" +
+        "  * feat(net): a faster protocol ([000cd158](https://github.com/user/repo/commit/000cd1589d0a29b56cd8261a888911201305b04d))\n" +
+        " * **other**\n" +
+        "  * feat: a new feature ([172cd158](https://github.com/user/repo/commit/172cd1589d0a29b56cd8261a888911201305b04d))\n\n\n";
+    assert!(release.ends_with(&expected_section));
+
+    Ok(())
+}
+
+// Tests the release is inserted below the header of a changelog, at its marker or before its first release.
+#[test]
+fn test_can_insert_release_below_header() {
+    // Unit test preparation
+    let release = "## v4.0.0\n\n* **feat**\n\n\n";
+    let with_marker = "# Changelog\n\n## Unreleased\n\n* a change\n\n<!-- sleppa:insert -->\n## v3.2.1\n";
+    let with_heading = "# Changelog\n\nAll notable changes.\n\n## v3.2.1\n\n## v3.2.0\n";
+    let with_marker_at_end = "# Changelog\n<!-- sleppa:insert -->";
+
+    // Execution step and asserts the header is kept above the release
+    assert_eq!(
+        insert_release(with_marker, release),
+        "# Changelog\n\n## Unreleased\n\n* a change\n\n<!-- sleppa:insert -->\n## v4.0.0\n\n* **feat**\n\n\n## v3.2.1\n"
+    );
+    assert_eq!(
+        insert_release(with_heading, release),
+        "# Changelog\n\nAll notable changes.\n\n## v4.0.0\n\n* **feat**\n\n\n## v3.2.1\n\n## v3.2.0\n"
+    );
+    assert_eq!(
+        insert_release(with_marker_at_end, release),
+        "# Changelog\n<!-- sleppa:insert -->\n## v4.0.0\n\n* **feat**\n\n\n"
+    );
+    assert_eq!(insert_release("", release), release);
+}
+
+// Tests the insertion of a release below the `Unreleased` section of a changelog having no marker.
+#[test]
+fn test_can_insert_release_below_unreleased() {
+    // Unit test preparation
+    let release = "## v4.0.0\n\n* **feat**\n\n\n";
+    let with_unreleased = "# Changelog\n\n## [Unreleased]\n\n* a change\n\n## v3.2.1\n";
+    let with_only_unreleased = "# Changelog\n\n## unreleased\n\n* a change\n";
+
+    // Execution step and asserts the `Unreleased` section is kept above the release
+    assert_eq!(
+        insert_release(with_unreleased, release),
+        "# Changelog\n\n## [Unreleased]\n\n* a change\n\n## v4.0.0\n\n* **feat**\n\n\n## v3.2.1\n"
+    );
+    assert_eq!(
+        insert_release(with_only_unreleased, release),
+        "# Changelog\n\n## unreleased\n\n* a change\n## v4.0.0\n\n* **feat**\n\n\n"
+    );
+}
+
+// Tests the serialization of the changelog below the title of an existing file, leaving no temporary file.
+#[rstest]
+fn test_can_serialize_below_header(commits_constructor: Vec<Commit>) -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("CHANGELOG.md");
+    let mut file = File::create(&file_path)?;
+    write!(
+        &mut file,
+        "# Changelog\n\n## [v3.2.1](https://github.com/user/repo/compare/v3.2.0..v3.2.1)\n"
+    )?;
+    #[cfg(unix)]
+    fs::set_permissions(&file_path, std::os::unix::fs::PermissionsExt::from_mode(0o640))?;
+
+    let mut changelog_plugin = ChangelogPlugin::new();
+    changelog_plugin.with_commits(commits_constructor, "v3.2.1", "v4.0.0", "https://github.com/user/repo");
+
+    // Execution step
+    changelog_plugin.serialize(&file_path, &changelog_plugin.render()?)?;
+
+    // Asserts the release is inserted below the title, and the temporary file is renamed
+    let changelog = fs::read_to_string(&file_path)?;
+    assert!(changelog.starts_with("# Changelog\n\n## [v4.0.0](https://github.com/user/repo/compare/v3.2.1..v4.0.0)"));
+    assert!(changelog.ends_with("\n\n\n## [v3.2.1](https://github.com/user/repo/compare/v3.2.0..v3.2.1)\n"));
+    assert_eq!(fs::read_dir(tmp_dir.path())?.count(), 1);
+
+    // Asserts the permissions of the file are kept
+    #[cfg(unix)]
+    assert_eq!(
+        std::os::unix::fs::PermissionsExt::mode(&fs::metadata(&file_path)?.permissions()) & 0o777,
+        0o640
+    );
+
+    Ok(())
+}
+
+// Tests the released commits are the ones whose header matches a release rule, or introducing a breaking change.
+#[test]
+fn test_can_keep_release_commits() {
+    // Unit test preparation
+    let mut configuration = Configuration::default();
+    configuration.release_rules.insert(
+        ReleaseAction::Patch,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: "^fix: .*$".to_string(),
+        },
+    );
+    let commits = vec![
+        Commit::new("1", "fix: a fix\n\nSome body text."),
+        Commit::new("2", "chore: not released\n\nSome body text."),
+        Commit::new("3", "chore!: drop a feature"),
+    ];
+
+    // Execution step
+    let released = release_commits(&commits, &configuration);
+
+    // Asserts the commit with a body and the breaking change are released
+    let hashes: Vec<&str> = released.iter().map(|commit| commit.hash.as_str()).collect();
+    assert_eq!(hashes, vec!["1", "3"]);
+}
+
+// A repository whose commits and tag dates are given, indexed by the hash of their tag.
+struct HistoryRepository {
+    history: Vec<(RepositoryTag, Vec<Commit>, OffsetDateTime)>,
+}
+
+#[async_trait]
+impl Repository for HistoryRepository {
+    async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag> {
+        Ok(self.history.last().map(|(tag, _, _)| tag.clone()).unwrap_or_default())
+    }
+
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>> {
+        Ok(self.history.iter().rev().map(|(tag, _, _)| tag.clone()).collect())
+    }
+
+    async fn is_ancestor(&self, _tag: &RepositoryTag, _branch: &str) -> RepositoryResult<bool> {
+        Ok(true)
+    }
+
+    // Returns the commits of the newest tag, the history being linear.
+    async fn get_inner_commits_between(
+        &self,
+        _oldest: &RepositoryTag,
+        newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>> {
+        let release = self.history.iter().find(|(tag, _, _)| tag == newest);
+        Ok(release.map(|(_, commits, _)| commits.clone()).unwrap_or_default())
+    }
+
+    async fn get_tag_date(&self, tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime> {
+        let release = self.history.iter().find(|(release_tag, _, _)| release_tag == tag);
+        Ok(release.map(|(_, _, date)| *date).unwrap_or(OffsetDateTime::UNIX_EPOCH))
+    }
+}
+
+// Tests the whole changelog is regenerated from the tag history, each release being dated with its tag.
+#[tokio::test]
+async fn test_can_backfill_from_tag_history() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("changelogs").join("CHANGELOG.md");
+    let tag = |identifier: &str, hash: &str| RepositoryTag {
+        identifier: identifier.to_string(),
+        hash: hash.to_string(),
+    };
+    let repository = HistoryRepository {
+        history: vec![
+            (
+                tag("v1.0.0", "c1"),
+                vec![Commit::new(
+                    "1ebdf43e8950d8f9dace2e554be5d387267575ef",
+                    "feat: a first feature",
+                )],
+                OffsetDateTime::from_unix_timestamp(1_672_531_200)?,
+            ),
+            (
+                tag("v1.0.1", "c2"),
+                vec![
+                    Commit::new("172cd1589d0a29b56cd8261a888911201305b04d", "fix: a fix"),
+                    Commit::new("cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a", "chore: not released"),
+                ],
+                OffsetDateTime::from_unix_timestamp(1_683_244_800)?,
+            ),
+        ],
+    };
+
+    let mut configuration = Configuration::default();
+    for (release_action, grammar) in [
+        (ReleaseAction::Minor, "^feat: .*$"),
+        (ReleaseAction::Patch, "^fix: .*$"),
+    ] {
+        configuration.release_rules.insert(
+            release_action,
+            ReleaseRule {
+                format: ReleaseRuleFormat::Regex,
+                grammar: grammar.to_string(),
+            },
+        );
+    }
+    configuration.changelog.sections.titles = HashMap::from([("fix".to_string(), "Bug Fixes".to_string())]);
+
+    let tags = repository
+        .get_tags()
+        .await?
+        .into_iter()
+        .rev()
+        .collect::<Vec<RepositoryTag>>();
+
+    // Execution step
+    let changelog = ChangelogPlugin::new()
+        .backfill(
+            &repository,
+            &tags,
+            &configuration,
+            &file_path,
+            "https://github.com/user/repo",
+        )
+        .await?;
+
+    // Asserts the releases are listed from the newest, dated with their tag and with the released commits only
+    let expected_changelog = "## [v1.0.1](https://github.com/user/repo/compare/v1.0.0..v1.0.1) (2023-05-05)\n\n".to_string() +
+        "* **Bug Fixes**\n" +
+        " * fix: a fix ([172cd158](https://github.com/user/repo/commit/172cd1589d0a29b56cd8261a888911201305b04d))\n\n\n" +
+        "## [v1.0.0](https://github.com/user/repo/compare/..v1.0.0) (2023-01-01)\n\n" +
+        "* **feat**\n" +
+        " * feat: a first feature ([1ebdf43e](https://github.com/user/repo/commit/1ebdf43e8950d8f9dace2e554be5d387267575ef))\n\n\n";
+    assert_eq!(changelog, expected_changelog);
+    assert_eq!(fs::read_to_string(&file_path)?, expected_changelog);
+
+    // Unit test preparation
+    let header = "# Changelog\n\nAll notable changes.\n\n## [Unreleased]\n\n* a change\n\n<!-- sleppa:insert -->\n";
+    fs::write(&file_path, format!("{header}## v0.1.0\n\n* an outdated release\n"))?;
+
+    // Execution step
+    let changelog = ChangelogPlugin::new()
+        .backfill(
+            &repository,
+            &tags,
+            &configuration,
+            &file_path,
+            "https://github.com/user/repo",
+        )
+        .await?;
+
+    // Asserts the header of the existing changelog is kept above the regenerated releases
+    assert_eq!(changelog, format!("{header}{expected_changelog}"));
+    assert_eq!(fs::read_to_string(&file_path)?, changelog);
+
+    Ok(())
+}
@@ -12,9 +12,13 @@ pub enum CodeArchiverError {
     #[error(transparent)]
     ApiError(#[from] octocrab::Error),
 
-    // Chained errors occurring when accessing environment variables
+    // Chained errors occurring when sending requests to a forge
     #[error(transparent)]
-    VarError(#[from] std::env::VarError),
+    RepositoryError(#[from] sleppa_primitives::repositories::errors::RepositoryError),
+
+    // The client of the forge is not authenticated
+    #[error("Credentials are missing to publish the release : {0}")]
+    MissingCredentials(String),
 }
 
 /// Definition of the commit analyzer result
@@ -0,0 +1,80 @@
+//! Gitea release publisher
+//!
+//! This module publishes a release into a Gitea or Forgejo repository, with the release notes rendered by the
+//! changelog plugin as its body. Gitea attaches the archives of the source code to every release.
+//!
+//! The release needs an authenticated client, with a `GITEA_TOKEN` (see [GiteaRepository::from_env]). In dry-run
+//! mode, no request is sent to Gitea and no credential is needed.
+
+use async_trait::async_trait;
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
+use sleppa_primitives::repositories::gitea::GiteaRepository;
+
+use crate::errors::{CodeArchiverError, CodeArchiverResult};
+
+/// Defines the Gitea release plugin and its fields
+pub struct GiteaReleasePlugin {
+    /// The repository where the release is to be published
+    pub repository: GiteaRepository,
+    /// The release is published as a pre-release when it is made on a pre-release channel
+    pub pre_release: bool,
+    /// In dry-run mode, the release is not published
+    pub dry_run: bool,
+}
+
+impl GiteaReleasePlugin {
+    /// Publishes the release of a tag into the Gitea repository
+    ///
+    /// The tag is created from the branch if it doesn't exist yet, or from the default branch if the branch is
+    /// empty. Nothing is published in dry-run mode.
+    pub async fn run(&self, tag: &str, branch: &str, notes: &str) -> CodeArchiverResult<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        if !self.repository.client.is_authenticated() {
+            return Err(missing_credentials());
+        }
+
+        self.repository
+            .create_release(tag, branch, notes, self.pre_release)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Returns the error of a Gitea client without credentials.
+fn missing_credentials() -> CodeArchiverError {
+    CodeArchiverError::MissingCredentials("set GITEA_TOKEN".to_string())
+}
+
+#[async_trait]
+impl Plugin for GiteaReleasePlugin {
+    fn name(&self) -> &str {
+        "gitea release"
+    }
+
+    /// Verifies the credentials are provided, unless in dry-run mode.
+    async fn verify_conditions(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run || self.repository.client.is_authenticated() {
+            return Ok(());
+        }
+
+        Err(PluginError::failure(missing_credentials()))
+    }
+
+    /// Publishes the release of the new tag from the released branch, with the release notes, unless in dry-run
+    /// mode.
+    ///
+    /// The release is marked as a pre-release if the branch is mapped to a pre-release channel.
+    async fn publish(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        self.pre_release = context.configuration.branches.contains_key(&context.branch);
+        self.dry_run = self.dry_run || context.dry_run;
+
+        self.run(context.new_tag()?, &context.branch, &context.notes)
+            .await
+            .map_err(PluginError::failure)
+    }
+}
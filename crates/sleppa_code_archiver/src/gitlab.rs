@@ -0,0 +1,73 @@
+//! GitLab release publisher
+//!
+//! This module publishes a release into a GitLab project, with the release notes rendered by the changelog plugin
+//! as its description. GitLab attaches the archives of the source code to every release.
+//!
+//! The release needs an authenticated client, with a `GITLAB_TOKEN` or the `CI_JOB_TOKEN` of GitLab CI/CD (see
+//! [GitlabRepository::from_env]). In dry-run mode, no request is sent to GitLab and no credential is needed.
+
+use async_trait::async_trait;
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
+use sleppa_primitives::repositories::gitlab::GitlabRepository;
+
+use crate::errors::{CodeArchiverError, CodeArchiverResult};
+
+/// Defines the GitLab release plugin and its fields
+pub struct GitlabReleasePlugin {
+    /// The repository where the release is to be published
+    pub repository: GitlabRepository,
+    /// In dry-run mode, the release is not published
+    pub dry_run: bool,
+}
+
+impl GitlabReleasePlugin {
+    /// Publishes the release of a tag into the GitLab project
+    ///
+    /// The tag is created from the branch if it doesn't exist yet, or from the default branch if the branch is
+    /// empty. Nothing is published in dry-run mode.
+    pub async fn run(&self, tag: &str, branch: &str, notes: &str) -> CodeArchiverResult<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        if !self.repository.client.is_authenticated() {
+            return Err(missing_credentials());
+        }
+
+        self.repository.create_release(tag, branch, notes).await?;
+        Ok(())
+    }
+}
+
+/// Returns the error of a GitLab client without credentials.
+fn missing_credentials() -> CodeArchiverError {
+    CodeArchiverError::MissingCredentials("set GITLAB_TOKEN, or run in GitLab CI/CD".to_string())
+}
+
+#[async_trait]
+impl Plugin for GitlabReleasePlugin {
+    fn name(&self) -> &str {
+        "gitlab release"
+    }
+
+    /// Verifies the credentials are provided, unless in dry-run mode.
+    async fn verify_conditions(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run || self.repository.client.is_authenticated() {
+            return Ok(());
+        }
+
+        Err(PluginError::failure(missing_credentials()))
+    }
+
+    /// Publishes the release of the new tag from the released branch, with the release notes, unless in dry-run
+    /// mode.
+    async fn publish(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        self.dry_run = self.dry_run || context.dry_run;
+
+        self.run(context.new_tag()?, &context.branch, &context.notes)
+            .await
+            .map_err(PluginError::failure)
+    }
+}
@@ -3,11 +3,24 @@
 //! This crate produces a release into a GitHub repository while publishing
 //! code archives in a `.zip` and a `.tar.gz` format.
 //!
-//! The release needs a logged user and a tag to be published.
+//! The release needs a tag and an authenticated client to be published : the release is sent by the
+//! GitHub client of the repository, authenticated with a token or a GitHub App.
+//!
+//! The releases of the repositories hosted on GitLab are published by the [gitlab::GitlabReleasePlugin], and the
+//! ones hosted on Gitea or Forgejo by the [gitea::GiteaReleasePlugin].
+//!
+//! In dry-run mode, no request is sent to GitHub and no credential is needed.
 
-mod errors;
+pub mod errors;
+pub mod gitea;
+pub mod gitlab;
 
-use errors::CodeArchiverResult;
+use async_trait::async_trait;
+use errors::{CodeArchiverError, CodeArchiverResult};
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
 use sleppa_primitives::repositories::{github::GithubRepository, *};
 
 /// Defines the code archiver plugin and its fields
@@ -19,28 +32,75 @@ pub struct CodeArchiverPlugin {
     pub release_tag: RepositoryTag,
     /// The repository where the release is to be published
     pub repository: GithubRepository,
+    /// The branch the release's tag is created from, `main` if empty
+    pub branch: String,
+    /// The release is published as a pre-release when it is made on a pre-release channel
+    pub pre_release: bool,
+    /// In dry-run mode, the release is not published
+    pub dry_run: bool,
 }
 
 impl CodeArchiverPlugin {
     /// Publishes a release into the GitHub repository
     ///
-    /// The release is published for a given [RepositoryTag] into a [GithubRepository].
-    /// The credentials are mandatory to publish a release.
+    /// The release is published for a given [RepositoryTag] into a [GithubRepository], with the repository's client.
+    /// The credentials are mandatory to publish a release, except in dry-run mode where nothing is published.
     pub async fn run(&self) -> CodeArchiverResult<()> {
-        // Constructs the credentials
-        let token = std::env::var("GITHUB_TOKEN")?;
-
-        // Build an octocrab instance with the provided credentials.
-        let octocrab = octocrab::Octocrab::builder().personal_token(token).build()?;
+        if self.dry_run {
+            return Ok(());
+        }
+        if !self.repository.client.is_authenticated() {
+            return Err(missing_credentials());
+        }
 
         // Publishes the release for the given tag.
-        octocrab
+        let branch = if self.branch.is_empty() { "main" } else { &self.branch };
+        self.repository
+            .client
+            .octocrab()
             .repos(&self.repository.owner, &self.repository.repo)
             .releases()
             .create(&self.release_tag.identifier)
-            .target_commitish("main")
+            .target_commitish(branch)
+            .prerelease(self.pre_release)
             .send()
             .await?;
         Ok(())
     }
 }
+
+/// Returns the error of a GitHub client without credentials.
+fn missing_credentials() -> CodeArchiverError {
+    CodeArchiverError::MissingCredentials("set GITHUB_TOKEN or the GitHub App credentials".to_string())
+}
+
+#[async_trait]
+impl Plugin for CodeArchiverPlugin {
+    fn name(&self) -> &str {
+        "code archiver"
+    }
+
+    /// Verifies the credentials are provided, unless in dry-run mode.
+    async fn verify_conditions(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.dry_run || context.dry_run {
+            return Ok(());
+        }
+
+        match self.repository.client.is_authenticated() {
+            true => Ok(()),
+            false => Err(PluginError::failure(missing_credentials())),
+        }
+    }
+
+    /// Publishes the release of the new tag from the released branch, unless in dry-run mode.
+    ///
+    /// The release is marked as a pre-release if the branch is mapped to a pre-release channel.
+    async fn publish(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        self.release_tag.identifier = context.new_tag()?.to_string();
+        self.branch = context.branch.clone();
+        self.pre_release = context.configuration.branches.contains_key(&context.branch);
+        self.dry_run = self.dry_run || context.dry_run;
+
+        self.run().await.map_err(PluginError::failure)
+    }
+}
@@ -7,11 +7,31 @@
 //!
 //! As only one release action type must be defined for a new release, only the higher one is kept :
 //! - Major > Minor > Patch
+//!
+//! Breaking changes are detected by a built-in rule, taking precedence over the configured release rules : a commit
+//! whose header holds the `!` marker, e.g. `feat(api)!: drop v1`, or whose message has a `BREAKING CHANGE` footer,
+//! leads to a major release.
+//!
+//! The analysis of every commit is detailed by [CommitAnalyzerPlugin::report] : the rule each commit matched or the
+//! reason it was ignored, the number of commits per release action and the commit deciding the release action.
+//!
+//! The commits can be linted with [CommitAnalyzerPlugin::lint], reporting the messages which don't follow the
+//! Conventional Commits specification or match no release rule, instead of silently skipping them.
+//!
+//! As a [Plugin], it determines the release action during the `analyze_commits` step.
 
 mod errors;
+pub mod lint;
+pub mod report;
 
+use async_trait::async_trait;
 use errors::*;
+use lint::{LintRule, Violation};
+use report::{AnalysisReport, CommitAnalysis, MatchedRule};
 use sleppa_configuration::*;
+use sleppa_primitives::conventional::{errors::ConventionalError, validate};
+use sleppa_primitives::plugins::{errors::PluginResult, Plugin, ReleaseContext};
+use sleppa_primitives::Commit;
 
 /// Defines the commit analyzer plugin
 ///
@@ -27,43 +47,86 @@ impl CommitAnalyzerPlugin {
     /// to retrieve the release action type to apply since the last tag.
     /// As it is impossible to have two release action types at the same time, only the higher one is kept.
     pub fn analyze(&self, commit_messages: Vec<String>, rules: &ReleaseRules) -> Option<ReleaseAction> {
-        let mut major_count = 0;
-        let mut minor_count = 0;
-        let mut patch_count = 0;
-
-        // Matches the release action type according to the commit message contents.
-        for message in commit_messages {
-            match self.execute(&message, rules) {
-                Ok(ReleaseAction::Major) => major_count += 1,
-                Ok(ReleaseAction::Minor) => minor_count += 1,
-                Ok(ReleaseAction::Patch) => patch_count += 1,
-                Err(_err) => continue,
-            }
+        let commits: Vec<Commit> = commit_messages.iter().map(|message| Commit::new("", message)).collect();
+        self.report(&commits, rules).release_action
+    }
+
+    /// Analyzes the commits and details the analysis in an [AnalysisReport].
+    ///
+    /// Every commit is reported with the rule it matched, or the reason it was ignored. The release action to apply is
+    /// the higher one, decided by the first commit matching it.
+    pub fn report(&self, commits: &[Commit], rules: &ReleaseRules) -> AnalysisReport {
+        let mut report = AnalysisReport::default();
+
+        for commit in commits {
+            let (release_action, rule, ignore_reason) = match self.execute(commit, rules) {
+                Ok(release_action) if commit.breaking => {
+                    (Some(release_action), Some(MatchedRule::BreakingChange), None)
+                }
+                Ok(release_action) => (Some(release_action), Some(MatchedRule::ReleaseRule), None),
+                Err(_err) => (None, None, Some(ignore_reason(commit))),
+            };
+
+            report.push(CommitAnalysis {
+                hash: commit.hash.clone(),
+                header: commit.header().to_string(),
+                release_action,
+                rule,
+                ignore_reason,
+            });
         }
 
-        // Returns only the higher action release type.
-        if major_count > 0 {
-            Some(ReleaseAction::Major)
-        } else if minor_count > 0 {
-            Some(ReleaseAction::Minor)
-        } else if patch_count > 0 {
-            Some(ReleaseAction::Patch)
-        } else {
-            None
+        report
+    }
+
+    /// Lints the commits, returning the violations of the messages.
+    ///
+    /// A message must follow the Conventional Commits specification and match one of the release rules, unless it
+    /// is a breaking change. Every commit is reported at most once, with the first rule it failed.
+    pub fn lint(&self, commits: &[Commit], rules: &ReleaseRules) -> Vec<Violation> {
+        let mut violations = vec![];
+
+        for commit in commits {
+            let (rule, reason) = match (validate(&commit.message), self.execute(commit, rules)) {
+                (Err(err), _) => (LintRule::ConventionalCommits, err.to_string()),
+                (Ok(()), Err(_err)) => (
+                    LintRule::ReleaseRules,
+                    format!("No release rule matches the type '{}'", commit.commit_type),
+                ),
+                (Ok(()), Ok(_)) => continue,
+            };
+
+            violations.push(Violation {
+                hash: commit.hash.clone(),
+                header: commit.header().to_string(),
+                rule,
+                reason,
+            });
         }
+
+        violations
     }
 
-    /// Parses a message and matches a ReleaseAction.
+    /// Matches a commit to a ReleaseAction.
     ///
-    /// This function reads a given message and verifies if the message matches a [ReleaseAction].
-    /// thanks to the trait [ReleaseRuleHandler].
+    /// This function verifies if the header of a commit, i.e. the first line of its message, matches a
+    /// [ReleaseAction] thanks to the trait [ReleaseRuleHandler], so that a message with a body matches as well.
+    /// A breaking change, marked with `!` or described by a `BREAKING CHANGE` footer, matches a major release
+    /// before any release rule is tried.
+    /// A release action without release rule, e.g. in a default configuration, matches no message.
     /// If no match is found, a [CommitAnalyzerError] is returned.
-    fn execute(&self, message: &str, release_rule: &ReleaseRules) -> CommitAnalyzerResult<ReleaseAction> {
-        if release_rule[&ReleaseAction::Major].handle(message).is_ok() {
+    fn execute(&self, commit: &Commit, release_rule: &ReleaseRules) -> CommitAnalyzerResult<ReleaseAction> {
+        let matches = |release_action: ReleaseAction| {
+            release_rule
+                .get(&release_action)
+                .map_or(false, |rule| rule.handle(commit.header()).is_ok())
+        };
+
+        if commit.breaking || matches(ReleaseAction::Major) {
             Ok(ReleaseAction::Major)
-        } else if release_rule[&ReleaseAction::Minor].handle(message).is_ok() {
+        } else if matches(ReleaseAction::Minor) {
             Ok(ReleaseAction::Minor)
-        } else if release_rule[&ReleaseAction::Patch].handle(message).is_ok() {
+        } else if matches(ReleaseAction::Patch) {
             Ok(ReleaseAction::Patch)
         } else {
             Err(CommitAnalyzerError::ErrorNoMatching())
@@ -71,5 +134,32 @@ impl CommitAnalyzerPlugin {
     }
 }
 
+/// Returns the reason a commit matches no rule.
+///
+/// A message which is not a conventional commit is reported with its violation, otherwise with its type.
+fn ignore_reason(commit: &Commit) -> String {
+    match validate(&commit.message) {
+        Err(ConventionalError::MissingBlankLine(_)) | Ok(()) => {
+            format!("No release rule matches the type '{}'", commit.commit_type)
+        }
+        Err(err) => format!("Not a conventional commit : {err}"),
+    }
+}
+
+#[async_trait]
+impl Plugin for CommitAnalyzerPlugin {
+    fn name(&self) -> &str {
+        "commit analyzer"
+    }
+
+    /// Determines the release action from the commits of the release context.
+    async fn analyze_commits(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        context.release_action = self
+            .report(&context.commits, &context.configuration.release_rules)
+            .release_action;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests;
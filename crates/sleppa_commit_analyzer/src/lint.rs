@@ -0,0 +1,51 @@
+//! Commit messages linting
+//!
+//! This module defines the violations reported by [crate::CommitAnalyzerPlugin::lint], when a commit message
+//! doesn't follow the [Conventional Commits](https://www.conventionalcommits.org) specification or matches none of
+//! the release rules of the configuration file. Unlike the analysis, which skips these commits, the linting reports
+//! them so that no release information is silently lost, e.g. `feat:add` missing the space after the colon.
+
+use serde::Serialize;
+use std::fmt;
+
+/// Defines a violation of a commit message and its fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// The commit's hash, empty for a message which is not committed yet
+    pub hash: String,
+    /// The first line of the commit's message
+    pub header: String,
+    /// The rule the message failed
+    pub rule: LintRule,
+    /// The reason the message failed the rule
+    pub reason: String,
+}
+
+/// Enumerates the rules a commit message must follow.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintRule {
+    /// The message follows the Conventional Commits specification
+    ConventionalCommits,
+    /// The message matches one of the release rules of the configuration file, or is a breaking change
+    ReleaseRules,
+}
+
+impl fmt::Display for LintRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LintRule::ConventionalCommits => write!(f, "conventional-commits"),
+            LintRule::ReleaseRules => write!(f, "release-rules"),
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    /// Formats the violation as `<short hash> <header> : [rule] reason`, without the hash if it is empty.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.hash.is_empty() {
+            write!(f, "{} ", &self.hash[..self.hash.len().min(8)])?;
+        }
+        write!(f, "{} : [{}] {}", self.header, self.rule, self.reason)
+    }
+}
@@ -0,0 +1,107 @@
+//! Analysis report
+//!
+//! This module details how the commits are analyzed : every commit is listed with the rule it matched, or the reason
+//! it was ignored, along with the number of commits matching each release action and the commit deciding the
+//! release action. The report serializes to JSON, e.g. to annotate a CI build :
+//!
+//!```json
+//! {
+//!   "commits": [
+//!     { "hash": "1ebdf43e", "header": "feat(api)!: drop v1", "release_action": "major", "rule": "breaking_change" },
+//!     { "hash": "172cd158", "header": "ci: build the docs", "ignore_reason": "No release rule matches the type 'ci'" }
+//!   ],
+//!   "counts": { "major": 1, "minor": 0, "patch": 0, "ignored": 1 },
+//!   "release_action": "major",
+//!   "deciding_commit": "1ebdf43e"
+//! }
+//!```
+
+use serde::Serialize;
+use sleppa_configuration::ReleaseAction;
+
+/// Defines the analysis report of the commits and its fields
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct AnalysisReport {
+    /// The analysis of every commit, in the order they were given
+    pub commits: Vec<CommitAnalysis>,
+    /// The number of commits matching each release action, and of ignored commits
+    pub counts: ActionCounts,
+    /// The release action to apply, i.e. the highest one matched, if any
+    pub release_action: Option<ReleaseAction>,
+    /// The hash of the first commit matching the release action to apply, if any
+    pub deciding_commit: Option<String>,
+}
+
+/// Defines the analysis of a commit and its fields
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommitAnalysis {
+    /// The commit's hash
+    pub hash: String,
+    /// The first line of the commit's message
+    pub header: String,
+    /// The release action matched by the commit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_action: Option<ReleaseAction>,
+    /// The rule matched by the commit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule: Option<MatchedRule>,
+    /// The reason the commit is ignored, if it matches no rule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_reason: Option<String>,
+}
+
+/// Enumerates the rules a commit can match.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchedRule {
+    /// The built-in rule detecting the breaking changes
+    BreakingChange,
+    /// The release rule of the configuration file for the matched release action
+    ReleaseRule,
+}
+
+/// Defines the number of commits matching each release action and its fields
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct ActionCounts {
+    /// Number of commits matching a major release
+    pub major: usize,
+    /// Number of commits matching a minor release
+    pub minor: usize,
+    /// Number of commits matching a patch release
+    pub patch: usize,
+    /// Number of commits matching no rule
+    pub ignored: usize,
+}
+
+impl AnalysisReport {
+    /// Adds the analysis of a commit to the report, updating the counts and the release action to apply.
+    pub fn push(&mut self, analysis: CommitAnalysis) {
+        match analysis.release_action {
+            Some(ReleaseAction::Major) => self.counts.major += 1,
+            Some(ReleaseAction::Minor) => self.counts.minor += 1,
+            Some(ReleaseAction::Patch) => self.counts.patch += 1,
+            None => self.counts.ignored += 1,
+        }
+
+        if let Some(release_action) = analysis.release_action {
+            if self
+                .release_action
+                .map_or(true, |current| rank(release_action) > rank(current))
+            {
+                self.release_action = Some(release_action);
+                self.deciding_commit = Some(analysis.hash.clone());
+            }
+        }
+
+        self.commits.push(analysis);
+    }
+}
+
+/// Returns the rank of a release action, the highest one being kept : Major > Minor > Patch.
+fn rank(release_action: ReleaseAction) -> u8 {
+    match release_action {
+        ReleaseAction::Major => 3,
+        ReleaseAction::Minor => 2,
+        ReleaseAction::Patch => 1,
+    }
+}
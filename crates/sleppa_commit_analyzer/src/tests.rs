@@ -2,7 +2,7 @@
 //!
 //! This testing module implements the unit tests for testing the commit analyzer routines.
 
-use super::{errors::TestResult, *};
+use super::{errors::TestResult, lint::*, report::*, *};
 
 // Tests the function `execute`.
 //
@@ -46,26 +46,44 @@ fn test_can_execute() -> TestResult<()> {
     let incorrect_message_no_semicolumn = "feat introduced new function";
 
     // Execution step
-    let analyzer = CommitAnalyzerPlugin::default();
+    let analyzer = CommitAnalyzerPlugin;
 
     // Asserts the results of the function match the correct ReleaseAction.
     assert_eq!(
-        analyzer.execute(correct_message_major_release_action, &config.release_rules)?,
+        analyzer.execute(
+            &Commit::new("", correct_message_major_release_action),
+            &config.release_rules
+        )?,
         ReleaseAction::Major
     );
     assert_eq!(
-        analyzer.execute(correct_message_patch_release_action, &config.release_rules)?,
+        analyzer.execute(
+            &Commit::new("", correct_message_patch_release_action),
+            &config.release_rules
+        )?,
         ReleaseAction::Patch
     );
 
     // Asserts the results of the function are incorrects.
     assert!(analyzer
-        .execute(incorrect_message_ci_not_match, &config.release_rules)
+        .execute(&Commit::new("", incorrect_message_ci_not_match), &config.release_rules)
         .is_err());
     assert!(analyzer
-        .execute(incorrect_message_no_semicolumn, &config.release_rules)
+        .execute(&Commit::new("", incorrect_message_no_semicolumn), &config.release_rules)
         .is_err());
 
+    // Asserts a configuration without release rules matches the breaking changes only, without panicking
+    assert!(analyzer
+        .execute(
+            &Commit::new("", correct_message_patch_release_action),
+            &ReleaseRules::new()
+        )
+        .is_err());
+    assert_eq!(
+        analyzer.execute(&Commit::new("", "feat!: drop a function"), &ReleaseRules::new())?,
+        ReleaseAction::Major
+    );
+
     Ok(())
 }
 
@@ -115,10 +133,16 @@ fn test_can_analyze() {
         "ci: some change".to_string(),
     ];
 
+    // The release rules match the header of a message having a body
+    let correct_messages_with_body = vec![
+        "ci: some change\n\nSome body text.".to_string(),
+        "feat: add a thing\n\nSome body text.\n\nRefs #42".to_string(),
+    ];
+
     let correct_no_release: Vec<String> = vec![];
 
     // Execution step
-    let analyzer = CommitAnalyzerPlugin::default();
+    let analyzer = CommitAnalyzerPlugin;
 
     // Asserts the results of the function matches the correct ReleaseAction
     assert_eq!(
@@ -133,5 +157,241 @@ fn test_can_analyze() {
             .unwrap(),
         ReleaseAction::Patch
     );
+    assert_eq!(
+        analyzer.analyze(correct_messages_with_body, &config.release_rules),
+        Some(ReleaseAction::Minor)
+    );
     assert!(analyzer.analyze(correct_no_release, &config.release_rules).is_none());
 }
+
+// Tests the breaking changes are detected from the `!` marker and the `BREAKING CHANGE` footers.
+//
+// The built-in rule takes precedence over the release rules, even if the message matches another rule or none.
+#[test]
+fn test_can_detect_breaking_changes() -> TestResult<()> {
+    // Unit test preparation
+    // Builds a correct [Configuration] structure for testing purpose.
+    let mut config: Configuration = Configuration::new();
+    config.release_rules.insert(
+        ReleaseAction::Major,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(break){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    config.release_rules.insert(
+        ReleaseAction::Minor,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(feat){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    config.release_rules.insert(
+        ReleaseAction::Patch,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(refac){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+
+    // Creates breaking messages which don't match the major release rule
+    let marked_message = "feat(api)!: drop v1";
+    let footer_message = "refac: rename the routes\n\nBREAKING CHANGE: the routes are renamed";
+    let hyphen_footer_message = "ci: change the build\n\nBREAKING-CHANGE: the artifacts are renamed";
+
+    // Creates a message mentioning a breaking change in its body only
+    let body_message = "feat: a feature\n\nIt is not a BREAKING CHANGE: at all.\n\nRefs #42";
+
+    // Execution step
+    let analyzer = CommitAnalyzerPlugin;
+
+    // Asserts the breaking changes lead to a major release
+    assert_eq!(
+        analyzer.execute(&Commit::new("", marked_message), &config.release_rules)?,
+        ReleaseAction::Major
+    );
+    assert_eq!(
+        analyzer.execute(&Commit::new("", footer_message), &config.release_rules)?,
+        ReleaseAction::Major
+    );
+    assert_eq!(
+        analyzer.execute(&Commit::new("", hyphen_footer_message), &config.release_rules)?,
+        ReleaseAction::Major
+    );
+    assert_eq!(
+        analyzer.analyze(
+            vec!["feat: a feature".to_string(), footer_message.to_string()],
+            &config.release_rules
+        ),
+        Some(ReleaseAction::Major)
+    );
+
+    // Asserts a breaking change mentioned in the body is not a footer, the header matching the minor release rule
+    assert_eq!(
+        analyzer.execute(&Commit::new("", body_message), &config.release_rules)?,
+        ReleaseAction::Minor
+    );
+
+    Ok(())
+}
+
+// Tests the function `report`.
+//
+// Every commit is reported with the rule it matched or the reason it was ignored, and the report serializes to JSON.
+#[test]
+fn test_can_report() -> TestResult<()> {
+    // Unit test preparation
+    // Builds a correct [Configuration] structure for testing purpose.
+    let mut config: Configuration = Configuration::new();
+    config.release_rules.insert(
+        ReleaseAction::Major,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(break){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    config.release_rules.insert(
+        ReleaseAction::Minor,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(feat){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    config.release_rules.insert(
+        ReleaseAction::Patch,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(refac){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    let commits = vec![
+        Commit::new("1", "feat: a cool feature"),
+        Commit::new("2", "ci: some change"),
+        Commit::new("3", "feat(api)!: drop v1"),
+        Commit::new("4", "Issue to solve (#2)"),
+        Commit::new("5", "break: another breaking change"),
+        Commit::new("6", "refac: some ref\n\nSome body text."),
+    ];
+
+    // Execution step
+    let analyzer = CommitAnalyzerPlugin;
+    let report = analyzer.report(&commits, &config.release_rules);
+
+    // Asserts every commit is reported with its rule or its ignore reason
+    assert_eq!(
+        report.commits[0],
+        CommitAnalysis {
+            hash: "1".to_string(),
+            header: "feat: a cool feature".to_string(),
+            release_action: Some(ReleaseAction::Minor),
+            rule: Some(MatchedRule::ReleaseRule),
+            ignore_reason: None,
+        }
+    );
+    assert_eq!(
+        report.commits[1].ignore_reason.as_deref(),
+        Some("No release rule matches the type 'ci'")
+    );
+    assert_eq!(report.commits[2].rule, Some(MatchedRule::BreakingChange));
+    assert!(report.commits[3]
+        .ignore_reason
+        .as_deref()
+        .map_or(false, |reason| reason.starts_with("Not a conventional commit")));
+    assert_eq!(
+        report.commits[5],
+        CommitAnalysis {
+            hash: "6".to_string(),
+            header: "refac: some ref".to_string(),
+            release_action: Some(ReleaseAction::Patch),
+            rule: Some(MatchedRule::ReleaseRule),
+            ignore_reason: None,
+        }
+    );
+
+    // Asserts the counts and the deciding commit, the first one matching the highest release action
+    assert_eq!(
+        report.counts,
+        ActionCounts {
+            major: 2,
+            minor: 1,
+            patch: 1,
+            ignored: 2,
+        }
+    );
+    assert_eq!(report.release_action, Some(ReleaseAction::Major));
+    assert_eq!(report.deciding_commit.as_deref(), Some("3"));
+
+    // Asserts the report serializes to JSON
+    let json = serde_json::to_value(&report)?;
+    assert_eq!(json["release_action"], "major");
+    assert_eq!(json["deciding_commit"], "3");
+    assert_eq!(json["counts"]["ignored"], 2);
+    assert_eq!(json["commits"][2]["rule"], "breaking_change");
+    assert!(json["commits"][1].get("rule").is_none());
+
+    Ok(())
+}
+
+// Tests the function `lint`.
+//
+// The messages which are not conventional commits, or match no release rule, are reported with the rule they failed.
+#[test]
+fn test_can_lint() {
+    // Unit test preparation
+    // Builds a correct [Configuration] structure for testing purpose.
+    let mut config: Configuration = Configuration::new();
+    config.release_rules.insert(
+        ReleaseAction::Major,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(break){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    config.release_rules.insert(
+        ReleaseAction::Minor,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(feat){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    config.release_rules.insert(
+        ReleaseAction::Patch,
+        ReleaseRule {
+            format: ReleaseRuleFormat::Regex,
+            grammar: r"^(refac){1}(\(\S.*\S\))?:\s.*[a-z0-9]$".to_string(),
+        },
+    );
+    let commits = vec![
+        Commit::new("1ebdf43e8950d8f9dace2e554be5d387267575ef", "feat: a cool feature"),
+        Commit::new("172cd1589d0a29b56cd8261a888911201305b04d", "feat:add a feature"),
+        Commit::new("000cd1589d0a29b56cd8261a888911201305b04d", "ci: some change"),
+        Commit::new("987cd1589d0a29b56cd8261a888911201305b04d", "ci(api)!: drop v1"),
+    ];
+
+    // Execution step
+    let analyzer = CommitAnalyzerPlugin;
+    let violations = analyzer.lint(&commits, &config.release_rules);
+
+    // Asserts the missing space and the unknown type are reported, the breaking change being accepted
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].rule, LintRule::ConventionalCommits);
+    assert_eq!(violations[0].header, "feat:add a feature");
+    assert_eq!(
+        violations[1],
+        Violation {
+            hash: "000cd1589d0a29b56cd8261a888911201305b04d".to_string(),
+            header: "ci: some change".to_string(),
+            rule: LintRule::ReleaseRules,
+            reason: "No release rule matches the type 'ci'".to_string(),
+        }
+    );
+    assert_eq!(
+        violations[1].to_string(),
+        "000cd158 ci: some change : [release-rules] No release rule matches the type 'ci'"
+    );
+
+    // Asserts the correct messages have no violation, even with a body
+    assert!(analyzer.lint(&commits[..1], &config.release_rules).is_empty());
+    let commit_with_body = Commit::new("", "feat: add a thing\n\nSome body text.\n");
+    assert!(analyzer.lint(&[commit_with_body], &config.release_rules).is_empty());
+}
@@ -0,0 +1,22 @@
+//! This module regroups all the constants used in the `sleppa_configuration` crate.
+
+/// The placeholder replaced by the version in a tag format.
+pub const VERSION_PLACEHOLDER: &str = "{version}";
+
+/// The default tag format, producing tags like `v3.2.1`.
+pub const DEFAULT_TAG_FORMAT: &str = "v{version}";
+
+/// The placeholder replaced by the package name in the tag format of a monorepo.
+pub const PACKAGE_PLACEHOLDER: &str = "{package}";
+
+/// The default tag format of a monorepo's packages, producing tags like `sleppa_versioner@v0.2.0`.
+pub const DEFAULT_PACKAGE_TAG_FORMAT: &str = "{package}@v{version}";
+
+/// The default key of the trailer referencing a pull request, e.g. `Pull-Request: #12`.
+pub const DEFAULT_PULL_REQUEST_TRAILER: &str = "Pull-Request";
+
+/// The default title of the changelog section listing the breaking changes.
+pub const DEFAULT_BREAKING_TITLE: &str = "Breaking Changes";
+
+/// The default scope of the unscoped commits, when the changelog entries are grouped by scope.
+pub const DEFAULT_OTHER_SCOPE: &str = "other";
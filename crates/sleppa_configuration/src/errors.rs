@@ -16,10 +16,30 @@ pub enum ConfigurationError {
     #[error(transparent)]
     RegexError(#[from] regex::Error),
 
+    // Errors occurring when compiling a parsing expression grammar
+    #[error("Invalid PEG grammar : {0}")]
+    PegError(String),
+
     // Wrong or no ReleaseActino found
     #[error("The release action is 'major', 'minor' or 'patch'. Found : {0}")]
     IncorrectReleaseAction(String),
 
+    // Packages' tag format not containing the `{package}` placeholder
+    #[error("The packages' tag format must contain '{{package}}'. Found : {0}")]
+    IncorrectPackageTagFormat(String),
+
+    // Channel which is not a valid pre-release identifier
+    #[error("The channel must be made of alphanumerics and hyphens, and not only of digits. Found : {0}")]
+    IncorrectChannel(String),
+
+    // Key of the pull requests' trailer which is not a single word
+    #[error("The trailer must be made of alphanumerics and hyphens. Found : {0}")]
+    IncorrectTrailer(String),
+
+    // More than one registry given to publish the crates to
+    #[error("A single registry must be given to publish the crates. Found : {0}")]
+    IncorrectRegistry(String),
+
     // No match found when analyzing commit message with the grammar
     #[error("No match found.")]
     ErrorNoMatch(),
@@ -21,15 +21,96 @@
 //!
 //! The trait [ReleaseRuleHandler] handles the release rule and verifies if a commit message
 //! matches a grammar.
+//!
+//! A grammar is compiled once, when the configuration file is loaded, and reused for every commit message.
+//! Therefore an incorrect grammar is reported by [try_parse] as a [ConfigurationError].
+//!
+//! An optional `[branches]` section maps a branch to a pre-release channel. The releases made from this branch are
+//! pre-releases of the channel, e.g. `v2.0.0-beta.1`, while the other branches release stable versions :
+//!
+//!```toml
+//! [branches]
+//! next = "beta"
+//!```
+//!
+//! An optional `tag_format` field gives the text surrounding the version in the tags, with the `{version}`
+//! placeholder, e.g. `tag_format = "release-{version}"`. It defaults to `v{version}`, producing tags like `v3.2.1`.
+//!
+//! An optional `[monorepo]` section releases every package of a Cargo workspace independently. The tags of a package
+//! are formatted with the `{package}` and `{version}` placeholders, defaulting to `{package}@v{version}`. The commits
+//! are attributed to a package by the files they change, or by their scope, which can be an alias of the package :
+//!
+//!```toml
+//! [monorepo]
+//! tag_format = "{package}@v{version}"
+//! scopes = { versioner = "sleppa_versioner" }
+//!```
+//!
+//! An optional `[pull_requests]` section defines how the pull request merged by a commit of the released branch is
+//! found from the commit's message. The strategies are tried in order, the first one finding a pull request number
+//! winning (see [PullRequestStrategy]). Only the pull request's title suffix, e.g. `Issue to solve (#2)`, is looked
+//! for by default. A commit referencing no pull request is ignored, unless `analyze_unreferenced` is set : its own
+//! message is then analyzed.
+//!
+//!```toml
+//! [pull_requests]
+//! strategies = ["title_suffix", "merge_subject", "trailer", "body_reference"]
+//! trailer = "Pull-Request"
+//! analyze_unreferenced = true
+//!```
+//!
+//! An optional `[changelog]` section gives the template rendering the release notes and the changelog, a
+//! [MiniJinja](https://docs.rs/minijinja) template file whose path is relative to the working directory. The
+//! built-in template is used if no template is given :
+//!
+//!```toml
+//! [changelog]
+//! template = ".github/changelog.md.j2"
+//!```
+//!
+//! Its `[changelog.sections]` table gives the titles of the sections, indexed by their commit type, and the order of
+//! the sections, the other sections following in alphabetical order. The sections of the `hidden` types are left
+//! out of the changelog. The breaking changes are always listed first, in their own section. The entries of each
+//! section can be grouped by scope, the `scopes` aliases merging equivalent scopes and the unscoped commits being
+//! grouped under the `other_scope` :
+//!
+//!```toml
+//! [changelog.sections]
+//! titles = { feat = "Features", fix = "Bug Fixes", sec = "Security" }
+//! order = ["sec", "feat", "fix"]
+//! hidden = ["style", "test"]
+//! breaking_title = "Breaking Changes"
+//! group_by_scope = true
+//! scopes = { net = "network" }
+//! other_scope = "other"
+//!```
+//!
+//! An optional `[publish]` section publishes the released crates to a Cargo registry, crates.io by default. Either
+//! an alternative registry declared in the Cargo configuration, a registry index or a local registry directory can
+//! be given, but only one of them :
+//!
+//!```toml
+//! [publish]
+//! registry = "my-registry"
+//!```
 
-mod errors;
+pub mod constants;
+pub mod errors;
+mod peg;
 
+use constants::{
+    DEFAULT_BREAKING_TITLE, DEFAULT_OTHER_SCOPE, DEFAULT_PACKAGE_TAG_FORMAT, DEFAULT_PULL_REQUEST_TRAILER,
+    DEFAULT_TAG_FORMAT, PACKAGE_PLACEHOLDER,
+};
 use errors::{ConfigurationError, ConfigurationResult};
+use once_cell::sync::Lazy;
+use peg::PegParser;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
 
 /// Configuration data structure
 ///
@@ -37,13 +118,128 @@ use std::path::Path;
 ///
 /// The `release_rules` hashmap contains 3 keys : `major`, `minor` and `patch`.
 /// For every key a [ReleaseRule] is associated.
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Configuration {
+    /// Format of the tags, containing the `{version}` placeholder, e.g. `mycrate@{version}`
+    #[serde(default = "default_tag_format")]
+    pub tag_format: String,
     pub release_rules: ReleaseRules,
+    /// Pre-release channels, e.g. `beta`, indexed by the name of the branch they are released from
+    #[serde(default)]
+    pub branches: HashMap<String, String>,
+    /// Releases every package of a workspace independently, if defined
+    pub monorepo: Option<MonorepoConfiguration>,
+    /// Publishes the released crates to a Cargo registry, if defined
+    pub publish: Option<PublishConfiguration>,
+    /// Finds the pull requests merged by the commits of the released branch
+    #[serde(default)]
+    pub pull_requests: PullRequestConfiguration,
+    /// Renders the release notes and the changelog
+    #[serde(default)]
+    pub changelog: ChangelogConfiguration,
+}
+
+/// Changelog configuration data structure
+///
+/// This structure defines how the release notes and the changelog are rendered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogConfiguration {
+    /// Path of the template file rendering a release, the built-in template being used if not given
+    pub template: Option<PathBuf>,
+    /// Titles, order and visibility of the sections
+    #[serde(default)]
+    pub sections: SectionsConfiguration,
+}
+
+/// Changelog sections configuration data structure
+///
+/// This structure defines how the sections of a release, each one listing the commits of a type, are titled and
+/// ordered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionsConfiguration {
+    /// Titles of the sections indexed by their commit type, e.g. `feat = "Features"`, the type being the title if not
+    /// given
+    #[serde(default)]
+    pub titles: HashMap<String, String>,
+    /// Commit types whose sections are listed first, in order, the other sections following in alphabetical order
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// Commit types whose sections are left out, e.g. `style`, their breaking changes being still listed
+    #[serde(default)]
+    pub hidden: Vec<String>,
+    /// Title of the section listing the breaking changes, before the other sections
+    #[serde(default = "default_breaking_title")]
+    pub breaking_title: String,
+    /// The entries of each section are grouped by scope
+    #[serde(default)]
+    pub group_by_scope: bool,
+    /// Scopes indexed by the scopes aliasing them, e.g. `net = "network"`, grouped together
+    #[serde(default)]
+    pub scopes: HashMap<String, String>,
+    /// Scope of the group of the unscoped commits
+    #[serde(default = "default_other_scope")]
+    pub other_scope: String,
+}
+
+/// Monorepo configuration data structure
+///
+/// This structure defines how the packages of a workspace are released independently.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MonorepoConfiguration {
+    /// Format of the packages' tags, containing the `{package}` and `{version}` placeholders
+    #[serde(default = "default_package_tag_format")]
+    pub tag_format: String,
+    /// Packages' names indexed by the commit scopes aliasing them, e.g. `versioner = "sleppa_versioner"`
+    #[serde(default)]
+    pub scopes: HashMap<String, String>,
+}
+
+/// Publication configuration data structure
+///
+/// This structure defines the Cargo registry the released crates are published to. If no field is set, the crates
+/// are published to crates.io.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PublishConfiguration {
+    /// Name of an alternative registry declared in the Cargo configuration, e.g. `my-registry`
+    pub registry: Option<String>,
+    /// URL of a registry index, e.g. `https://my-intranet:8080/git/index`
+    pub index: Option<String>,
+    /// Local registry directory where the packaged crates are copied instead of being uploaded
+    pub directory: Option<PathBuf>,
+}
+
+/// Pull requests configuration data structure
+///
+/// This structure defines how the pull request merged by a commit is found from the commit's message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullRequestConfiguration {
+    /// Strategies finding the pull request's number in a commit message, tried in order
+    #[serde(default = "default_pull_request_strategies")]
+    pub strategies: Vec<PullRequestStrategy>,
+    /// Key of the trailer referencing the pull request, e.g. `Pull-Request` for `Pull-Request: #12`
+    #[serde(default = "default_pull_request_trailer")]
+    pub trailer: String,
+    /// The message of a commit referencing no pull request is analyzed, instead of the commit being ignored
+    #[serde(default)]
+    pub analyze_unreferenced: bool,
+}
+
+/// Enumerates the strategies finding the pull request referenced by a commit message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullRequestStrategy {
+    /// The title ends with the pull request's number, as in a squash commit, e.g. `Issue to solve (#12)`
+    TitleSuffix,
+    /// The title is the one of a merge commit, e.g. `Merge pull request #12 from owner/branch`
+    MergeSubject,
+    /// A trailer references the pull request by its number or its URL, e.g. `Pull-Request: #12`
+    Trailer,
+    /// A line of the body ends with the pull request's number, e.g. `Backported from main (#12)`
+    BodyReference,
 }
 
 /// Enumerates available release actions.
-#[derive(PartialEq, Debug, Serialize, Deserialize, Eq, Hash)]
+#[derive(PartialEq, Debug, Serialize, Deserialize, Eq, Hash, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum ReleaseAction {
     /// Major release implying the left digit of a semantic version to be incremented (e.g. from `1.0.1` -> `2.0.0`)
@@ -57,7 +253,7 @@ pub enum ReleaseAction {
 /// Enumerates available format for a release rule.
 ///
 /// Two format are available : Regex and PEG.
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum ReleaseRuleFormat {
     /// Grammar of the release rule is defined as a [regular expression](https://en.wikipedia.org/wiki/Regular_expression)
@@ -78,6 +274,21 @@ pub struct ReleaseRule {
     pub grammar: String,
 }
 
+/// Enumerates the compiled forms of a release rule grammar.
+#[derive(Debug)]
+enum CompiledGrammar {
+    /// Grammar compiled as a regular expression
+    Regex(Regex),
+    /// Grammar compiled as a parsing expression grammar
+    Peg(PegParser),
+}
+
+/// Compiled grammars, keyed by their format and their grammar
+type CompiledGrammars = HashMap<(ReleaseRuleFormat, String), Arc<CompiledGrammar>>;
+
+/// Grammars of the release rules, compiled once for each format and grammar
+static COMPILED_GRAMMARS: Lazy<Mutex<CompiledGrammars>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
 /// Type alias used for typing release rule
 pub type ReleaseRules = HashMap<ReleaseAction, ReleaseRule>;
 
@@ -92,6 +303,121 @@ impl Configuration {
     pub fn new() -> Self {
         Configuration::default()
     }
+
+    /// Returns the tag format of the given package, or the repository's one if no package is given.
+    ///
+    /// The `{package}` placeholder of the monorepo's tag format is replaced by the package name,
+    /// e.g. `sleppa_versioner@v{version}`.
+    pub fn tag_format(&self, package: Option<&str>) -> String {
+        match (package, &self.monorepo) {
+            (Some(package), Some(monorepo)) => monorepo.tag_format.replace(PACKAGE_PLACEHOLDER, package),
+            _ => self.tag_format.clone(),
+        }
+    }
+}
+
+impl Default for Configuration {
+    /// Creates an empty configuration with the default tag format.
+    fn default() -> Self {
+        Configuration {
+            tag_format: default_tag_format(),
+            release_rules: ReleaseRules::new(),
+            branches: HashMap::new(),
+            monorepo: None,
+            publish: None,
+            pull_requests: PullRequestConfiguration::default(),
+            changelog: ChangelogConfiguration::default(),
+        }
+    }
+}
+
+/// Returns the default tag format, used when the configuration file doesn't give one.
+fn default_tag_format() -> String {
+    DEFAULT_TAG_FORMAT.to_string()
+}
+
+/// Returns the default tag format of the packages, used when the monorepo section doesn't give one.
+fn default_package_tag_format() -> String {
+    DEFAULT_PACKAGE_TAG_FORMAT.to_string()
+}
+
+/// Returns the default strategies finding a pull request, only the title suffix written by a squash merge.
+fn default_pull_request_strategies() -> Vec<PullRequestStrategy> {
+    vec![PullRequestStrategy::TitleSuffix]
+}
+
+/// Returns the default key of the trailer referencing a pull request.
+fn default_pull_request_trailer() -> String {
+    DEFAULT_PULL_REQUEST_TRAILER.to_string()
+}
+
+/// Returns the default title of the breaking changes section, used when the sections table doesn't give one.
+fn default_breaking_title() -> String {
+    DEFAULT_BREAKING_TITLE.to_string()
+}
+
+/// Returns the default scope of the unscoped commits, used when the sections table doesn't give one.
+fn default_other_scope() -> String {
+    DEFAULT_OTHER_SCOPE.to_string()
+}
+
+impl Default for SectionsConfiguration {
+    /// Creates the configuration titling the sections with their commit type, in alphabetical order, without grouping
+    /// their entries by scope.
+    fn default() -> Self {
+        SectionsConfiguration {
+            titles: HashMap::new(),
+            order: vec![],
+            hidden: vec![],
+            breaking_title: default_breaking_title(),
+            group_by_scope: false,
+            scopes: HashMap::new(),
+            other_scope: default_other_scope(),
+        }
+    }
+}
+
+impl SectionsConfiguration {
+    /// Returns the scope grouping a commit's scope, i.e. the scope it aliases or itself, and the other scope if the
+    /// commit is unscoped.
+    pub fn group_scope<'a>(&'a self, scope: &'a str) -> &'a str {
+        match scope {
+            "" => &self.other_scope,
+            scope => self.scopes.get(scope).map_or(scope, String::as_str),
+        }
+    }
+}
+
+impl Default for PullRequestConfiguration {
+    /// Creates the configuration finding the pull requests by their title suffix, and ignoring the other commits.
+    fn default() -> Self {
+        PullRequestConfiguration {
+            strategies: default_pull_request_strategies(),
+            trailer: default_pull_request_trailer(),
+            analyze_unreferenced: false,
+        }
+    }
+}
+
+impl ReleaseRule {
+    /// Compiles the release rule's grammar according to its format.
+    ///
+    /// A grammar is compiled only once, the result being kept for the following calls of every release rule with
+    /// the same format and grammar. A [ConfigurationError] is returned if the grammar is incorrect.
+    fn compile(&self) -> ConfigurationResult<Arc<CompiledGrammar>> {
+        let key = (self.format.clone(), self.grammar.clone());
+        let mut compiled_grammars = COMPILED_GRAMMARS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(compiled) = compiled_grammars.get(&key) {
+            return Ok(compiled.clone());
+        }
+
+        let compiled = Arc::new(match &self.format {
+            ReleaseRuleFormat::Regex => CompiledGrammar::Regex(Regex::new(&self.grammar)?),
+            ReleaseRuleFormat::Peg => CompiledGrammar::Peg(PegParser::try_new(&self.grammar)?),
+        });
+        compiled_grammars.insert(key, compiled.clone());
+        Ok(compiled)
+    }
 }
 
 impl ReleaseRuleHandler for ReleaseRule {
@@ -100,22 +426,15 @@ impl ReleaseRuleHandler for ReleaseRule {
     /// The given commit message is parsed using the release rule's grammar and
     /// in case it matches, an Ok(()) is returned.
     fn handle(&self, message: &str) -> ConfigurationResult<()> {
-        match &self.format {
-            ReleaseRuleFormat::Regex => {
-                let regex = match Regex::new(self.grammar.as_str()) {
-                    Ok(regex) => regex,
-                    Err(err) => {
-                        return Err(ConfigurationError::RegexError(err));
-                    }
-                };
-                match regex.captures(message) {
-                    Some(_cap) => Ok(()),
-                    None => Err(ConfigurationError::ErrorNoMatch()),
-                }
-            }
-            ReleaseRuleFormat::Peg => {
-                unimplemented!()
-            }
+        let is_match = match self.compile()?.as_ref() {
+            CompiledGrammar::Regex(regex) => regex.is_match(message),
+            CompiledGrammar::Peg(parser) => parser.is_match(message),
+        };
+
+        if is_match {
+            Ok(())
+        } else {
+            Err(ConfigurationError::ErrorNoMatch())
         }
     }
 }
@@ -124,28 +443,74 @@ impl ReleaseRuleHandler for ReleaseRule {
 ///
 /// The given toml configuration file is loaded and parsed, and if successful,
 /// a [Configuration] is returned or a [ConfigurationError] otherwise.
-/// The parsing returns a [ConfigurationError] if a [ReleaseAction] is missing or if the
-/// `format` is not recognized.
+/// The parsing returns a [ConfigurationError] if a [ReleaseAction] is missing, if the
+/// `format` is not recognized, if a `grammar` can't be compiled, if the packages' tag format, a channel or the pull
+/// requests' trailer is incorrect. The tag format is verified when it is parsed by the versioner.
 pub fn try_parse(path: &Path) -> ConfigurationResult<Configuration> {
     let content = fs::read_to_string(path)?;
 
     let config: Configuration = toml::from_str(&content)?;
 
     // Verify that the configuration file contains a release rule for each release action types.
-    if config.release_rules.get(&ReleaseAction::Major).is_none() {
+    if !config.release_rules.contains_key(&ReleaseAction::Major) {
         return Err(ConfigurationError::IncorrectReleaseAction(
             "major is missing".to_string(),
         ));
-    } else if config.release_rules.get(&ReleaseAction::Minor).is_none() {
+    } else if !config.release_rules.contains_key(&ReleaseAction::Minor) {
         return Err(ConfigurationError::IncorrectReleaseAction(
             "minor is missing".to_string(),
         ));
-    } else if config.release_rules.get(&ReleaseAction::Patch).is_none() {
+    } else if !config.release_rules.contains_key(&ReleaseAction::Patch) {
         return Err(ConfigurationError::IncorrectReleaseAction(
             "patch is missing".to_string(),
         ));
     }
 
+    // Compiles every grammar so that an incorrect one is reported while loading the configuration.
+    for rule in config.release_rules.values() {
+        rule.compile()?;
+    }
+
+    // Verify that the packages' tag format contains the package placeholder, so that every package has its own tags.
+    if let Some(monorepo) = &config.monorepo {
+        if !monorepo.tag_format.contains(PACKAGE_PLACEHOLDER) {
+            return Err(ConfigurationError::IncorrectPackageTagFormat(
+                monorepo.tag_format.to_string(),
+            ));
+        }
+    }
+
+    // Verify that every channel is a valid SemVer pre-release identifier, which is not only made of digits
+    // to be distinguished from the pre-release counter.
+    for channel in config.branches.values() {
+        if channel.is_empty()
+            || !channel.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            || channel.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(ConfigurationError::IncorrectChannel(channel.to_string()));
+        }
+    }
+
+    // Verify that the trailer's key is a single word, as written before the colon of a trailer.
+    let trailer = &config.pull_requests.trailer;
+    if trailer.is_empty() || !trailer.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(ConfigurationError::IncorrectTrailer(trailer.to_string()));
+    }
+
+    // Verify that a single registry is given to publish the crates to.
+    if let Some(publish) = &config.publish {
+        let registries = [
+            publish.registry.is_some(),
+            publish.index.is_some(),
+            publish.directory.is_some(),
+        ];
+        if registries.iter().filter(|is_set| **is_set).count() > 1 {
+            return Err(ConfigurationError::IncorrectRegistry(
+                "registry, index and directory are exclusive".to_string(),
+            ));
+        }
+    }
+
     Ok(config)
 }
 
@@ -0,0 +1,67 @@
+//! Parsing expression grammar engine
+//!
+//! This module compiles a [PEG](https://en.wikipedia.org/wiki/Parsing_expression_grammar) grammar, written with the
+//! [pest](https://pest.rs/book/grammars/syntax.html) syntax, into a parser at runtime thanks to the [pest_vm] crate.
+//!
+//! The first rule declared in the grammar is the entry point used to analyze a commit message, e.g. :
+//!
+//!```pest
+//! message = { SOI ~ type ~ scope? ~ ": " ~ ANY+ ~ EOI }
+//! type    = { "feat" | "ci" }
+//! scope   = { "(" ~ (!")" ~ ANY)+ ~ ")" }
+//!```
+//!
+//! As a PEG parser matches the beginning of the message, the `SOI` and `EOI` built-in rules are needed to force the
+//! whole message to match the grammar.
+
+use pest_vm::Vm;
+
+use crate::errors::{ConfigurationError, ConfigurationResult};
+
+/// Parser compiled from a PEG grammar
+pub struct PegParser {
+    /// Virtual machine running the compiled grammar rules
+    vm: Vm,
+    /// Name of the rule used as entry point, i.e. the first rule of the grammar
+    entry_rule: String,
+}
+
+impl PegParser {
+    /// Compiles a PEG grammar into a parser.
+    ///
+    /// A [ConfigurationError] is returned if the grammar is syntactically incorrect or if it defines no rule.
+    pub fn try_new(grammar: &str) -> ConfigurationResult<Self> {
+        let (_, rules) = match pest_meta::parse_and_optimize(grammar) {
+            Ok(compiled) => compiled,
+            Err(errors) => {
+                let messages: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+                return Err(ConfigurationError::PegError(messages.join("\n")));
+            }
+        };
+
+        // The first rule of the grammar is the entry point
+        let entry_rule = match rules.first() {
+            Some(rule) => rule.name.clone(),
+            None => return Err(ConfigurationError::PegError("the grammar defines no rule".to_string())),
+        };
+
+        Ok(PegParser {
+            vm: Vm::new(rules),
+            entry_rule,
+        })
+    }
+
+    /// Verifies if a message matches the grammar's entry rule.
+    pub fn is_match(&self, message: &str) -> bool {
+        self.vm.parse(&self.entry_rule, message).is_ok()
+    }
+}
+
+impl std::fmt::Debug for PegParser {
+    /// Prints the parser with its entry rule, as the [Vm] can't be printed.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PegParser")
+            .field("entry_rule", &self.entry_rule)
+            .finish()
+    }
+}
@@ -207,3 +207,333 @@ fn test_can_trait_implementation_regex() {
     assert!(release_rule_def.handle(msg4).is_err());
     assert!(release_rule_def.handle(msg5).is_err());
 }
+
+#[test]
+/// Tests the `handle`function implementation for a ReleaseRule using the PEG format
+fn test_can_trait_implementation_peg() {
+    // Unit test preparation
+    // Creates an instance of a ReleaseRule with a PEG grammar similar to the regex one above
+    let release_rule_def = ReleaseRule {
+        format: ReleaseRuleFormat::Peg,
+        grammar: r#"
+        message = { SOI ~ type ~ scope? ~ ": " ~ ANY+ ~ EOI }
+        type = { "feat" | "ci" }
+        scope = { "(" ~ (!")" ~ ANY)+ ~ ")" }
+        "#
+        .to_string(),
+    };
+
+    // Creates correct messages for the grammar defined above
+    // `feat` without scope is correct
+    let msg0 = "feat: add a function";
+    // `feat` with a scope is correct
+    let msg1 = "feat(sync): add a function";
+    // `ci` is correct
+    let msg2 = "ci: add a workflow";
+
+    // Creates incorrect messages for the grammar defined above
+    // `break` is incorrect
+    let msg3 = "break(sync): add a function";
+    // Space after `feat` is incorrect
+    let msg4 = "feat (sync): add a function";
+    // No semi-column after `feat` is incorrect
+    let msg5 = "feat add a function";
+
+    // Asserts handle is matching
+    assert!(release_rule_def.handle(msg0).is_ok());
+    assert!(release_rule_def.handle(msg1).is_ok());
+    assert!(release_rule_def.handle(msg2).is_ok());
+
+    // Asserts handle is not matching
+    assert!(release_rule_def.handle(msg3).is_err());
+    assert!(release_rule_def.handle(msg4).is_err());
+    assert!(release_rule_def.handle(msg5).is_err());
+}
+
+#[test]
+/// Tests the `try_parse` function with a PEG grammar containing a syntax error.
+fn test_fail_incorrect_peg_grammar() -> TestResult<()> {
+    // Creates a temporary directory and a temporary file.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let mut file = File::create(&file_path)?;
+
+    // Unit test preparation
+    // Builds a configuration file where the `patch` grammar misses a closing brace.
+    writeln!(&mut file, "[release_rules]")?;
+    writeln!(
+        &mut file,
+        r#"major = {{ format = "peg" , grammar = 'message = {{ SOI ~ "break: " ~ ANY+ ~ EOI }}' }}"#
+    )?;
+    writeln!(
+        &mut file,
+        r#"minor = {{ format = "peg" , grammar = 'message = {{ SOI ~ "feat: " ~ ANY+ ~ EOI }}' }}"#
+    )?;
+    writeln!(
+        &mut file,
+        r#"patch = {{ format = "peg" , grammar = 'message = {{ SOI ~ "fix: " ~ ANY+ ~ EOI' }}"#
+    )?;
+
+    // Asserts the result is a PEG error : the grammar is reported when loading the file.
+    assert!(matches!(try_parse(&file_path), Err(ConfigurationError::PegError(_))));
+    Ok(())
+}
+
+#[test]
+/// Tests the `try_parse` function with branches mapped to pre-release channels.
+fn test_can_parse_branches() -> TestResult<()> {
+    // Creates a temporary directory and temporary files.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let incorrect_file_path = tmp_dir.path().join("incorrect.toml");
+
+    // Unit test preparation
+    // Builds a configuration file with a correct channel and another with a numeric channel.
+    for (path, channel) in [(&file_path, "beta"), (&incorrect_file_path, "1")] {
+        let mut file = File::create(path)?;
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+        writeln!(&mut file, "[branches]")?;
+        writeln!(&mut file, r#"next = "{channel}""#)?;
+    }
+
+    // Execution step
+    let config = try_parse(&file_path)?;
+
+    // Asserts the branch is mapped to its channel and the numeric channel is rejected.
+    assert_eq!(config.branches.get("next"), Some(&"beta".to_string()));
+    assert_eq!(config.branches.get("main"), None);
+    assert!(matches!(
+        try_parse(&incorrect_file_path),
+        Err(ConfigurationError::IncorrectChannel(_))
+    ));
+    Ok(())
+}
+
+#[test]
+/// Tests the `try_parse` function with a custom tag format, and its default value.
+fn test_can_parse_tag_format() -> TestResult<()> {
+    // Creates a temporary directory and temporary files.
+    let tmp_dir = tempdir()?;
+    let default_file_path = tmp_dir.path().join("default.toml");
+    let file_path = tmp_dir.path().join("sleppa.toml");
+
+    // Unit test preparation
+    // Builds configuration files without tag format and with a custom one.
+    for (path, tag_format) in [(&default_file_path, None), (&file_path, Some("mycrate@{version}"))] {
+        let mut file = File::create(path)?;
+        if let Some(tag_format) = tag_format {
+            writeln!(&mut file, r#"tag_format = "{tag_format}""#)?;
+        }
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+    }
+
+    // Asserts the tag formats are correct.
+    assert_eq!(try_parse(&default_file_path)?.tag_format, "v{version}");
+    assert_eq!(try_parse(&file_path)?.tag_format, "mycrate@{version}");
+    Ok(())
+}
+
+#[test]
+/// Tests the `try_parse` function with a monorepo section, and the tag format of its packages.
+fn test_can_parse_monorepo() -> TestResult<()> {
+    // Creates a temporary directory and temporary files.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let incorrect_file_path = tmp_dir.path().join("incorrect.toml");
+
+    // Unit test preparation
+    // Builds a configuration file with a monorepo section and another one whose tag format misses the package.
+    for (path, monorepo) in [
+        (&file_path, r#"scopes = { versioner = "sleppa_versioner" }"#),
+        (&incorrect_file_path, r#"tag_format = "v{version}""#),
+    ] {
+        let mut file = File::create(path)?;
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+        writeln!(&mut file, "[monorepo]")?;
+        writeln!(&mut file, "{monorepo}")?;
+    }
+
+    // Execution step
+    let config = try_parse(&file_path)?;
+
+    // Asserts the monorepo section is correct and the packages have their own tag format.
+    let monorepo = config.monorepo.as_ref().ok_or("missing monorepo section")?;
+    assert_eq!(monorepo.tag_format, "{package}@v{version}");
+    assert_eq!(monorepo.scopes.get("versioner"), Some(&"sleppa_versioner".to_string()));
+    assert_eq!(
+        config.tag_format(Some("sleppa_versioner")),
+        "sleppa_versioner@v{version}"
+    );
+    assert_eq!(config.tag_format(None), "v{version}");
+    assert_eq!(Configuration::new().tag_format(Some("sleppa_versioner")), "v{version}");
+
+    // Asserts a tag format without the package placeholder is rejected.
+    assert!(matches!(
+        try_parse(&incorrect_file_path),
+        Err(ConfigurationError::IncorrectPackageTagFormat(_))
+    ));
+    Ok(())
+}
+
+// Tests the publication section is parsed, with a single registry.
+#[test]
+fn test_can_parse_publish() -> TestResult<()> {
+    // Creates a temporary directory and temporary files.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let incorrect_file_path = tmp_dir.path().join("incorrect.toml");
+
+    // Unit test preparation
+    // Builds a configuration file with a local registry and another one with two registries.
+    for (path, publish) in [
+        (&file_path, r#"directory = "target/registry""#),
+        (
+            &incorrect_file_path,
+            "registry = \"my-registry\"\nindex = \"https://my-intranet:8080/git/index\"",
+        ),
+    ] {
+        let mut file = File::create(path)?;
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+        writeln!(&mut file, "[publish]")?;
+        writeln!(&mut file, "{publish}")?;
+    }
+
+    // Execution step
+    let config = try_parse(&file_path)?;
+
+    // Asserts the publication section is correct.
+    let publish = config.publish.as_ref().ok_or("missing publish section")?;
+    assert_eq!(publish.directory, Some(PathBuf::from("target/registry")));
+    assert_eq!(publish.registry, None);
+    assert_eq!(publish.index, None);
+
+    // Asserts several registries are rejected.
+    assert!(matches!(
+        try_parse(&incorrect_file_path),
+        Err(ConfigurationError::IncorrectRegistry(_))
+    ));
+    Ok(())
+}
+
+// Tests the pull requests section is parsed, with its strategies in order.
+#[test]
+fn test_can_parse_pull_requests() -> TestResult<()> {
+    // Creates a temporary directory and temporary files.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let default_file_path = tmp_dir.path().join("default.toml");
+    let incorrect_file_path = tmp_dir.path().join("incorrect.toml");
+
+    // Unit test preparation
+    // Builds a configuration file with every strategy, another one without the section and an incorrect one.
+    for (path, pull_requests) in [
+        (
+            &file_path,
+            "[pull_requests]\nstrategies = [\"merge_subject\", \"trailer\", \"title_suffix\", \"body_reference\"]\n\
+             trailer = \"PR-URL\"\nanalyze_unreferenced = true",
+        ),
+        (&default_file_path, ""),
+        (&incorrect_file_path, "[pull_requests]\ntrailer = \"Pull request\""),
+    ] {
+        let mut file = File::create(path)?;
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+        writeln!(&mut file, "{pull_requests}")?;
+    }
+
+    // Execution step
+    let config = try_parse(&file_path)?;
+    let default_config = try_parse(&default_file_path)?;
+
+    // Asserts the strategies are read in order, with the trailer and the fallback.
+    assert_eq!(
+        config.pull_requests.strategies,
+        vec![
+            PullRequestStrategy::MergeSubject,
+            PullRequestStrategy::Trailer,
+            PullRequestStrategy::TitleSuffix,
+            PullRequestStrategy::BodyReference
+        ]
+    );
+    assert_eq!(config.pull_requests.trailer, "PR-URL");
+    assert!(config.pull_requests.analyze_unreferenced);
+
+    // Asserts only the title suffix is looked for by default, and the unreferenced commits are ignored.
+    assert_eq!(default_config.pull_requests, PullRequestConfiguration::default());
+    assert_eq!(
+        default_config.pull_requests.strategies,
+        vec![PullRequestStrategy::TitleSuffix]
+    );
+    assert!(!default_config.pull_requests.analyze_unreferenced);
+
+    // Asserts a trailer's key made of several words is rejected.
+    assert!(matches!(
+        try_parse(&incorrect_file_path),
+        Err(ConfigurationError::IncorrectTrailer(_))
+    ));
+    Ok(())
+}
+
+// Tests the parsing of the `[changelog]` section and its `[changelog.sections]` table.
+#[test]
+fn test_can_parse_changelog() -> TestResult<()> {
+    // Creates a temporary directory and temporary files.
+    let tmp_dir = tempdir()?;
+    let file_path = tmp_dir.path().join("sleppa.toml");
+    let default_file_path = tmp_dir.path().join("default.toml");
+
+    // Unit test preparation
+    // Builds a configuration file with a changelog template and its sections, and another one without the section.
+    for (path, changelog) in [
+        (
+            &file_path,
+            "[changelog]\ntemplate = \".github/changelog.md.j2\"\n[changelog.sections]\n\
+             titles = { feat = \"Features\", sec = \"Security\" }\norder = [\"sec\", \"feat\"]\nhidden = [\"style\"]\n\
+             group_by_scope = true\nscopes = { net = \"network\" }",
+        ),
+        (&default_file_path, ""),
+    ] {
+        let mut file = File::create(path)?;
+        writeln!(&mut file, "[release_rules]")?;
+        writeln!(&mut file, r#"major = {{ format = "regex" , grammar = '^break: .*$' }}"#)?;
+        writeln!(&mut file, r#"minor = {{ format = "regex" , grammar = '^feat: .*$' }}"#)?;
+        writeln!(&mut file, r#"patch = {{ format = "regex" , grammar = '^fix: .*$' }}"#)?;
+        writeln!(&mut file, "{changelog}")?;
+    }
+
+    // Execution step
+    let config = try_parse(&file_path)?;
+    let default_config = try_parse(&default_file_path)?;
+
+    // Asserts the template is read, and the built-in template is used by default.
+    assert_eq!(
+        config.changelog.template,
+        Some(std::path::PathBuf::from(".github/changelog.md.j2"))
+    );
+    assert_eq!(config.changelog.sections.titles["sec"], "Security");
+    assert_eq!(config.changelog.sections.order, vec!["sec", "feat"]);
+    assert_eq!(config.changelog.sections.hidden, vec!["style"]);
+    assert_eq!(config.changelog.sections.breaking_title, "Breaking Changes");
+    assert!(config.changelog.sections.group_by_scope);
+    assert_eq!(config.changelog.sections.group_scope("net"), "network");
+    assert_eq!(config.changelog.sections.group_scope("largo"), "largo");
+    assert_eq!(config.changelog.sections.group_scope(""), "other");
+    assert_eq!(default_config.changelog, ChangelogConfiguration::default());
+    assert!(default_config.changelog.sections.order.is_empty());
+    assert!(!default_config.changelog.sections.group_by_scope);
+    Ok(())
+}
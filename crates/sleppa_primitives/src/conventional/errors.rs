@@ -0,0 +1,33 @@
+/// Enumerates errors that could occur when parsing a conventional commit message.
+///
+/// This list is a central structure aiming to define the violations of the
+/// [Conventional Commits](https://www.conventionalcommits.org/en/v1.0.0/) specification.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ConventionalError {
+    // The message is empty
+    #[error("The commit message is empty")]
+    EmptyMessage,
+
+    // The header doesn't start with a type followed by a colon and a space
+    #[error("The header must be formed as 'type(scope)!: description'. Found : {0}")]
+    InvalidHeader(String),
+
+    // The scope is given but empty
+    #[error("The scope must not be empty. Found : {0}")]
+    EmptyScope(String),
+
+    // The description following the type is empty
+    #[error("The description must not be empty. Found : {0}")]
+    EmptyDescription(String),
+
+    // The body or the footers are not separated from the header by a blank line
+    #[error("The header must be followed by a blank line. Found : {0}")]
+    MissingBlankLine(String),
+}
+
+/// Definition of the conventional commit parser result
+pub type ConventionalResult<R> = Result<R, ConventionalError>;
+
+#[cfg(test)]
+/// Result type alias returned by function in unit tests.
+pub type TestResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
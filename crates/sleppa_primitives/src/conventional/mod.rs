@@ -0,0 +1,176 @@
+//! Sleppa conventional commits module
+//!
+//! This module parses a commit message following the [Conventional Commits](https://www.conventionalcommits.org)
+//! specification :
+//!
+//!```text
+//! feat(api)!: drop the first version of the API
+//!
+//! The first version of the API was deprecated for a year.
+//!
+//! BREAKING CHANGE: the `/v1` routes are removed
+//! Refs #42
+//!```
+//!
+//! The message is made of :
+//! - a header formed as `type(scope)!: description`, where the scope and the `!` breaking marker are optional,
+//! - an optional body, separated from the header by a blank line,
+//! - optional footers in the last paragraph, formed as `token: value` or `token #value`. A footer's token is a
+//!   word using `-` instead of spaces, except for `BREAKING CHANGE`. A footer's value can span several lines.
+//!
+//! The [Header] and the [Footer]s are read leniently, so that any message can be turned into a [crate::Commit].
+//! The function [validate] verifies a message strictly follows the specification.
+
+pub mod errors;
+
+use errors::{ConventionalError, ConventionalResult};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Tokens of the footers describing a breaking change
+pub const BREAKING_CHANGE_TOKENS: [&str; 2] = ["BREAKING CHANGE", "BREAKING-CHANGE"];
+
+/// Regular expression of a header, e.g. `feat(api)!: drop v1`
+static HEADER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // The regex is built from a constant expression, hence it can't fail.
+    Regex::new(r"^(?P<type>[a-zA-Z]+)(\((?P<scope>[^()]*)\))?(?P<breaking>!)?:(?P<separator>\s*)(?P<description>.*)$")
+        .unwrap()
+});
+
+/// Regular expression of a footer, e.g. `BREAKING CHANGE: drop v1` or `Refs #42`
+static FOOTER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // The regex is built from a constant expression, hence it can't fail.
+    Regex::new(r"^(?P<token>BREAKING[ -]CHANGE|[a-zA-Z][a-zA-Z0-9-]*)(: | #)(?P<value>.*)$").unwrap()
+});
+
+/// Defines the header of a commit message and its fields
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Header {
+    /// The type of the commit, e.g. `feat`
+    pub commit_type: String,
+    /// The scope of the commit, e.g. `api`, empty if not scoped
+    pub scope: String,
+    /// The header holds the `!` breaking marker
+    pub breaking: bool,
+    /// The description following the type, e.g. `drop the first version of the API`
+    pub description: String,
+}
+
+/// Defines a footer of a commit message and its fields
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Footer {
+    /// The token of the footer, e.g. `BREAKING CHANGE` or `Refs`
+    pub token: String,
+    /// The value of the footer, without its separator, e.g. `42` for `Refs #42`
+    pub value: String,
+}
+
+impl Header {
+    /// Parses the first line of a commit message.
+    ///
+    /// If the line doesn't start with a type followed by a colon, the whole line is the description.
+    pub fn parse(line: &str) -> Self {
+        match HEADER_REGEX.captures(line.trim_end()) {
+            Some(captured) => {
+                let group = |name: &str| match captured.name(name) {
+                    Some(group) => group.as_str().to_string(),
+                    None => "".to_string(),
+                };
+                Header {
+                    commit_type: group("type"),
+                    scope: group("scope"),
+                    breaking: captured.name("breaking").is_some(),
+                    description: group("description").trim().to_string(),
+                }
+            }
+            None => Header {
+                description: line.trim().to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Footer {
+    /// Verifies if the footer describes a breaking change, i.e. its token is `BREAKING CHANGE`.
+    pub fn is_breaking(&self) -> bool {
+        BREAKING_CHANGE_TOKENS.contains(&self.token.as_str())
+    }
+}
+
+/// Splits the lines following the header of a commit message into its body and its footers.
+///
+/// The footers are read from the last paragraph, if its first line is a footer. The lines of this paragraph which
+/// are not footers continue the value of the previous footer.
+pub fn parse_body(lines: &[&str]) -> (String, Vec<Footer>) {
+    // The last paragraph starts after the last blank line
+    let last_paragraph = lines
+        .iter()
+        .rposition(|line| line.trim().is_empty())
+        .map_or(0, |position| position + 1);
+    let footer_start = match lines.get(last_paragraph) {
+        Some(line) if FOOTER_REGEX.is_match(line) => last_paragraph,
+        _ => lines.len(),
+    };
+
+    let mut footers: Vec<Footer> = vec![];
+    for line in &lines[footer_start..] {
+        match (FOOTER_REGEX.captures(line), footers.last_mut()) {
+            (Some(captured), _) => footers.push(Footer {
+                token: captured["token"].to_string(),
+                value: captured["value"].trim().to_string(),
+            }),
+            (None, Some(footer)) => {
+                footer.value.push('\n');
+                footer.value.push_str(line.trim_end());
+            }
+            (None, None) => (),
+        }
+    }
+
+    (lines[..footer_start].join("\n").trim().to_string(), footers)
+}
+
+/// Verifies if a commit message describes a breaking change.
+///
+/// A breaking change is marked by a `!` before the colon of the header, e.g. `feat(api)!: drop v1`, or described
+/// by a `BREAKING CHANGE` footer.
+pub fn is_breaking(message: &str) -> bool {
+    crate::Commit::new("", message).breaking
+}
+
+/// Verifies a commit message strictly follows the Conventional Commits specification.
+///
+/// The first violation found is returned as a [ConventionalError].
+pub fn validate(message: &str) -> ConventionalResult<()> {
+    let mut lines = message.lines();
+    let header = match lines.next() {
+        Some(header) if !header.trim().is_empty() => header.trim_end(),
+        _ => return Err(ConventionalError::EmptyMessage),
+    };
+
+    let captured = match HEADER_REGEX.captures(header) {
+        Some(captured) => captured,
+        None => return Err(ConventionalError::InvalidHeader(header.to_string())),
+    };
+    if captured["description"].trim().is_empty() {
+        return Err(ConventionalError::EmptyDescription(header.to_string()));
+    }
+    if &captured["separator"] != " " {
+        return Err(ConventionalError::InvalidHeader(header.to_string()));
+    }
+    if captured
+        .name("scope")
+        .map_or(false, |scope| scope.as_str().trim().is_empty())
+    {
+        return Err(ConventionalError::EmptyScope(header.to_string()));
+    }
+
+    match lines.next() {
+        Some(line) if !line.trim().is_empty() => Err(ConventionalError::MissingBlankLine(line.to_string())),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests;
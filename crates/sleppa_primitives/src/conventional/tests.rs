@@ -0,0 +1,115 @@
+//! Unit tests
+//!
+//! This testing module implements the unit tests for parsing conventional commit messages.
+use super::{errors::*, *};
+
+// Tests the parsing of a header, with and without its optional parts.
+#[test]
+fn test_can_parse_header() {
+    // Execution step
+    let full = Header::parse("feat(api)!: drop the first version ");
+    let unscoped = Header::parse("fix: correct the parsing");
+    let no_type = Header::parse("Issue to solve (#2)");
+
+    // Asserts the header fields are correct
+    assert_eq!(
+        full,
+        Header {
+            commit_type: "feat".to_string(),
+            scope: "api".to_string(),
+            breaking: true,
+            description: "drop the first version".to_string(),
+        }
+    );
+    assert_eq!(unscoped.commit_type, "fix");
+    assert_eq!(unscoped.scope, "");
+    assert!(!unscoped.breaking);
+    assert_eq!(no_type.commit_type, "");
+    assert_eq!(no_type.description, "Issue to solve (#2)");
+}
+
+// Tests the body and the footers are split, the footers being read from the last paragraph.
+#[test]
+fn test_can_parse_body_and_footers() {
+    // Unit test preparation
+    let lines = [
+        "",
+        "The first version was deprecated.",
+        "",
+        "Note: it is not a footer since it is not the last paragraph.",
+        "",
+        "BREAKING CHANGE: the routes are removed",
+        "and the clients must migrate.",
+        "Refs #42",
+        "Reviewed-by: Z",
+    ];
+
+    // Execution step
+    let (body, footers) = parse_body(&lines);
+    let (only_body, no_footers) = parse_body(&["", "A body without footers."]);
+    let (no_body, only_footers) = parse_body(&["", "BREAKING-CHANGE: environment variables take precedence"]);
+
+    // Asserts the body is kept and the footers are parsed, a value spanning several lines
+    assert_eq!(
+        body,
+        "The first version was deprecated.\n\nNote: it is not a footer since it is not the last paragraph."
+    );
+    assert_eq!(
+        footers,
+        vec![
+            Footer {
+                token: "BREAKING CHANGE".to_string(),
+                value: "the routes are removed\nand the clients must migrate.".to_string(),
+            },
+            Footer {
+                token: "Refs".to_string(),
+                value: "42".to_string(),
+            },
+            Footer {
+                token: "Reviewed-by".to_string(),
+                value: "Z".to_string(),
+            },
+        ]
+    );
+    assert!(footers[0].is_breaking());
+    assert!(!footers[1].is_breaking());
+
+    // Asserts a paragraph which doesn't start with a footer is part of the body
+    assert_eq!(only_body, "A body without footers.");
+    assert!(no_footers.is_empty());
+
+    // Asserts the footers can directly follow the header
+    assert_eq!(no_body, "");
+    assert!(only_footers[0].is_breaking());
+}
+
+// Tests a message is validated against the Conventional Commits specification.
+#[test]
+fn test_can_validate_message() {
+    // Asserts the correct messages are validated
+    assert_eq!(validate("feat(api)!: drop v1"), Ok(()));
+    assert_eq!(validate("fix: correct the parsing\n\nA body.\n\nRefs #42"), Ok(()));
+
+    // Asserts the violations are reported
+    assert_eq!(validate(""), Err(ConventionalError::EmptyMessage));
+    assert_eq!(
+        validate("Issue to solve (#2)"),
+        Err(ConventionalError::InvalidHeader("Issue to solve (#2)".to_string()))
+    );
+    assert_eq!(
+        validate("feat:no space"),
+        Err(ConventionalError::InvalidHeader("feat:no space".to_string()))
+    );
+    assert_eq!(
+        validate("feat(): a feature"),
+        Err(ConventionalError::EmptyScope("feat(): a feature".to_string()))
+    );
+    assert_eq!(
+        validate("feat: "),
+        Err(ConventionalError::EmptyDescription("feat:".to_string()))
+    );
+    assert_eq!(
+        validate("feat: a feature\nwith a body"),
+        Err(ConventionalError::MissingBlankLine("with a body".to_string()))
+    );
+}
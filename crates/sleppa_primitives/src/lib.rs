@@ -3,10 +3,14 @@
 //! This crate provides convenient primitives, like, structures, modules or
 //! reusable code.
 
+pub mod conventional;
+pub mod plugins;
 pub mod repositories;
 
+use conventional::{parse_body, Footer, Header};
+
 /// Defines Commit and its fields used for the changelog
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Commit {
     /// long commit identifier (i.e. 40 digits long SHA-1 hash)
     pub hash: String,
@@ -14,4 +18,65 @@ pub struct Commit {
     pub message: String,
     /// Commit message type value, e.g. `feat`, `break`, `refac`, etc.
     pub commit_type: String,
+    /// Commit message scope value, e.g. `github` for `feat(github): a new feature`, empty if not scoped
+    pub scope: String,
+    /// The commit introduces a breaking change, with the `!` marker or a `BREAKING CHANGE` footer
+    pub breaking: bool,
+    /// Commit message description, e.g. `a new feature` for `feat(github): a new feature`
+    pub description: String,
+    /// Commit message body, between the header and the footers, empty if none
+    pub body: String,
+    /// Commit message footers, e.g. `BREAKING CHANGE: drop v1` or `Refs #42`
+    pub footers: Vec<Footer>,
+    /// Paths of the files changed by the commit, relative to the repository's root, empty if unknown
+    pub files: Vec<String>,
+    /// The commit's author, e.g. a GitHub login or a git author name, empty if unknown
+    pub author: String,
+}
+
+impl Commit {
+    /// Creates a commit from its hash and its message.
+    ///
+    /// The message is parsed as a conventional commit (see the [conventional] module), e.g. the type `feat`, the
+    /// scope `github` and the description `a new feature` are read from `feat(github): a new feature`. If the
+    /// message doesn't start with a type, the type and the scope are left empty and the first line is the
+    /// description.
+    pub fn new(hash: &str, message: &str) -> Self {
+        let lines: Vec<&str> = message.lines().collect();
+        let header = Header::parse(lines.first().unwrap_or(&""));
+        let (body, footers) = parse_body(lines.get(1..).unwrap_or_default());
+
+        Commit {
+            hash: hash.to_string(),
+            message: message.to_string(),
+            commit_type: header.commit_type,
+            scope: header.scope,
+            breaking: header.breaking || footers.iter().any(|footer| footer.is_breaking()),
+            description: header.description,
+            body,
+            footers,
+            files: vec![],
+            author: "".to_string(),
+        }
+    }
+
+    /// Returns the header of the commit, i.e. the first line of its message, e.g. `feat(github): a new feature`.
+    pub fn header(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+
+    /// Sets the paths of the files changed by the commit.
+    pub fn with_files(mut self, files: Vec<String>) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Sets the author of the commit.
+    pub fn with_author(mut self, author: &str) -> Self {
+        self.author = author.to_string();
+        self
+    }
 }
+
+#[cfg(test)]
+mod tests;
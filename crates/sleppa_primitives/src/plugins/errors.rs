@@ -0,0 +1,38 @@
+use super::Step;
+
+/// Enumerates errors that could occur when running plugins.
+///
+/// This list is a central structure aiming to define errors that can occur
+/// while running the steps of a release.
+#[derive(thiserror::Error, Debug)]
+pub enum PluginError {
+    // Chained errors raised by a plugin
+    #[error(transparent)]
+    PluginFailure(#[from] Box<dyn std::error::Error + Send + Sync>),
+
+    // A data needed by a plugin has not been provided by a previous step
+    #[error("Missing data in the release context : {0}")]
+    MissingContext(String),
+
+    // Errors raised by a plugin while running a step of the release
+    #[error("The plugin '{plugin}' failed at the {step} step : {source}")]
+    StepFailure {
+        step: Step,
+        plugin: String,
+        source: Box<PluginError>,
+    },
+}
+
+impl PluginError {
+    /// Wraps any error raised by a plugin.
+    pub fn failure<E: std::error::Error + Send + Sync + 'static>(err: E) -> Self {
+        PluginError::PluginFailure(Box::new(err))
+    }
+}
+
+/// Definition of the plugin result
+pub type PluginResult<R> = Result<R, PluginError>;
+
+#[cfg(test)]
+/// Result type alias returned by function in unit tests.
+pub type TestResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
@@ -0,0 +1,234 @@
+//! Sleppa plugins module
+//!
+//! This module defines the common behavior of the Sleppa plugins, so that they can be chained to produce a release.
+//! Every plugin implements the trait [Plugin], whose lifecycle is modelled on
+//! [semantic-release](https://semantic-release.gitbook.io/semantic-release/usage/plugins) :
+//! - `verify_conditions`: verifies the plugin can run, e.g. the credentials are provided,
+//! - `analyze_commits`: determines the release action and the new tag from the commits,
+//! - `generate_notes`: renders the release notes,
+//! - `prepare`: prepares the release, e.g. writes the changelog file,
+//! - `publish`: publishes the release,
+//! - `success`: notifies the release succeeded,
+//! - `fail`: notifies the release failed.
+//!
+//! Every step has a default implementation doing nothing, hence a plugin only implements the steps it needs.
+//!
+//! The plugins exchange data through the [ReleaseContext], which is filled step after step.
+//! The [ReleasePipeline] runs each step for every plugin, in the order the plugins were added, before moving to the
+//! next step. Therefore, a third party plugin can be added to the pipeline without modifying the orchestration.
+
+pub mod errors;
+
+use async_trait::async_trait;
+use errors::{PluginError, PluginResult};
+use sleppa_configuration::{Configuration, ReleaseAction};
+
+use crate::repositories::RepositoryTag;
+use crate::Commit;
+
+/// Enumerates the steps of a release, in their execution order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Step {
+    /// Verifies the conditions needed to release
+    VerifyConditions,
+    /// Determines the release action and the new tag
+    AnalyzeCommits,
+    /// Renders the release notes
+    GenerateNotes,
+    /// Prepares the release
+    Prepare,
+    /// Publishes the release
+    Publish,
+    /// Notifies the release succeeded
+    Success,
+}
+
+impl std::fmt::Display for Step {
+    /// Prints the name of the step, e.g. "analyze commits".
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Step::VerifyConditions => "verify conditions",
+            Step::AnalyzeCommits => "analyze commits",
+            Step::GenerateNotes => "generate notes",
+            Step::Prepare => "prepare",
+            Step::Publish => "publish",
+            Step::Success => "success",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Defines the release context and its fields
+///
+/// The context is created with the configuration, the repository's last tag and the commits since this tag.
+/// The other fields are filled by the plugins while the release steps are run.
+#[derive(Debug, Default)]
+pub struct ReleaseContext {
+    /// The loaded configuration file
+    pub configuration: Configuration,
+    /// The repository's URL like `https://github.com/USER/REPO`
+    pub repository_url: String,
+    /// The branch being released, e.g. `main`, used to find its pre-release channel in the configuration
+    pub branch: String,
+    /// The package being released in a monorepo, e.g. `sleppa_versioner`, or `None` for the whole repository
+    pub package: Option<String>,
+    /// The repository's last tag, with an empty identifier if the repository has not been released yet
+    pub last_tag: RepositoryTag,
+    /// The commits since the last tag
+    pub commits: Vec<Commit>,
+    /// The release action to apply, set by the `analyze_commits` step if a release is needed
+    pub release_action: Option<ReleaseAction>,
+    /// The new tag, e.g. `v3.2.1`, set by the `analyze_commits` step
+    pub new_tag: Option<String>,
+    /// The version of the new tag without its format, e.g. `3.2.1`, set by the `analyze_commits` step
+    pub new_version: Option<String>,
+    /// The release notes, set by the `generate_notes` step
+    pub notes: String,
+    /// The messages reported to the user by the plugins, e.g. a skipped publication
+    pub reports: Vec<String>,
+    /// In dry-run mode, the plugins compute the release without writing, committing nor publishing anything
+    pub dry_run: bool,
+}
+
+impl ReleaseContext {
+    /// Returns the new tag, or a [PluginError] if no previous step has set it.
+    pub fn new_tag(&self) -> PluginResult<&str> {
+        match &self.new_tag {
+            Some(new_tag) => Ok(new_tag),
+            None => Err(PluginError::MissingContext("new tag".to_string())),
+        }
+    }
+
+    /// Returns the new version, or a [PluginError] if no previous step has set it.
+    pub fn new_version(&self) -> PluginResult<&str> {
+        match &self.new_version {
+            Some(new_version) => Ok(new_version),
+            None => Err(PluginError::MissingContext("new version".to_string())),
+        }
+    }
+}
+
+/// Trait to interface a plugin with the release steps.
+#[async_trait]
+pub trait Plugin: Send {
+    /// Name of the plugin, used to report errors.
+    fn name(&self) -> &str;
+
+    /// Verifies the conditions needed by the plugin to release.
+    async fn verify_conditions(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// Analyzes the commits to determine the release action and the new tag.
+    async fn analyze_commits(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// Generates the release notes.
+    async fn generate_notes(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// Prepares the release.
+    async fn prepare(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// Publishes the release.
+    async fn publish(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// Notifies the release succeeded.
+    async fn success(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        Ok(())
+    }
+
+    /// Notifies the release failed with the given error.
+    async fn fail(&mut self, _context: &mut ReleaseContext, _error: &PluginError) -> PluginResult<()> {
+        Ok(())
+    }
+}
+
+/// Defines the release pipeline and its plugins
+///
+/// The plugins are run in the order they were added, e.g. the plugin determining the release action must be added
+/// before the one calculating the new tag.
+#[derive(Default)]
+pub struct ReleasePipeline {
+    /// The plugins run at each step
+    pub plugins: Vec<Box<dyn Plugin>>,
+}
+
+impl ReleasePipeline {
+    /// Implementation of the `new` method : `ReleasePipeline::new()`.
+    pub fn new() -> Self {
+        ReleasePipeline::default()
+    }
+
+    /// Adds a plugin at the end of the pipeline.
+    pub fn with_plugin<P: Plugin + 'static>(mut self, plugin: P) -> Self {
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    /// Runs the release steps until the given last step, included.
+    ///
+    /// The steps following `analyze_commits` are skipped if no release action has been found.
+    /// If a step fails, the `fail` step of every plugin is run and the error is returned.
+    pub async fn run(&mut self, context: &mut ReleaseContext, last_step: Step) -> PluginResult<()> {
+        match self.run_steps(context, last_step).await {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                for plugin in self.plugins.iter_mut() {
+                    // A failing notification must not hide the error which made the release fail.
+                    let _ = plugin.fail(context, &err).await;
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Runs every step until the given last step, for every plugin.
+    async fn run_steps(&mut self, context: &mut ReleaseContext, last_step: Step) -> PluginResult<()> {
+        let steps = [
+            Step::VerifyConditions,
+            Step::AnalyzeCommits,
+            Step::GenerateNotes,
+            Step::Prepare,
+            Step::Publish,
+            Step::Success,
+        ];
+
+        for step in steps.into_iter().filter(|step| *step <= last_step) {
+            for plugin in self.plugins.iter_mut() {
+                let result = match step {
+                    Step::VerifyConditions => plugin.verify_conditions(context).await,
+                    Step::AnalyzeCommits => plugin.analyze_commits(context).await,
+                    Step::GenerateNotes => plugin.generate_notes(context).await,
+                    Step::Prepare => plugin.prepare(context).await,
+                    Step::Publish => plugin.publish(context).await,
+                    Step::Success => plugin.success(context).await,
+                };
+
+                if let Err(err) = result {
+                    return Err(PluginError::StepFailure {
+                        step,
+                        plugin: plugin.name().to_string(),
+                        source: Box::new(err),
+                    });
+                }
+            }
+
+            // No release is needed
+            if step == Step::AnalyzeCommits && context.release_action.is_none() {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
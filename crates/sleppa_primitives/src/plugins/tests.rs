@@ -0,0 +1,152 @@
+//! Unit tests
+//!
+//! This testing module implements the unit tests for testing the plugins module routines.
+use super::{errors::TestResult, *};
+use std::sync::{Arc, Mutex};
+
+/// Plugin recording the steps it runs
+struct RecordingPlugin {
+    /// Name of the plugin
+    name: String,
+    /// Steps run by every recording plugin, as `{name}:{step}`
+    records: Arc<Mutex<Vec<String>>>,
+    /// Release action found when analyzing the commits
+    release_action: Option<ReleaseAction>,
+    /// Step at which the plugin fails
+    failing_step: Option<Step>,
+}
+
+impl RecordingPlugin {
+    /// Records a step and fails if it is the failing step.
+    fn record(&self, step: Step) -> PluginResult<()> {
+        self.records.lock().unwrap().push(format!("{}:{step}", self.name));
+        match self.failing_step {
+            Some(failing_step) if failing_step == step => Err(PluginError::MissingContext("failure".to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for RecordingPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn verify_conditions(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        self.record(Step::VerifyConditions)
+    }
+
+    async fn analyze_commits(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        if self.release_action.is_some() {
+            context.release_action = self.release_action;
+        }
+        self.record(Step::AnalyzeCommits)
+    }
+
+    async fn publish(&mut self, _context: &mut ReleaseContext) -> PluginResult<()> {
+        self.record(Step::Publish)
+    }
+
+    async fn fail(&mut self, _context: &mut ReleaseContext, _error: &PluginError) -> PluginResult<()> {
+        self.records.lock().unwrap().push(format!("{}:fail", self.name));
+        Ok(())
+    }
+}
+
+// Builds a pipeline with two recording plugins, the first one finding the given release action.
+fn pipeline_constructor(
+    release_action: Option<ReleaseAction>,
+    failing_step: Option<Step>,
+) -> (ReleasePipeline, Arc<Mutex<Vec<String>>>) {
+    let records = Arc::new(Mutex::new(vec![]));
+    let pipeline = ReleasePipeline::new()
+        .with_plugin(RecordingPlugin {
+            name: "first".to_string(),
+            records: records.clone(),
+            release_action,
+            failing_step: None,
+        })
+        .with_plugin(RecordingPlugin {
+            name: "second".to_string(),
+            records: records.clone(),
+            release_action: None,
+            failing_step,
+        });
+    (pipeline, records)
+}
+
+// Tests the steps are run in order, for every plugin in the order they were added.
+#[tokio::test]
+async fn test_can_run_steps_in_order() -> TestResult<()> {
+    // Unit test preparation
+    let (mut pipeline, records) = pipeline_constructor(Some(ReleaseAction::Minor), None);
+    let mut context = ReleaseContext::default();
+
+    // Execution step
+    pipeline.run(&mut context, Step::Success).await?;
+
+    // Asserts the steps are run in order and the context is shared
+    assert_eq!(
+        *records.lock().unwrap(),
+        vec![
+            "first:verify conditions",
+            "second:verify conditions",
+            "first:analyze commits",
+            "second:analyze commits",
+            "first:publish",
+            "second:publish",
+        ]
+    );
+    assert_eq!(context.release_action, Some(ReleaseAction::Minor));
+    Ok(())
+}
+
+// Tests the steps following `analyze_commits` are skipped when no release is needed, or when they follow the
+// last step to run.
+#[tokio::test]
+async fn test_can_stop_steps() -> TestResult<()> {
+    // Unit test preparation
+    let (mut no_release_pipeline, no_release_records) = pipeline_constructor(None, None);
+    let (mut pipeline, records) = pipeline_constructor(Some(ReleaseAction::Patch), None);
+
+    // Execution step
+    no_release_pipeline
+        .run(&mut ReleaseContext::default(), Step::Success)
+        .await?;
+    pipeline
+        .run(&mut ReleaseContext::default(), Step::AnalyzeCommits)
+        .await?;
+
+    // Asserts the publish step is not run
+    assert!(!no_release_records
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|record| record.ends_with("publish")));
+    assert!(!records.lock().unwrap().iter().any(|record| record.ends_with("publish")));
+    Ok(())
+}
+
+// Tests every plugin is notified when a step fails, and the error tells the failing plugin and step.
+#[tokio::test]
+async fn test_can_fail() {
+    // Unit test preparation
+    let (mut pipeline, records) = pipeline_constructor(Some(ReleaseAction::Major), Some(Step::Publish));
+
+    // Execution step
+    let result = pipeline.run(&mut ReleaseContext::default(), Step::Success).await;
+
+    // Asserts the error and the notifications are correct
+    match result {
+        Err(PluginError::StepFailure { step, plugin, .. }) => {
+            assert_eq!(step, Step::Publish);
+            assert_eq!(plugin, "second");
+        }
+        _ => panic!("the pipeline must fail at the publish step"),
+    }
+    assert_eq!(
+        records.lock().unwrap()[5..],
+        ["second:publish", "first:fail", "second:fail"]
+    );
+}
@@ -12,6 +12,14 @@ pub enum RepositoryError {
     #[error(transparent)]
     ApiError(#[from] octocrab::Error),
 
+    // Chained errors occurring when sending requests to a REST API
+    #[error(transparent)]
+    HttpError(#[from] reqwest::Error),
+
+    // Chained errors occuring when processing with a local git repository
+    #[error(transparent)]
+    GitError(#[from] git2::Error),
+
     // Chained errors occurring when processing regular expressions
     #[error(transparent)]
     RegexError(#[from] regex::Error),
@@ -20,6 +28,26 @@ pub enum RepositoryError {
     #[error(transparent)]
     ParsingError(#[from] std::num::ParseIntError),
 
+    // Chained errors occurring when parsing the date of a commit
+    #[error(transparent)]
+    DateError(#[from] time::error::Parse),
+
+    // Chained errors occurring when converting the timestamp of a commit into a date
+    #[error(transparent)]
+    TimestampError(#[from] time::error::ComponentRange),
+
+    // The date of a commit is not returned by the forge
+    #[error("The commit {0} has no date")]
+    MissingDate(String),
+
+    // The credentials of the GitHub client are incorrect
+    #[error("GitHub credentials are incorrect : {0}")]
+    InvalidCredentials(String),
+
+    // An environment variable needed to reach a forge is not set
+    #[error("Environment variable {0} is not set")]
+    MissingVariable(String),
+
     // Message is not correct
     #[error("Pull request name is incorrect : {0}")]
     InvalidMessage(String),
@@ -27,3 +55,7 @@ pub enum RepositoryError {
 
 /// Definition of the commit analyzer result
 pub type RepositoryResult<R> = Result<R, RepositoryError>;
+
+#[cfg(test)]
+/// Result type alias returned by function in unit tests.
+pub type TestResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
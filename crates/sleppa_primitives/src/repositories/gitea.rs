@@ -0,0 +1,382 @@
+//! Wrapper around Gitea API
+//!
+//! The [REST API](https://docs.gitea.com/api) of Gitea, or of its Forgejo fork, closely resembles the GitHub one :
+//! it is used to retrieve the tags, the pull requests and their inner commits since the last release. The requests
+//! are sent to the `/api/v1` routes of the instance by a [RestClient], authenticated with a token in the
+//! `Authorization` header.
+//!
+//! As on GitHub, the commits of the default branch are expanded into the inner commits of the pull request they
+//! merged, found from their message with the configured strategies (see the [super::pull_requests] module), e.g. the
+//! squash commits named after their pull request like `Issue to solve (#2)`. The commits referencing no pull
+//! request are ignored, or analyzed themselves if the configuration asks for it.
+//!
+//! The listings are paginated with the `limit` parameter, the instances capping it at 50 items per page by default.
+
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sleppa_configuration::PullRequestConfiguration;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{
+    errors::{RepositoryError, RepositoryResult},
+    github::GithubRepository,
+    pull_requests::get_pull_request_number,
+    rest_client::RestClient,
+    Repository, RepositoryTag,
+};
+use crate::Commit;
+
+/// Number of items requested per page, the maximum allowed by the default configuration of Gitea
+pub const PER_PAGE: &str = "50";
+
+/// A minimal Gitea repository structure
+///
+/// A Gitea repository comes with an owner and a name, like a GitHub one, and its requests are sent to the API of
+/// its instance by its [RestClient].
+#[derive(Default, Debug, Clone)]
+pub struct GiteaRepository {
+    /// Represents the owner
+    pub owner: String,
+    /// Represents the name of the repository
+    pub repo: String,
+    /// The client sending the requests to the Gitea API
+    pub client: RestClient,
+    /// Defines how the pull request merged by a commit is found
+    pub pull_requests: PullRequestConfiguration,
+    /// The name of the default branch, requested once when first needed, unless set
+    pub default_branch: OnceCell<String>,
+}
+
+/// A tag, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaTag {
+    /// Name of the tag, e.g. `v1.0.0`
+    name: String,
+    /// Commit of the tag
+    commit: GiteaCommitReference,
+}
+
+/// A reference to a commit, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaCommitReference {
+    /// Hash of the commit
+    sha: String,
+}
+
+/// A commit, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaCommit {
+    /// Hash of the commit
+    sha: String,
+    /// Details of the commit
+    commit: GiteaCommitDetails,
+    /// Gitea user who authored the commit, if known
+    #[serde(default)]
+    author: Option<GiteaUser>,
+    /// Parents of the commit, the first one being on the same branch
+    #[serde(default)]
+    parents: Vec<GiteaCommitReference>,
+}
+
+/// A user, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaUser {
+    /// Login of the user
+    login: String,
+}
+
+/// The details of a commit, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaCommitDetails {
+    /// Message of the commit
+    message: String,
+    /// Git committer of the commit, if known
+    #[serde(default)]
+    committer: Option<GiteaSignature>,
+}
+
+/// The git signature of a commit, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaSignature {
+    /// Date of the signature, in the RFC 3339 format
+    date: String,
+}
+
+/// A commit with its changed files, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaCommitFiles {
+    /// Files changed by the commit
+    #[serde(default)]
+    files: Vec<GiteaFile>,
+}
+
+/// A file changed by a pull request or a commit, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaFile {
+    /// Path of the file, relative to the repository's root
+    filename: String,
+}
+
+/// A comparison between two commits, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaComparison {
+    /// Number of commits of the head which are not in the base
+    total_commits: u64,
+}
+
+/// A repository, as returned by the Gitea API
+#[derive(Debug, Deserialize)]
+struct GiteaRepositoryDetails {
+    /// Name of the default branch of the repository
+    default_branch: String,
+}
+
+/// A release to create, as sent to the Gitea API
+#[derive(Debug, Serialize)]
+struct GiteaRelease<'a> {
+    /// Name of the tag, created if it doesn't exist
+    tag_name: &'a str,
+    /// Branch the tag is created from, the default branch if empty
+    target_commitish: &'a str,
+    /// Name of the release, the tag's name
+    name: &'a str,
+    /// Release notes, in Markdown
+    body: &'a str,
+    /// The release is a pre-release
+    prerelease: bool,
+}
+
+impl GiteaRepository {
+    /// Implementation of the `new` method : `GiteaRepository::new("https://gitea.example.com", "owner", "repo")`.
+    ///
+    /// The base URL is the one of the instance, whose API is served under `/api/v1`.
+    pub fn new(base_url: &str, owner: &str, repo: &str) -> Self {
+        GiteaRepository {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            client: RestClient::new(&format!("{}/api/v1", base_url.trim_end_matches('/'))),
+            pull_requests: PullRequestConfiguration::default(),
+            default_branch: OnceCell::new(),
+        }
+    }
+
+    /// Creates a repository from the environment.
+    ///
+    /// The base URL of the instance is read from `GITEA_URL`, and the requests are authenticated with the
+    /// `GITEA_TOKEN` if set.
+    pub fn from_env(owner: &str, repo: &str) -> RepositoryResult<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let base_url = var("GITEA_URL").ok_or_else(|| RepositoryError::MissingVariable("GITEA_URL".to_string()))?;
+
+        let repository = GiteaRepository::new(&base_url, owner, repo);
+        Ok(match var("GITEA_TOKEN") {
+            Some(token) => repository.with_token(&token),
+            None => repository,
+        })
+    }
+
+    /// Authenticates the requests with an access token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.client = self
+            .client
+            .with_authentication("Authorization", &format!("token {token}"));
+        self
+    }
+
+    /// Returns the route of the repository, e.g. `/repos/owner/repo`.
+    fn repository_route(&self) -> String {
+        format!("/repos/{}/{}", self.owner, self.repo)
+    }
+
+    /// Get the name of the repository's default branch.
+    ///
+    /// The default branch is requested once, then kept for the following calls.
+    pub async fn get_default_branch(&self) -> RepositoryResult<String> {
+        if let Some(default_branch) = self.default_branch.get() {
+            return Ok(default_branch.clone());
+        }
+
+        let repository: GiteaRepositoryDetails = self.client.get(&self.repository_route(), &[]).await?;
+        Ok(self.default_branch.get_or_init(|| repository.default_branch).clone())
+    }
+
+    /// Get the default branch's commits since the tag's commit, from the newest to the oldest
+    ///
+    /// The commits are retrieved from the head of the default branch (see [GiteaRepository::get_commits_between]).
+    pub async fn get_commits_until_tag(&self, tag_sha: &str) -> RepositoryResult<Vec<Commit>> {
+        self.get_commits_between(tag_sha, "").await
+    }
+
+    /// Get the commits from the newest commit to the oldest one, excluded, from the newest to the oldest
+    ///
+    /// The commits are listed from the newest commit, or from the head of the default branch if its sha is empty.
+    /// The next pages are retrieved until the oldest commit is found, or until the last page if its sha is empty.
+    /// Only the first-parent commits are kept, the ones brought by a merge being listed by their pull request.
+    pub async fn get_commits_between(&self, oldest_sha: &str, newest_sha: &str) -> RepositoryResult<Vec<Commit>> {
+        let route = format!("{}/commits", self.repository_route());
+        let mut query = vec![("limit", PER_PAGE), ("stat", "false"), ("files", "false")];
+        if !newest_sha.is_empty() {
+            query.push(("sha", newest_sha));
+        }
+
+        let mut taken: Vec<Commit> = vec![];
+        let mut next_sha: Option<String> = None;
+        for page in 1.. {
+            let commits: Vec<GiteaCommit> = self.client.get_page(&route, &query, page).await?;
+            if commits.is_empty() {
+                break;
+            }
+            let commits = commits.into_iter().map(|commit| {
+                let taken_commit = Commit::new(&commit.sha, &commit.commit.message);
+                let first_parent = commit.parents.into_iter().next().map(|parent| parent.sha);
+                (commit.sha, first_parent, taken_commit)
+            });
+            if GithubRepository::take_first_parents_until_tag(commits, oldest_sha, &mut next_sha, &mut taken) {
+                break;
+            }
+        }
+
+        Ok(taken)
+    }
+
+    /// Get pull request's inner commits, from the oldest to the newest
+    pub async fn get_inner_commits_from_pull_request(&self, pr_number: u64) -> RepositoryResult<Vec<Commit>> {
+        let route = format!("{}/pulls/{pr_number}/commits", self.repository_route());
+        let query = [("limit", PER_PAGE), ("stat", "false"), ("files", "false")];
+
+        let commits: Vec<GiteaCommit> = self.client.get_all_pages(&route, &query).await?;
+        Ok(commits
+            .into_iter()
+            .map(|commit| {
+                let author = commit.author.map(|author| author.login).unwrap_or_default();
+                Commit::new(&commit.sha, &commit.commit.message).with_author(&author)
+            })
+            .collect())
+    }
+
+    /// Get the files changed by a pull request
+    pub async fn get_files_from_pull_request(&self, pr_number: u64) -> RepositoryResult<Vec<String>> {
+        let route = format!("{}/pulls/{pr_number}/files", self.repository_route());
+
+        let files: Vec<GiteaFile> = self.client.get_all_pages(&route, &[("limit", PER_PAGE)]).await?;
+        Ok(files.into_iter().map(|file| file.filename).collect())
+    }
+
+    /// Get the files changed by a commit
+    pub async fn get_files_from_commit(&self, sha: &str) -> RepositoryResult<Vec<String>> {
+        let route = format!("{}/git/commits/{sha}", self.repository_route());
+
+        let commit: GiteaCommitFiles = self.client.get(&route, &[("stat", "false"), ("files", "true")]).await?;
+        Ok(commit.files.into_iter().map(|file| file.filename).collect())
+    }
+
+    /// Creates a release of the tag, with the release notes as its body
+    ///
+    /// If the tag doesn't exist yet, it is created from the given branch, or from the default branch if the branch
+    /// is empty.
+    pub async fn create_release(
+        &self,
+        tag: &str,
+        branch: &str,
+        notes: &str,
+        pre_release: bool,
+    ) -> RepositoryResult<()> {
+        let release = GiteaRelease {
+            tag_name: tag,
+            target_commitish: branch,
+            name: tag,
+            body: notes,
+            prerelease: pre_release,
+        };
+
+        let route = format!("{}/releases", self.repository_route());
+        let _: serde::de::IgnoredAny = self.client.post(&route, &release).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository for GiteaRepository {
+    /// Get the repository's last tag and its sha
+    ///
+    /// If the repository has no tag yet, an empty one is returned.
+    async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag> {
+        Ok(self.get_tags().await?.into_iter().next().unwrap_or_default())
+    }
+
+    /// Get the repository's tags and their sha, all the pages being retrieved
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>> {
+        let route = format!("{}/tags", self.repository_route());
+        let tags: Vec<GiteaTag> = self.client.get_all_pages(&route, &[("limit", PER_PAGE)]).await?;
+
+        Ok(tags
+            .into_iter()
+            .map(|tag| RepositoryTag {
+                identifier: tag.name,
+                hash: tag.commit.sha,
+            })
+            .collect())
+    }
+
+    /// Verifies the tag's commit is an ancestor of the branch's head
+    ///
+    /// The tag is compared to the branch : it is an ancestor if it has no commit which is not in the branch. If the
+    /// branch is empty, the repository's default branch is used.
+    async fn is_ancestor(&self, tag: &RepositoryTag, branch: &str) -> RepositoryResult<bool> {
+        let branch = match branch {
+            "" => self.get_default_branch().await?,
+            branch => branch.to_string(),
+        };
+
+        let route = format!("{}/compare/{branch}...{}", self.repository_route(), tag.hash);
+        let comparison: GiteaComparison = self.client.get(&route, &[]).await?;
+        Ok(comparison.total_commits == 0)
+    }
+
+    /// Get inner commits between two tags
+    ///
+    /// The pull requests merged from the oldest tag to the newest one are expanded into their inner commits, which
+    /// share the files changed by their pull request. If the oldest tag is empty, the whole history is analyzed, and
+    /// if the newest tag is empty, the history of the default branch is. The commits referencing no pull request are
+    /// ignored, or analyzed themselves with their changed files if the configuration asks for it.
+    async fn get_inner_commits_between(
+        &self,
+        oldest: &RepositoryTag,
+        newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>> {
+        let mut inner_commits: Vec<Commit> = vec![];
+
+        for commit in self.get_commits_between(&oldest.hash, &newest.hash).await? {
+            let pr_number = match get_pull_request_number(&commit.message, &self.pull_requests)? {
+                Some(pr_number) => pr_number,
+                None if self.pull_requests.analyze_unreferenced => {
+                    let files = self.get_files_from_commit(&commit.hash).await?;
+                    inner_commits.push(commit.with_files(files));
+                    continue;
+                }
+                None => continue, // Ignore the commit referencing no pull request
+            };
+
+            let files = self.get_files_from_pull_request(pr_number).await?;
+            for commit in self.get_inner_commits_from_pull_request(pr_number).await? {
+                inner_commits.push(commit.with_files(files.clone()));
+            }
+        }
+        Ok(inner_commits)
+    }
+
+    /// Get the date of the tag's commit, the date of its committer
+    async fn get_tag_date(&self, tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime> {
+        let route = format!("{}/git/commits/{}", self.repository_route(), tag.hash);
+
+        let commit: GiteaCommit = self
+            .client
+            .get(&route, &[("stat", "false"), ("files", "false")])
+            .await?;
+        match commit.commit.committer {
+            Some(committer) => Ok(OffsetDateTime::parse(&committer.date, &Rfc3339)?),
+            None => Err(RepositoryError::MissingDate(tag.hash.clone())),
+        }
+    }
+}
@@ -7,10 +7,21 @@
 //! inner commit as this request is not implemented by the semantic API.
 //!
 //! Octocrab semantic API are built as `Builder` structures using methods with multiple optionnal parameters e.g. :
-//! `let mut page = octocrab::instance().issues("octocrab", "repo").list().creator("octocrab").per_page(50).send().await?;`
+//! `let mut page = octocrab.issues("octocrab", "repo").list().creator("octocrab").per_page(50).send().await?;`
 //!
 //! This builder returns a listing represented as [octocrab::Page] structure.
 //!
+//! The octocrab instance is the one of the repository's [GithubClient], authenticated and pointed at the GitHub
+//! Enterprise server if configured so (see the [super::github_client] module).
+//!
+//! Every listing is paginated fully, requesting [PER_PAGE] items per page and following the `next` links of the pages.
+//! The repository's commits are the exception : their pages are read until the last tag's commit is found, even
+//! if it is many pages back, so that the older history is not requested.
+//!
+//! The pull request merged by a commit is found from its message with the configured strategies (see the
+//! [super::pull_requests] module). A commit referencing no pull request is ignored, or analyzed itself if the
+//! configuration asks for it.
+//!
 //! The pull requests are [RepoCommit] structure. It contains a field \[RepoCommit::commit\] where the message is
 //! stored inside a [octocrab::models::repos::RepoCommitPage] structure along with other fields.
 //!
@@ -23,12 +34,23 @@
 
 use async_trait::async_trait;
 use octocrab::models::repos::RepoCommit;
+use octocrab::Page;
+use once_cell::sync::OnceCell;
 use regex::Regex;
+use serde::Deserialize;
+use sleppa_configuration::PullRequestConfiguration;
+use time::OffsetDateTime;
 
 use super::{
     errors::{RepositoryError, RepositoryResult},
+    github_client::GithubClient,
+    pull_requests::get_pull_request_number,
     Repository, RepositoryTag,
 };
+use crate::Commit;
+
+/// Number of items requested per page, the maximum allowed by the GitHub API
+pub const PER_PAGE: u8 = 100;
 
 /// A minimal GitHub repository structure
 ///
@@ -37,12 +59,49 @@ use super::{
 /// - a name
 ///
 /// The path is then like `/repos/{owner}/{name}/` for the GitHub's API
-#[derive(Default, Debug)]
+///
+/// The requests are sent by its [GithubClient], unauthenticated by default.
+#[derive(Default, Debug, Clone)]
 pub struct GithubRepository {
     /// Represents the owner
     pub owner: String,
     /// Represents the name of the repository
     pub repo: String,
+    /// The client sending the requests to the GitHub API
+    pub client: GithubClient,
+    /// Defines how the pull request merged by a commit is found
+    pub pull_requests: PullRequestConfiguration,
+    /// The name of the default branch, requested once when first needed, unless set
+    pub default_branch: OnceCell<String>,
+}
+
+/// A repository's details, as returned by the GitHub API
+#[derive(Debug, Deserialize)]
+struct RepositoryDetails {
+    /// Name of the default branch of the repository
+    default_branch: Option<String>,
+}
+
+/// A comparison between two commits, as returned by the GitHub API
+#[derive(Debug, Deserialize)]
+struct Comparison {
+    /// Status of the head compared to the base, i.e. `ahead`, `behind`, `diverged` or `identical`
+    status: String,
+}
+
+/// A file changed by a pull request, as returned by the GitHub API
+#[derive(Debug, Deserialize)]
+struct PullRequestFile {
+    /// Path of the file, relative to the repository's root
+    filename: String,
+}
+
+/// A commit with its changed files, as returned by the GitHub API
+#[derive(Debug, Deserialize)]
+struct CommitFiles {
+    /// Files changed by the commit
+    #[serde(default)]
+    files: Vec<PullRequestFile>,
 }
 
 #[async_trait]
@@ -50,101 +109,238 @@ impl Repository for GithubRepository {
     /// Get the reposiroty's last tag and its sha
     ///
     /// If the repository has no tag yet, an empty one is created.
-    /// Else the repository's tag is used to create a new [RepositoryTag].
-    ///
-    /// The octocrab semantic API returns a [octocrab::Page] of [octocrab::Tag].
+    /// Else the first tag listed by the API is used to create a new [RepositoryTag]. The last release tag is rather
+    /// resolved by SemVer order with `sleppa_versioner::find_last_tag`.
     async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag> {
+        match self.get_tags().await?.into_iter().next() {
+            Some(last_tag) => Ok(last_tag),
+            // Creates an empty [RepositoryTag] if no tag is found.
+            None => Ok(RepositoryTag {
+                identifier: "".to_string(),
+                hash: "".to_string(),
+            }),
+        }
+    }
+
+    /// Get the repository's tags and their sha
+    ///
+    /// The octocrab semantic API returns a [octocrab::Page] of [octocrab::models::repos::Tag], whose next pages
+    /// are all retrieved.
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>> {
         // Get all the tag of a repository.
-        let page_tags = octocrab::instance()
+        let octocrab = self.client.octocrab();
+        let page_tags = octocrab
             .repos(&self.owner, &self.repo)
             .list_tags()
+            .per_page(PER_PAGE)
             .send()
             .await?;
+        let tags = octocrab.all_pages(page_tags).await?;
 
-        if page_tags.items.is_empty() {
-            // Creates an empty [RepositoryTag] if no tag is found.
-            let last_tag = RepositoryTag {
-                identifier: "".to_string(),
-                hash: "".to_string(),
-            };
-            Ok(last_tag)
-        } else {
-            // Creates a [RepositoryTag] with the tag found.
-            let last_tag = &page_tags.items[0];
-            Ok(RepositoryTag {
-                identifier: last_tag.name.to_string(),
-                hash: last_tag.commit.sha.to_string(),
+        Ok(tags
+            .iter()
+            .map(|tag| RepositoryTag {
+                identifier: tag.name.to_string(),
+                hash: tag.commit.sha.to_string(),
             })
-        }
+            .collect())
     }
 
-    /// Get inner commit messages since the last tag
+    /// Verifies the tag's commit is an ancestor of the branch's head
     ///
-    /// From a repository's name and owner, all the inner commits' messages since the last tag are retrieved.
-    /// If no tag is found, all the [RepoCommit] are analyzed.
-    /// If the name of the pull request is malformed, it is then ignored.
-    async fn get_inner_commits(&self) -> RepositoryResult<Vec<String>> {
-        let mut inner_commits_messages: Vec<String> = vec![];
+    /// The tag's commit is compared to the branch thanks to [octocrab] HTTP API : it is an ancestor if the branch
+    /// is ahead of it or identical. If the branch is empty, the repository's default branch is used.
+    async fn is_ancestor(&self, tag: &RepositoryTag, branch: &str) -> RepositoryResult<bool> {
+        let octocrab = self.client.octocrab();
+        let branch = match branch {
+            "" => self.get_default_branch().await?,
+            branch => branch.to_string(),
+        };
 
-        // Get the repository's tag.
-        let tag = self.get_last_tag().await?;
+        // Format the route to the comparison
+        let repo_address = format! {"/repos/{}/{}/compare/{}...{}", &self.owner, &self.repo, &tag.hash, branch};
+        let comparison: Comparison = octocrab.get(repo_address, None::<&()>).await?;
+        Ok(comparison.status == "ahead" || comparison.status == "identical")
+    }
 
-        // Get the repository's pull request from the tag.
-        let repo_commits = self.get_pull_request(&tag.hash).await?;
+    /// Get inner commits between two tags
+    ///
+    /// From a repository's name and owner, all the inner commits from the oldest tag to the newest one are
+    /// retrieved. If the oldest tag is empty, all the [RepoCommit] are analyzed, and if the newest tag is empty, the
+    /// ones of the default branch are.
+    /// If a commit references no pull request, it is ignored, or analyzed itself with its changed files if the
+    /// configuration asks for it.
+    ///
+    /// The inner commits share the files changed by their pull request.
+    async fn get_inner_commits_between(
+        &self,
+        oldest: &RepositoryTag,
+        newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>> {
+        let mut inner_commits: Vec<Commit> = vec![];
+
+        // Get the repository's commits between the tags.
+        let repo_commits = self.get_commits_between(&oldest.hash, &newest.hash).await?;
 
-        // Extracts the pull request's number from its name.
-        // If the pull request's name is malformed, the procces ignores it.
-        for name in repo_commits {
-            let pr_number = match GithubRepository::get_pull_request_number_from_its_name(&name) {
-                Ok(pr_number) => pr_number, // Get the pull request's number
-                Err(_err) => continue,      // Ignore malformed pull request's name
+        // Extracts the pull request's number from the commit's message.
+        for commit in repo_commits {
+            let pr_number = match get_pull_request_number(&commit.message, &self.pull_requests)? {
+                Some(pr_number) => pr_number, // Get the pull request's number
+                None if self.pull_requests.analyze_unreferenced => {
+                    // Analyzes the commit itself
+                    let files = self.get_files_from_commit(&commit.hash).await?;
+                    inner_commits.push(commit.with_files(files));
+                    continue;
+                }
+                None => continue, // Ignore the commit referencing no pull request
             };
 
-            // Get the inner commits from the pull request's number found previously
-            let inner_commits = self.get_inner_commits_from_pull_request(pr_number).await?;
+            // Get the inner commits and the changed files from the pull request's number found previously
+            let repo_commits = self.get_inner_commits_from_pull_request(pr_number).await?;
+            let files = self.get_files_from_pull_request(pr_number).await?;
 
-            // Pushes inner commit messages to the result array
-            for commit in inner_commits {
-                inner_commits_messages.push(commit.commit.message.to_string());
+            // Pushes inner commits to the result array, authored by their GitHub user or their git author
+            for repo_commit in repo_commits {
+                let author = match (&repo_commit.author, &repo_commit.commit.author) {
+                    (Some(author), _) => author.login.clone(),
+                    (None, Some(git_author)) => git_author.user.name.clone(),
+                    (None, None) => "".to_string(),
+                };
+                inner_commits.push(
+                    Commit::new(&repo_commit.sha, &repo_commit.commit.message)
+                        .with_files(files.clone())
+                        .with_author(&author),
+                );
             }
         }
-        Ok(inner_commits_messages)
+        Ok(inner_commits)
+    }
+
+    /// Get the date of the tag's commit
+    ///
+    /// The commit is retrieved thanks to [octocrab] HTTP API, its date being the one of its committer.
+    async fn get_tag_date(&self, tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime> {
+        let repo_address = format! {"/repos/{}/{}/commits/{}", &self.owner, &self.repo, &tag.hash};
+
+        let repo_commit: RepoCommit = self.client.octocrab().get(repo_address, None::<&()>).await?;
+        match repo_commit.commit.committer.and_then(|committer| committer.date) {
+            Some(date) => Ok(OffsetDateTime::from_unix_timestamp(date.timestamp())?),
+            None => Err(RepositoryError::MissingDate(tag.hash.clone())),
+        }
     }
 }
 
 impl GithubRepository {
+    /// Get the name of the repository's default branch, `main` if unknown.
+    ///
+    /// The default branch is requested once, then kept for the following calls.
+    pub async fn get_default_branch(&self) -> RepositoryResult<String> {
+        if let Some(default_branch) = self.default_branch.get() {
+            return Ok(default_branch.clone());
+        }
+
+        let repo_address = format!("/repos/{}/{}", &self.owner, &self.repo);
+        let details: RepositoryDetails = self.client.octocrab().get(repo_address, None::<&()>).await?;
+        let default_branch = details.default_branch.unwrap_or_else(|| "main".to_string());
+        Ok(self.default_branch.get_or_init(|| default_branch).clone())
+    }
+
     /// Get the pull request's name
     ///
     /// In a squash-and-merge strategy, the merged commits are pull-request. Therefore their name
     /// must be well formed e.g. "Issue to solve (#2)" in order to retrieve their number.
     ///
-    /// The octocrab Semantic API returns a [octocrab::Page] of [RepoCommit].
+    /// The messages of the commits since the tag are retrieved (see [GithubRepository::get_commits_until_tag]).
     pub async fn get_pull_request(&self, tag_sha: &str) -> RepositoryResult<Vec<String>> {
-        let repo_commits = octocrab::instance()
-            .repos(&self.owner, &self.repo)
-            .list_commits()
-            .send()
-            .await?;
+        let commits = self.get_commits_until_tag(tag_sha).await?;
+        Ok(commits.into_iter().map(|commit| commit.message).collect())
+    }
 
-        let mut pull_request_messages: Vec<String> = vec![];
+    /// Get the commits of the default branch since the tag's commit, from the newest to the oldest
+    ///
+    /// The commits are retrieved from the head of the default branch (see [GithubRepository::get_commits_between]).
+    pub async fn get_commits_until_tag(&self, tag_sha: &str) -> RepositoryResult<Vec<Commit>> {
+        self.get_commits_between(tag_sha, "").await
+    }
 
-        if tag_sha.is_empty() {
-            // Retrieves all the repository commit of a repository if there is no tag
-            for item in repo_commits.items {
-                pull_request_messages.push(item.commit.message.to_string())
+    /// Get the commits from the newest commit to the oldest one, excluded, from the newest to the oldest
+    ///
+    /// The octocrab Semantic API returns a [octocrab::Page] of [RepoCommit], starting from the newest commit or from
+    /// the head of the default branch if its sha is empty. The next pages are retrieved until the oldest commit is
+    /// found, or until the last page if its sha is empty.
+    ///
+    /// Only the first-parent commits are kept, the ones brought by a merge being listed by their pull request.
+    pub async fn get_commits_between(&self, oldest_sha: &str, newest_sha: &str) -> RepositoryResult<Vec<Commit>> {
+        let octocrab = self.client.octocrab();
+        let repos = octocrab.repos(&self.owner, &self.repo);
+        let mut list_commits = repos.list_commits().per_page(PER_PAGE);
+        if !newest_sha.is_empty() {
+            list_commits = list_commits.sha(newest_sha);
+        }
+        let mut page = list_commits.send().await?;
+
+        let mut commits: Vec<Commit> = vec![];
+        let mut next_sha: Option<String> = None;
+        loop {
+            let items = page.take_items().into_iter().map(|item| {
+                let commit = Commit::new(&item.sha, &item.commit.message);
+                let first_parent = item.parents.first().and_then(|parent| parent.sha.clone());
+                (item.sha, first_parent, commit)
+            });
+            if GithubRepository::take_first_parents_until_tag(items, oldest_sha, &mut next_sha, &mut commits) {
+                break;
             }
-        } else {
-            // If a tag is found, only the repository commits until this tag are retrieved
-            for item in repo_commits.items {
-                if item.sha != tag_sha {
-                    pull_request_messages.push(item.commit.message.to_string())
-                } else {
-                    break;
-                }
+
+            page = match octocrab.get_page::<RepoCommit>(&page.next).await? {
+                Some(next_page) => next_page,
+                None => break,
+            };
+        }
+
+        Ok(commits)
+    }
+
+    /// Takes the commits, e.g. their messages, until the tag's commit, returning if it was found.
+    ///
+    /// The commits are given as `(sha, commit)` pairs, from the newest to the oldest. If the tag's sha is empty,
+    /// all the commits are taken.
+    pub fn take_until_tag<T>(
+        commits: impl IntoIterator<Item = (String, T)>,
+        tag_sha: &str,
+        taken: &mut Vec<T>,
+    ) -> bool {
+        for (sha, commit) in commits {
+            if !tag_sha.is_empty() && sha == tag_sha {
+                return true;
             }
+            taken.push(commit);
         }
+        false
+    }
 
-        Ok(pull_request_messages)
+    /// Takes the first-parent commits until the tag's commit, returning if it was found.
+    ///
+    /// The commits are given as `(sha, first parent's sha, commit)` triples, from the newest to the oldest. Following
+    /// the first parents from the newest commit, the commits brought by a merge are skipped, the sha of the next
+    /// first parent being kept in `next_sha` across the pages. If the tag's sha is empty, all the first-parent
+    /// commits are taken.
+    pub fn take_first_parents_until_tag<T>(
+        commits: impl IntoIterator<Item = (String, Option<String>, T)>,
+        tag_sha: &str,
+        next_sha: &mut Option<String>,
+        taken: &mut Vec<T>,
+    ) -> bool {
+        for (sha, first_parent, commit) in commits {
+            if !tag_sha.is_empty() && sha == tag_sha {
+                return true;
+            }
+            if next_sha.as_ref().map_or(false, |next_sha| *next_sha != sha) {
+                continue;
+            }
+            *next_sha = Some(first_parent.unwrap_or_default());
+            taken.push(commit);
+        }
+        false
     }
 
     /// Get the pull request's number from its name
@@ -186,8 +382,34 @@ impl GithubRepository {
         // Format the route to the repository
         let repo_address = format! {"/repos/{}/{}/pulls/{}/commits", &self.owner, &self.repo, pr_number};
 
-        // Retrieve the inner commits with the octocrab HTTP API
-        let commits = octocrab::instance().get(repo_address, None::<&()>).await?;
-        Ok(commits)
+        // Retrieve the inner commits with the octocrab HTTP API, page after page
+        let octocrab = self.client.octocrab();
+        let page: Page<RepoCommit> = octocrab.get(repo_address, Some(&[("per_page", PER_PAGE)])).await?;
+        Ok(octocrab.all_pages(page).await?)
+    }
+
+    /// Get the files changed by a pull request
+    ///
+    /// From the pull request's number, the paths of its changed files are retrieved thanks to [octocrab] HTTP API.
+    pub async fn get_files_from_pull_request(&self, pr_number: u64) -> RepositoryResult<Vec<String>> {
+        // Format the route to the repository
+        let repo_address = format! {"/repos/{}/{}/pulls/{}/files", &self.owner, &self.repo, pr_number};
+
+        // Retrieve the changed files with the octocrab HTTP API, page after page
+        let octocrab = self.client.octocrab();
+        let page: Page<PullRequestFile> = octocrab.get(repo_address, Some(&[("per_page", PER_PAGE)])).await?;
+        let files = octocrab.all_pages(page).await?;
+        Ok(files.into_iter().map(|file| file.filename).collect())
+    }
+
+    /// Get the files changed by a commit
+    ///
+    /// From the commit's hash, the paths of its changed files are retrieved thanks to [octocrab] HTTP API.
+    pub async fn get_files_from_commit(&self, sha: &str) -> RepositoryResult<Vec<String>> {
+        // Format the route to the commit
+        let repo_address = format! {"/repos/{}/{}/commits/{}", &self.owner, &self.repo, sha};
+
+        let commit: CommitFiles = self.client.octocrab().get(repo_address, None::<&()>).await?;
+        Ok(commit.files.into_iter().map(|file| file.filename).collect())
     }
 }
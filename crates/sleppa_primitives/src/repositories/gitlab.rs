@@ -0,0 +1,352 @@
+//! Wrapper around GitLab API
+//!
+//! The [REST API](https://docs.gitlab.com/ee/api/rest/) of GitLab, on gitlab.com or on a self-hosted instance, is
+//! used to retrieve the tags, the merge requests and their inner commits since the last release. The requests are
+//! sent by a [RestClient], authenticated with the `PRIVATE-TOKEN` header, or with the `JOB-TOKEN` header in GitLab
+//! CI/CD.
+//!
+//! A project is identified by its path, e.g. `group/subgroup/project`, which is URL-encoded in the routes of the API.
+//!
+//! The merge requests are found from the commits of the default branch, whose message references them as
+//! `See merge request group/project!42`, as written by GitLab in its merge commits and in its squash commits when
+//! the merge request's description is kept. The merge requests are then expanded into their inner commits.
+//! The commits not referencing a merge request are ignored, or analyzed themselves if the `analyze_unreferenced`
+//! field of the `[pull_requests]` section of the configuration file is set.
+
+use async_trait::async_trait;
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sleppa_configuration::PullRequestConfiguration;
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{
+    errors::{RepositoryError, RepositoryResult},
+    github::GithubRepository,
+    rest_client::RestClient,
+    Repository, RepositoryTag,
+};
+use crate::Commit;
+
+/// Base URL of the API of gitlab.com
+pub const GITLAB_API_URL: &str = "https://gitlab.com/api/v4";
+
+/// Number of items requested per page, the maximum allowed by the GitLab API
+pub const PER_PAGE: &str = "100";
+
+/// Regular expression of the reference of a merge request, e.g. `See merge request group/project!42`
+static MERGE_REQUEST_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // The regex is built from a constant expression, hence it can't fail.
+    Regex::new(r"See merge request (?P<project>[^\s!]+)!(?P<iid>[0-9]+)").unwrap()
+});
+
+/// A minimal GitLab repository structure
+///
+/// A GitLab repository is identified by the path of its project, e.g. `group/project`. The requests are sent to
+/// the API of its GitLab instance by its [RestClient].
+#[derive(Default, Debug, Clone)]
+pub struct GitlabRepository {
+    /// Represents the path of the project, e.g. `group/project`
+    pub project: String,
+    /// The client sending the requests to the GitLab API
+    pub client: RestClient,
+    /// Defines whether a commit referencing no merge request is analyzed, the strategies finding a pull request
+    /// being specific to GitHub and Gitea
+    pub pull_requests: PullRequestConfiguration,
+    /// The name of the default branch, requested once when first needed, unless set
+    pub default_branch: OnceCell<String>,
+}
+
+/// A tag, as returned by the GitLab API
+#[derive(Debug, Deserialize)]
+struct GitlabTag {
+    /// Name of the tag, e.g. `v1.0.0`
+    name: String,
+    /// Commit of the tag
+    commit: GitlabCommit,
+}
+
+/// A commit, as returned by the GitLab API
+#[derive(Debug, Deserialize)]
+struct GitlabCommit {
+    /// Hash of the commit
+    id: String,
+    /// Message of the commit
+    #[serde(default)]
+    message: String,
+    /// Name of the commit's author
+    #[serde(default)]
+    author_name: String,
+    /// Date the commit was committed, in the RFC 3339 format
+    #[serde(default)]
+    committed_date: Option<String>,
+}
+
+/// A file changed by a merge request, as returned by the GitLab API
+#[derive(Debug, Deserialize)]
+struct GitlabDiff {
+    /// Path of the file after the change, relative to the repository's root
+    new_path: String,
+}
+
+/// A project, as returned by the GitLab API
+#[derive(Debug, Deserialize)]
+struct GitlabProject {
+    /// Name of the default branch of the project
+    default_branch: Option<String>,
+}
+
+/// A release to create, as sent to the GitLab API
+#[derive(Debug, Serialize)]
+struct GitlabRelease<'a> {
+    /// Name of the release, the tag's name
+    name: &'a str,
+    /// Name of the tag, created if it doesn't exist
+    tag_name: &'a str,
+    /// Branch or commit the tag is created from
+    #[serde(rename = "ref")]
+    reference: &'a str,
+    /// Release notes, in Markdown
+    description: &'a str,
+}
+
+impl GitlabRepository {
+    /// Implementation of the `new` method : `GitlabRepository::new("https://gitlab.com/api/v4", "group/project")`.
+    pub fn new(base_url: &str, project: &str) -> Self {
+        GitlabRepository {
+            project: project.to_string(),
+            client: RestClient::new(base_url),
+            pull_requests: PullRequestConfiguration::default(),
+            default_branch: OnceCell::new(),
+        }
+    }
+
+    /// Creates a repository from the environment of GitLab CI/CD.
+    ///
+    /// The base URL of the API is read from `CI_API_V4_URL`, gitlab.com being used if it is not set. The requests
+    /// are authenticated with the `GITLAB_TOKEN` if set, or with the `CI_JOB_TOKEN` of the running job.
+    pub fn from_env(project: &str) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let repository = GitlabRepository::new(&var("CI_API_V4_URL").unwrap_or(GITLAB_API_URL.to_string()), project);
+
+        match (var("GITLAB_TOKEN"), var("CI_JOB_TOKEN")) {
+            (Some(token), _) => repository.with_token(&token),
+            (None, Some(job_token)) => GitlabRepository {
+                client: repository.client.with_authentication("JOB-TOKEN", &job_token),
+                ..repository
+            },
+            (None, None) => repository,
+        }
+    }
+
+    /// Authenticates the requests with a personal, group or project access token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.client = self.client.with_authentication("PRIVATE-TOKEN", token);
+        self
+    }
+
+    /// Returns the route of the project, with its URL-encoded path, e.g. `/projects/group%2Fproject`.
+    fn project_route(project: &str) -> String {
+        format!("/projects/{}", project.replace('/', "%2F"))
+    }
+
+    /// Get the name of the project's default branch, `main` if unknown.
+    ///
+    /// The default branch is requested once, then kept for the following calls.
+    pub async fn get_default_branch(&self) -> RepositoryResult<String> {
+        if let Some(default_branch) = self.default_branch.get() {
+            return Ok(default_branch.clone());
+        }
+
+        let project: GitlabProject = self.client.get(&Self::project_route(&self.project), &[]).await?;
+        let default_branch = project.default_branch.unwrap_or_else(|| "main".to_string());
+        Ok(self.default_branch.get_or_init(|| default_branch).clone())
+    }
+
+    /// Get the default branch's commits since the tag's commit, from the newest to the oldest
+    ///
+    /// The commits are retrieved from the head of the default branch (see [GitlabRepository::get_commits_between]).
+    pub async fn get_merge_commits(&self, tag_sha: &str) -> RepositoryResult<Vec<Commit>> {
+        self.get_commits_between(tag_sha, "").await
+    }
+
+    /// Get the commits from the newest commit to the oldest one, excluded, from the newest to the oldest
+    ///
+    /// The commits are listed from the newest commit, or from the head of the default branch if its sha is empty.
+    /// The next pages are retrieved until the oldest commit is found, or until the last page if its sha is empty.
+    /// Only the first-parent commits are listed, the ones brought by a merge being listed by their merge request.
+    pub async fn get_commits_between(&self, oldest_sha: &str, newest_sha: &str) -> RepositoryResult<Vec<Commit>> {
+        let route = format!("{}/repository/commits", Self::project_route(&self.project));
+        let mut query = vec![("per_page", PER_PAGE), ("first_parent", "true")];
+        if !newest_sha.is_empty() {
+            query.push(("ref_name", newest_sha));
+        }
+
+        let mut taken: Vec<Commit> = vec![];
+        for page in 1.. {
+            let commits: Vec<GitlabCommit> = self.client.get_page(&route, &query, page).await?;
+            if commits.is_empty() {
+                break;
+            }
+            let commits = commits.into_iter().map(|commit| {
+                let taken_commit = Commit::new(&commit.id, &commit.message);
+                (commit.id, taken_commit)
+            });
+            if GithubRepository::take_until_tag(commits, oldest_sha, &mut taken) {
+                break;
+            }
+        }
+
+        Ok(taken)
+    }
+
+    /// Get the merge request referenced by a commit message
+    ///
+    /// The merge request is referenced as `See merge request group/project!42`, where `group/project` is the path of
+    /// its project and `42` its internal identifier in the project.
+    pub fn get_merge_request_from_its_message(message: &str) -> RepositoryResult<(String, u64)> {
+        match MERGE_REQUEST_REGEX.captures(message) {
+            Some(captured) => Ok((captured["project"].to_string(), captured["iid"].parse::<u64>()?)),
+            None => Err(RepositoryError::InvalidMessage(
+                "No merge request is referenced".to_string(),
+            )),
+        }
+    }
+
+    /// Get the merge request's inner commits, from the oldest to the newest
+    pub async fn get_inner_commits_from_merge_request(&self, project: &str, iid: u64) -> RepositoryResult<Vec<Commit>> {
+        let route = format!("{}/merge_requests/{iid}/commits", Self::project_route(project));
+
+        // The GitLab API lists the commits from the newest to the oldest
+        let commits: Vec<GitlabCommit> = self.client.get_all_pages(&route, &[("per_page", PER_PAGE)]).await?;
+        Ok(commits
+            .into_iter()
+            .rev()
+            .map(|commit| Commit::new(&commit.id, &commit.message).with_author(&commit.author_name))
+            .collect())
+    }
+
+    /// Get the files changed by a merge request
+    pub async fn get_files_from_merge_request(&self, project: &str, iid: u64) -> RepositoryResult<Vec<String>> {
+        let route = format!("{}/merge_requests/{iid}/diffs", Self::project_route(project));
+
+        let diffs: Vec<GitlabDiff> = self.client.get_all_pages(&route, &[("per_page", PER_PAGE)]).await?;
+        Ok(diffs.into_iter().map(|diff| diff.new_path).collect())
+    }
+
+    /// Get the files changed by a commit
+    pub async fn get_files_from_commit(&self, sha: &str) -> RepositoryResult<Vec<String>> {
+        let route = format!("{}/repository/commits/{sha}/diff", Self::project_route(&self.project));
+
+        let diffs: Vec<GitlabDiff> = self.client.get_all_pages(&route, &[("per_page", PER_PAGE)]).await?;
+        Ok(diffs.into_iter().map(|diff| diff.new_path).collect())
+    }
+
+    /// Creates a release of the tag, with the release notes as its description
+    ///
+    /// If the tag doesn't exist yet, it is created from the given branch, or from the project's default branch if
+    /// the branch is empty.
+    pub async fn create_release(&self, tag: &str, branch: &str, notes: &str) -> RepositoryResult<()> {
+        let branch = match branch {
+            "" => self.get_default_branch().await?,
+            branch => branch.to_string(),
+        };
+        let release = GitlabRelease {
+            name: tag,
+            tag_name: tag,
+            reference: &branch,
+            description: notes,
+        };
+
+        let route = format!("{}/releases", Self::project_route(&self.project));
+        let _: serde::de::IgnoredAny = self.client.post(&route, &release).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Repository for GitlabRepository {
+    /// Get the repository's last tag and its sha
+    ///
+    /// The GitLab API lists the tags from the most recently updated. If the repository has no tag yet, an empty
+    /// one is returned.
+    async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag> {
+        Ok(self.get_tags().await?.into_iter().next().unwrap_or_default())
+    }
+
+    /// Get the repository's tags and their sha, all the pages being retrieved
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>> {
+        let route = format!("{}/repository/tags", Self::project_route(&self.project));
+        let tags: Vec<GitlabTag> = self.client.get_all_pages(&route, &[("per_page", PER_PAGE)]).await?;
+
+        Ok(tags
+            .into_iter()
+            .map(|tag| RepositoryTag {
+                identifier: tag.name,
+                hash: tag.commit.id,
+            })
+            .collect())
+    }
+
+    /// Verifies the tag's commit is an ancestor of the branch's head
+    ///
+    /// The tag's commit is an ancestor if it is the merge base of the tag and the branch. If the branch is empty,
+    /// the project's default branch is used.
+    async fn is_ancestor(&self, tag: &RepositoryTag, branch: &str) -> RepositoryResult<bool> {
+        let branch = match branch {
+            "" => self.get_default_branch().await?,
+            branch => branch.to_string(),
+        };
+
+        let route = format!("{}/repository/merge_base", Self::project_route(&self.project));
+        let merge_base: GitlabCommit = self
+            .client
+            .get(&route, &[("refs[]", &tag.hash), ("refs[]", &branch)])
+            .await?;
+        Ok(merge_base.id == tag.hash)
+    }
+
+    /// Get inner commits between two tags
+    ///
+    /// The merge requests referenced by the commits from the oldest tag to the newest one are expanded into their
+    /// inner commits, which share the files changed by their merge request. If the oldest tag is empty, the whole
+    /// history is analyzed, and if the newest tag is empty, the history of the default branch is.
+    /// The commits referencing no merge request are ignored, or analyzed themselves with their changed files if the
+    /// configuration asks for it.
+    async fn get_inner_commits_between(
+        &self,
+        oldest: &RepositoryTag,
+        newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>> {
+        let mut inner_commits: Vec<Commit> = vec![];
+
+        for commit in self.get_commits_between(&oldest.hash, &newest.hash).await? {
+            let (project, iid) = match GitlabRepository::get_merge_request_from_its_message(&commit.message) {
+                Ok(merge_request) => merge_request,
+                Err(_err) if self.pull_requests.analyze_unreferenced => {
+                    let files = self.get_files_from_commit(&commit.hash).await?;
+                    inner_commits.push(commit.with_files(files));
+                    continue;
+                }
+                Err(_err) => continue, // Ignore the commits referencing no merge request
+            };
+
+            let files = self.get_files_from_merge_request(&project, iid).await?;
+            for commit in self.get_inner_commits_from_merge_request(&project, iid).await? {
+                inner_commits.push(commit.with_files(files.clone()));
+            }
+        }
+        Ok(inner_commits)
+    }
+
+    /// Get the date of the tag's commit, the date it was committed
+    async fn get_tag_date(&self, tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime> {
+        let route = format!("{}/repository/commits/{}", Self::project_route(&self.project), tag.hash);
+
+        let commit: GitlabCommit = self.client.get(&route, &[]).await?;
+        match commit.committed_date {
+            Some(date) => Ok(OffsetDateTime::parse(&date, &Rfc3339)?),
+            None => Err(RepositoryError::MissingDate(tag.hash.clone())),
+        }
+    }
+}
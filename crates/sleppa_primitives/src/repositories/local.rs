@@ -0,0 +1,250 @@
+//! Wrapper around a local git repository
+//!
+//! [git2](https://crates.io/crates/git2) crate is used to read the tags and the commit history directly from a
+//! `.git` directory on disk, hence without any network access.
+//!
+//! The same squash-and-merge strategy as for [super::github::GithubRepository] is expected. The inner commits of a
+//! pull request are not available locally, they are read from the squash commit's body instead. By default, GitHub
+//! writes the inner commit messages as a bullet list in the body of a squash commit, e.g. :
+//!
+//!```text
+//! Issue to solve (#2)
+//!
+//! * feat(script): add a script
+//!
+//! * feat: add a feature
+//!```
+//!
+//! The inner commits read from a squash commit's body share the hash, the author and the changed files of the squash
+//! commit.
+//!
+//! The pull request merged by a commit is found from its message with the configured strategies (see the
+//! [super::pull_requests] module). The inner commits of a merge commit, e.g. `Merge pull request #12 from
+//! owner/branch`, are the commits it merged, which are available locally. The commits referencing no pull request
+//! are ignored, or analyzed themselves if the configuration asks for it.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use git2::{Oid, Sort};
+use sleppa_configuration::PullRequestConfiguration;
+use time::{OffsetDateTime, UtcOffset};
+
+use super::{errors::RepositoryResult, pull_requests::get_pull_request_number, Repository, RepositoryTag};
+use crate::Commit;
+
+/// A local git repository structure
+///
+/// A local repository is defined by the path of its working directory or of its `.git` directory.
+#[derive(Default, Debug, Clone)]
+pub struct LocalGitRepository {
+    /// Represents the path of the repository
+    pub path: PathBuf,
+    /// Defines how the pull request merged by a commit is found
+    pub pull_requests: PullRequestConfiguration,
+}
+
+#[async_trait]
+impl Repository for LocalGitRepository {
+    /// Get the repository's last tag and its sha
+    ///
+    /// The last tag is the first tag found while walking the history from `HEAD`.
+    /// If the repository has no tag yet, an empty one is created.
+    async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag> {
+        match self.get_tags().await?.into_iter().next() {
+            Some(last_tag) => Ok(last_tag),
+            // Creates an empty [RepositoryTag] if no tag is found.
+            None => Ok(RepositoryTag {
+                identifier: "".to_string(),
+                hash: "".to_string(),
+            }),
+        }
+    }
+
+    /// Get the repository's tags and their sha
+    ///
+    /// Only the tags of the history of `HEAD` are retrieved, in the order they are found while walking the
+    /// history from `HEAD`. The tags of a same commit are sorted by name.
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>> {
+        let repository = git2::Repository::open(&self.path)?;
+
+        // Maps the tagged commits to their tag names
+        let mut tagged_commits: HashMap<Oid, Vec<String>> = HashMap::new();
+        for name in repository.tag_names(None)?.iter() {
+            // Ignores the tag names which are not valid UTF-8
+            let name = match name? {
+                Some(name) => name,
+                None => continue,
+            };
+            let commit = repository
+                .revparse_single(&format!("refs/tags/{name}"))?
+                .peel_to_commit()?;
+            tagged_commits.entry(commit.id()).or_default().push(name.to_string());
+        }
+
+        // Walks the history from `HEAD` to collect the tagged commits
+        let mut revwalk = repository.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+
+        let mut tags: Vec<RepositoryTag> = vec![];
+        for oid in revwalk {
+            let oid = oid?;
+            if let Some(names) = tagged_commits.get_mut(&oid) {
+                names.sort();
+                tags.extend(names.iter().map(|name| RepositoryTag {
+                    identifier: name.to_string(),
+                    hash: oid.to_string(),
+                }));
+            }
+        }
+        Ok(tags)
+    }
+
+    /// Verifies the tag's commit is an ancestor of the branch's head
+    ///
+    /// The branch is resolved as a git revision, e.g. `main`. If it is empty or unknown, e.g. in a detached checkout,
+    /// `HEAD` is used instead.
+    async fn is_ancestor(&self, tag: &RepositoryTag, branch: &str) -> RepositoryResult<bool> {
+        let repository = git2::Repository::open(&self.path)?;
+
+        let head = match repository.revparse_single(branch) {
+            Ok(object) if !branch.is_empty() => object.peel_to_commit()?.id(),
+            _ => repository.head()?.peel_to_commit()?.id(),
+        };
+        let tagged = Oid::from_str(&tag.hash)?;
+
+        Ok(head == tagged || repository.graph_descendant_of(head, tagged)?)
+    }
+
+    /// Get inner commits between two tags
+    ///
+    /// All the commits of the first-parent history from the oldest tag to the newest one are retrieved, from the
+    /// oldest to the newest. The inner commits of a squash commit are read from its body, and the ones of a merge
+    /// commit are the commits it merged. If the oldest tag is empty, the whole history is analyzed, and if the newest
+    /// tag is empty, the history until `HEAD` is.
+    /// If a commit references no pull request, it is ignored, or analyzed itself if the configuration asks for it.
+    ///
+    /// The inner commits share the hash, the author and the files changed by their squash or merge commit.
+    async fn get_inner_commits_between(
+        &self,
+        oldest: &RepositoryTag,
+        newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>> {
+        let repository = git2::Repository::open(&self.path)?;
+
+        // Walks the first-parent history from the newest tag, or from `HEAD`, to the oldest tag
+        let mut revwalk = repository.revwalk()?;
+        match newest.hash.as_str() {
+            "" => revwalk.push_head()?,
+            hash => revwalk.push(Oid::from_str(hash)?)?,
+        }
+        if !oldest.hash.is_empty() {
+            revwalk.hide(Oid::from_str(&oldest.hash)?)?;
+        }
+        revwalk.simplify_first_parent()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+        let mut inner_commits: Vec<Commit> = vec![];
+        for oid in revwalk {
+            let commit = repository.find_commit(oid?)?;
+            let message = commit.message().unwrap_or_default();
+
+            let hash = commit.id().to_string();
+            let author = commit.author().name().unwrap_or_default().to_string();
+            let files = LocalGitRepository::get_files(&repository, &commit)?;
+
+            // Ignores the commit if it references no pull request, unless it is analyzed itself
+            if get_pull_request_number(message, &self.pull_requests)?.is_none() {
+                if self.pull_requests.analyze_unreferenced {
+                    inner_commits.push(Commit::new(&hash, message).with_files(files).with_author(&author));
+                }
+                continue;
+            }
+
+            let inner_messages = match commit.parent_count() {
+                0 | 1 => LocalGitRepository::get_inner_messages_from_body(message),
+                _ => LocalGitRepository::get_merged_messages(&repository, &commit)?,
+            };
+            for inner_message in inner_messages {
+                inner_commits.push(
+                    Commit::new(&hash, &inner_message)
+                        .with_files(files.clone())
+                        .with_author(&author),
+                );
+            }
+        }
+        Ok(inner_commits)
+    }
+
+    /// Get the date of the tag's commit, in the time zone of its committer
+    async fn get_tag_date(&self, tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime> {
+        let repository = git2::Repository::open(&self.path)?;
+        let time = repository.find_commit(Oid::from_str(&tag.hash)?)?.time();
+
+        let offset = UtcOffset::from_whole_seconds(time.offset_minutes() * 60)?;
+        Ok(OffsetDateTime::from_unix_timestamp(time.seconds())?.to_offset(offset))
+    }
+}
+
+impl LocalGitRepository {
+    /// Get the paths of the files changed by a commit, compared to its first parent.
+    fn get_files(repository: &git2::Repository, commit: &git2::Commit) -> RepositoryResult<Vec<String>> {
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repository.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+        Ok(diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect())
+    }
+
+    /// Get the messages of the commits merged by a merge commit, from the oldest to the newest
+    ///
+    /// The merged commits are the ones reachable from the merged branch, i.e. the second parent, which are not
+    /// reachable from the first parent.
+    fn get_merged_messages(repository: &git2::Repository, commit: &git2::Commit) -> RepositoryResult<Vec<String>> {
+        let mut revwalk = repository.revwalk()?;
+        revwalk.push(commit.parent_id(1)?)?;
+        revwalk.hide(commit.parent_id(0)?)?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+        let mut messages = vec![];
+        for oid in revwalk {
+            let merged = repository.find_commit(oid?)?;
+            messages.push(merged.message().unwrap_or_default().trim_end().to_string());
+        }
+        Ok(messages)
+    }
+
+    /// Get the inner commit messages of a squash commit from its message
+    ///
+    /// Every line starting with `* ` begins a new inner commit message, the following lines being part of it until
+    /// the next one. The pull request's name, on the first line, and the `Co-authored-by:` trailers are ignored.
+    pub fn get_inner_messages_from_body(message: &str) -> Vec<String> {
+        let mut inner_messages: Vec<String> = vec![];
+
+        for line in message.lines().skip(1) {
+            if let Some(inner_message) = line.strip_prefix("* ") {
+                // A new inner commit starts
+                inner_messages.push(inner_message.to_string());
+            } else if line.starts_with("Co-authored-by:") {
+                continue;
+            } else if let Some(inner_message) = inner_messages.last_mut() {
+                // The line belongs to the body of the current inner commit
+                inner_message.push('\n');
+                inner_message.push_str(line);
+            }
+        }
+
+        inner_messages
+            .iter()
+            .map(|inner_message| inner_message.trim_end().to_string())
+            .collect()
+    }
+}
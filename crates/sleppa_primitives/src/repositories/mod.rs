@@ -6,15 +6,33 @@
 //! Also, a common [RepositoryTag] structure defines the tag of a git repository system with
 //! its two basic properties, namely, its identifier (e.g. `v3.2.1`) and its associated hash.
 //!
-//! It natively implements a [github::GithubRepository] link to work with GitHub.
+//! It natively implements a [github::GithubRepository] link to work with GitHub, a [gitlab::GitlabRepository]
+//! link to work with GitLab, a [gitea::GiteaRepository] link to work with Gitea or Forgejo, and a
+//! [local::LocalGitRepository] link to work offline with a git repository on disk.
+//!
+//! The pull requests merged by the commits of a repository are found from their messages, with the strategies of the
+//! configuration file (see the [pull_requests] module).
 
 pub mod errors;
+pub mod gitea;
 pub mod github;
+pub mod github_client;
+pub mod gitlab;
+pub mod local;
+pub mod pull_requests;
+pub mod rest_client;
 
 use async_trait::async_trait;
 use errors::RepositoryResult;
+use time::OffsetDateTime;
+
+use crate::Commit;
+
+/// User-agent of the requests sent to the forges
+pub const DEFAULT_USER_AGENT: &str = concat!("sleppa/", env!("CARGO_PKG_VERSION"));
 
 /// Definition of a repository's tag.
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct RepositoryTag {
     /// Value of the tag e.g. `v3.2.1` where `v{major}.{minor}.{patch}`
     pub identifier: String,
@@ -28,6 +46,39 @@ pub trait Repository {
     /// Get the repository's last tag and its sha.
     async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag>;
 
-    /// Get inner commit messages since the last tag.
-    async fn get_inner_commits(&self) -> RepositoryResult<Vec<String>>;
+    /// Get the repository's tags and their sha, from the newest to the oldest.
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>>;
+
+    /// Verifies the tag's commit is an ancestor of the branch's head, or of the default head if the branch is empty.
+    ///
+    /// A tag on the head itself is an ancestor.
+    async fn is_ancestor(&self, tag: &RepositoryTag, branch: &str) -> RepositoryResult<bool>;
+
+    /// Get inner commits since the given tag, or since the beginning of the history if its hash is empty.
+    async fn get_inner_commits_since(&self, tag: &RepositoryTag) -> RepositoryResult<Vec<Commit>> {
+        self.get_inner_commits_between(tag, &RepositoryTag::default()).await
+    }
+
+    /// Get inner commits between two tags, i.e. the commits of the newest tag's history which are not in the
+    /// oldest tag's one.
+    ///
+    /// The history is taken since its beginning if the oldest tag's hash is empty, and until the head of the default
+    /// branch if the newest tag's hash is empty.
+    async fn get_inner_commits_between(
+        &self,
+        oldest: &RepositoryTag,
+        newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>>;
+
+    /// Get the date of the tag's commit, i.e. the date it was committed.
+    async fn get_tag_date(&self, tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime>;
+
+    /// Get inner commits since the last tag.
+    async fn get_inner_commits(&self) -> RepositoryResult<Vec<Commit>> {
+        let tag = self.get_last_tag().await?;
+        self.get_inner_commits_since(&tag).await
+    }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,114 @@
+//! Pull requests references
+//!
+//! The commits of the released branch are expanded into the inner commits of the pull request they merged. The
+//! pull request is found from the commit's message, with the strategies of the `[pull_requests]` section of the
+//! configuration file, tried in order (see [PullRequestStrategy]) :
+//! - `title_suffix` : the title ends with the number, as written by a squash merge, e.g. `Issue to solve (#12)`,
+//! - `merge_subject` : the title is the one of a merge commit, e.g. `Merge pull request #12 from owner/branch` on
+//!   GitHub or `Merge pull request 'Issue to solve' (#12) from branch into main` on Gitea,
+//! - `trailer` : a trailer gives the number or the URL of the pull request, e.g. `Pull-Request: #12` or
+//!   `Pull-Request: https://github.com/owner/repo/pull/12`,
+//! - `body_reference` : a line of the body ends with the number, e.g. `Backported from main (#12)`. The lines of
+//!   the squashed commits, starting with `* `, are left out as they may reference other pull requests.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use sleppa_configuration::{PullRequestConfiguration, PullRequestStrategy};
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use super::{errors::RepositoryResult, github::GithubRepository};
+
+/// Regular expression of the title of a merge commit, e.g. `Merge pull request #12 from owner/branch`
+///
+/// It matches the merge commits of GitHub, e.g. `Merge pull request #12 from`, and of Gitea, e.g.
+/// `Merge pull request 'Issue to solve' (#12) from`.
+static MERGE_SUBJECT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // The regex is built from a constant expression, hence it can't fail.
+    Regex::new(r"^Merge pull request (?:'.*' \()?#(?P<number>[0-9]+)\)? from ").unwrap()
+});
+
+/// Regular expression of a line of the body ending with a pull request's number, e.g. `Backported from main (#12)`
+static BODY_REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // The regex is built from a constant expression, hence it can't fail.
+    Regex::new(r"\(#(?P<number>[0-9]+)\)\s*$").unwrap()
+});
+
+/// Regular expressions of the trailers referencing a pull request, built once for each trailer's key
+static TRAILER_REGEXES: Lazy<Mutex<HashMap<String, Regex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Get the number of the pull request referenced by a commit message
+///
+/// The strategies of the configuration are tried in order, and the first number found is returned. If no strategy
+/// finds a number, the commit references no pull request and `None` is returned.
+pub fn get_pull_request_number(
+    message: &str,
+    configuration: &PullRequestConfiguration,
+) -> RepositoryResult<Option<u64>> {
+    for strategy in &configuration.strategies {
+        if let Some(number) = find_pull_request_number(*strategy, message, &configuration.trailer)? {
+            return Ok(Some(number));
+        }
+    }
+    Ok(None)
+}
+
+/// Get the number of the pull request referenced by a commit message with a strategy, if any
+///
+/// The trailer is the key of the trailer referencing the pull request, used by the `trailer` strategy.
+pub fn find_pull_request_number(
+    strategy: PullRequestStrategy,
+    message: &str,
+    trailer: &str,
+) -> RepositoryResult<Option<u64>> {
+    let title = message.lines().next().unwrap_or_default();
+
+    match strategy {
+        PullRequestStrategy::TitleSuffix => Ok(GithubRepository::get_pull_request_number_from_its_name(title).ok()),
+        PullRequestStrategy::MergeSubject => capture_number(&MERGE_SUBJECT_REGEX, title),
+        PullRequestStrategy::Trailer => {
+            let regex = trailer_regex(trailer)?;
+            find_in_body(message, |line| capture_number(&regex, line))
+        }
+        PullRequestStrategy::BodyReference => find_in_body(message, |line| match line.starts_with("* ") {
+            true => Ok(None),
+            false => capture_number(&BODY_REFERENCE_REGEX, line),
+        }),
+    }
+}
+
+/// Returns the regex of a trailer referencing a pull request, built once for each trailer's key.
+///
+/// It matches the number, e.g. `Pull-Request: #12`, or the URL ending with the number, e.g. `Pull-Request: .../pull/12`.
+fn trailer_regex(trailer: &str) -> RepositoryResult<Regex> {
+    let mut regexes = TRAILER_REGEXES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(regex) = regexes.get(trailer) {
+        return Ok(regex.clone());
+    }
+
+    let grammar = format!(r"(?i)^{}:\s*(?:\S*[#/])?(?P<number>[0-9]+)\s*$", regex::escape(trailer));
+    let regex = Regex::new(&grammar)?;
+    regexes.insert(trailer.to_string(), regex.clone());
+    Ok(regex)
+}
+
+/// Returns the number captured by the `number` group of a regex in a text, if any.
+///
+/// A number which can't be parsed, e.g. an overflowing `#99999999999999999999999`, is no reference, so that the next
+/// strategy is tried.
+fn capture_number(regex: &Regex, text: &str) -> RepositoryResult<Option<u64>> {
+    Ok(regex
+        .captures(text)
+        .and_then(|captured| captured.name("number"))
+        .and_then(|number| number.as_str().parse::<u64>().ok()))
+}
+
+/// Returns the first number found in the lines of a message's body, the title being left out.
+fn find_in_body(message: &str, find: impl Fn(&str) -> RepositoryResult<Option<u64>>) -> RepositoryResult<Option<u64>> {
+    for line in message.lines().skip(1) {
+        if let Some(number) = find(line.trim_end())? {
+            return Ok(Some(number));
+        }
+    }
+    Ok(None)
+}
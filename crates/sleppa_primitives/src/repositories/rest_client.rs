@@ -0,0 +1,112 @@
+//! REST client
+//!
+//! The forges without a dedicated crate, e.g. GitLab, are called through their REST API with
+//! [reqwest](https://crates.io/crates/reqwest). The [RestClient] sends the requests to the base URL of the API,
+//! authenticated with the header expected by the forge, e.g. `PRIVATE-TOKEN` for GitLab.
+//!
+//! The listings are paginated with a `page` parameter, starting at 1, and the number of items per page is given by
+//! the caller with the parameter of the forge, e.g. `per_page=100`. The pages are read until an empty one is
+//! returned, so that a server limiting the number of items per page is read fully.
+
+use reqwest::{Method, RequestBuilder};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{errors::RepositoryResult, DEFAULT_USER_AGENT};
+
+/// Defines the REST client and its fields
+#[derive(Debug, Clone, Default)]
+pub struct RestClient {
+    /// The base URL of the API, e.g. `https://gitlab.com/api/v4`
+    pub base_url: String,
+    /// The header authenticating the requests and its value, e.g. `PRIVATE-TOKEN`
+    authentication: Option<(String, String)>,
+    /// The HTTP client sending the requests
+    client: reqwest::Client,
+}
+
+impl RestClient {
+    /// Implementation of the `new` method : `RestClient::new("https://gitlab.com/api/v4")`.
+    pub fn new(base_url: &str) -> Self {
+        RestClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Authenticates the requests with a header, e.g. `Authorization` and `token xxx`.
+    pub fn with_authentication(mut self, header: &str, value: &str) -> Self {
+        self.authentication = Some((header.to_string(), value.to_string()));
+        self
+    }
+
+    /// Verifies the requests are authenticated.
+    pub fn is_authenticated(&self) -> bool {
+        self.authentication.is_some()
+    }
+
+    /// Sends a `GET` request to a route of the API, e.g. `/projects/1`, and deserializes its JSON response.
+    pub async fn get<T: DeserializeOwned>(&self, route: &str, query: &[(&str, &str)]) -> RepositoryResult<T> {
+        Ok(self
+            .request(Method::GET, route)
+            .query(query)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Gets a page of a listing, the first page being `1`.
+    pub async fn get_page<T: DeserializeOwned>(
+        &self,
+        route: &str,
+        query: &[(&str, &str)],
+        page: u32,
+    ) -> RepositoryResult<Vec<T>> {
+        let page = page.to_string();
+        let mut query = query.to_vec();
+        query.push(("page", &page));
+        self.get(route, &query).await
+    }
+
+    /// Gets all the pages of a listing, until an empty page is returned.
+    pub async fn get_all_pages<T: DeserializeOwned>(
+        &self,
+        route: &str,
+        query: &[(&str, &str)],
+    ) -> RepositoryResult<Vec<T>> {
+        let mut items = vec![];
+        for page in 1.. {
+            let page_items: Vec<T> = self.get_page(route, query, page).await?;
+            if page_items.is_empty() {
+                break;
+            }
+            items.extend(page_items);
+        }
+        Ok(items)
+    }
+
+    /// Sends a `POST` request with a JSON body to a route of the API, and deserializes its JSON response.
+    pub async fn post<B: Serialize, T: DeserializeOwned>(&self, route: &str, body: &B) -> RepositoryResult<T> {
+        Ok(self
+            .request(Method::POST, route)
+            .json(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Builds a request to a route of the API, with the user-agent and the authentication header.
+    fn request(&self, method: Method, route: &str) -> RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{route}", self.base_url))
+            .header(reqwest::header::USER_AGENT, DEFAULT_USER_AGENT);
+        match &self.authentication {
+            Some((header, value)) => request.header(header.as_str(), value.as_str()),
+            None => request,
+        }
+    }
+}
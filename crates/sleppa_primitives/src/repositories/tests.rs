@@ -1,7 +1,28 @@
 //! Unit tests
 //!
 //! This testing module implements the unit tests for testing the repositories module routines.
-use super::*;
+//!
+//! The tests of [GithubRepository] send requests to GitHub, hence they are ignored by default and can be run with
+//! `cargo test -- --ignored`. The tests of [LocalGitRepository] use fixture repositories built on disk.
+//! The GitHub client, the [GitlabRepository] and the [GiteaRepository] are tested against a stub HTTP server, started
+//! locally.
+use super::{
+    errors::{RepositoryError, TestResult},
+    gitea::GiteaRepository,
+    github::GithubRepository,
+    github_client::GithubClientBuilder,
+    gitlab::GitlabRepository,
+    local::LocalGitRepository,
+    pull_requests::get_pull_request_number,
+    *,
+};
+use git2::{Oid, Signature};
+use sleppa_configuration::{PullRequestConfiguration, PullRequestStrategy};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::{tempdir, TempDir};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 
 // Tests to retrieve a pull request number's from it's name.
 #[test]
@@ -31,6 +52,38 @@ fn test_can_get_pull_request_number_from_its_name() {
     assert!(GithubRepository::get_pull_request_number_from_its_name(incorrect_no_hashtag).is_err());
 }
 
+// Tests the commits' messages are taken across pages until the tag's commit is found.
+#[test]
+fn test_can_take_messages_until_tag() {
+    // Unit test preparation
+    // Builds two pages of commits, the tag's commit being on the second one.
+    let page = |shas: &[&str]| -> Vec<(String, String)> {
+        shas.iter()
+            .map(|sha| (sha.to_string(), format!("Issue {sha} (#1)")))
+            .collect()
+    };
+    let first_page = page(&["c5", "c4", "c3"]);
+    let second_page = page(&["c2", "c1"]);
+
+    // Execution step
+    let mut messages = vec![];
+    let found_on_first_page = GithubRepository::take_until_tag(first_page.clone(), "c1", &mut messages);
+    let found_on_second_page = GithubRepository::take_until_tag(second_page.clone(), "c1", &mut messages);
+    let mut all_messages = vec![];
+    GithubRepository::take_until_tag(first_page, "", &mut all_messages);
+
+    // Asserts the pages are read until the tag's commit, excluded
+    assert!(!found_on_first_page);
+    assert!(found_on_second_page);
+    assert_eq!(
+        messages,
+        vec!["Issue c5 (#1)", "Issue c4 (#1)", "Issue c3 (#1)", "Issue c2 (#1)"]
+    );
+
+    // Asserts every message is taken without tag
+    assert_eq!(all_messages.len(), 3);
+}
+
 // Tests to retrieve the last tag of a GitHub repository.
 //
 // This function retrieves the last tag of a repository. As it works with [octocrab], the http request
@@ -38,12 +91,14 @@ fn test_can_get_pull_request_number_from_its_name() {
 // repository has been created.
 // The tag of this repo is "v1.0.0" and its associated hash : "cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a"
 #[tokio::test]
+#[ignore = "sends requests to the GitHub API"]
 async fn test_can_get_last_tag() -> TestResult<()> {
     // Unit test preparation
     // Providing the credentials for the test repository.
     let githubrepository = GithubRepository {
         repo: "semantic-release-squash-and-merge-testbed".to_string(),
         owner: "SofairOfficial".to_string(),
+        ..Default::default()
     };
 
     // Execution step
@@ -68,12 +123,14 @@ async fn test_can_get_last_tag() -> TestResult<()> {
 //
 // As the `Issue-to-solve-1 (#1)` is linked to the last tag, it will be ignored.
 #[tokio::test]
+#[ignore = "sends requests to the GitHub API"]
 async fn test_can_get_pull_request() -> TestResult<()> {
     // Unit test preparation
     // Providing the credentials for the test repository.
     let githubrepository = GithubRepository {
         repo: "semantic-release-squash-and-merge-testbed".to_string(),
         owner: "SofairOfficial".to_string(),
+        ..Default::default()
     };
     let tag_sha = "cd2fe77015b7aa2ac666ec05e14b76c9ba3dfd0a";
 
@@ -95,12 +152,14 @@ async fn test_can_get_pull_request() -> TestResult<()> {
 // The last valid pull request number comes from the name "Issue-to-solve-2 (#2)", hence the pull request's number to
 // analyze is `2`.
 #[tokio::test]
+#[ignore = "sends requests to the GitHub API"]
 async fn test_can_get_inner_commits_from_pull_request() -> TestResult<()> {
     // Unit test preparation
     // Providing the credentials for the test repository.
     let githubrepository = GithubRepository {
         repo: "semantic-release-squash-and-merge-testbed".to_string(),
         owner: "SofairOfficial".to_string(),
+        ..Default::default()
     };
     let pull_request_number = 2u64;
 
@@ -124,19 +183,998 @@ async fn test_can_get_inner_commits_from_pull_request() -> TestResult<()> {
 // the http request is automatically sent to GitHub. Therefore a [semantic release testbed](https://github.com/SofairOfficial/semantic-release-squash-and-merge-testbed)
 // repository has been created.
 #[tokio::test]
+#[ignore = "sends requests to the GitHub API"]
 async fn test_can_get_inner_commits_messages() -> TestResult<()> {
     let githubrepository = GithubRepository {
         repo: "semantic-release-squash-and-merge-testbed".to_string(),
         owner: "SofairOfficial".to_string(),
+        ..Default::default()
     };
 
     // Execution step
     let response = githubrepository.get_inner_commits().await?;
 
     // Asserts the name of retrived pull request are corrects.
-    assert_eq!(response[0], "patch:some patch");
-    assert_eq!(response[1], "feat(script): add a script");
-    assert_eq!(response[2], "feat: add a feature");
+    assert_eq!(response[0].message, "patch:some patch");
+    assert_eq!(response[1].message, "feat(script): add a script");
+    assert_eq!(response[2].message, "feat: add a feature");
+
+    Ok(())
+}
+
+// Tests to find the pull request referenced by a commit message, with the strategies tried in order.
+#[test]
+fn test_can_get_pull_request_number_with_strategies() -> TestResult<()> {
+    // Unit test preparation
+    let configuration = PullRequestConfiguration {
+        strategies: vec![
+            PullRequestStrategy::TitleSuffix,
+            PullRequestStrategy::MergeSubject,
+            PullRequestStrategy::Trailer,
+            PullRequestStrategy::BodyReference,
+        ],
+        trailer: "PR-URL".to_string(),
+        analyze_unreferenced: false,
+    };
+    let messages = [
+        ("Issue to solve (#2)\n\n* feat: add a feature (#1)", Some(2)),
+        ("Merge pull request #12 from owner/feature\n\nAdd a feature", Some(12)),
+        (
+            "Merge pull request 'Add a feature' (#13) from feature into main",
+            Some(13),
+        ),
+        (
+            "feat: add a feature\n\npr-url: https://github.com/owner/repo/pull/14",
+            Some(14),
+        ),
+        (
+            "feat: add a feature\n\nPR-URL: #15\nReviewed-by: Sofair Maintainers",
+            Some(15),
+        ),
+        ("feat: add a feature\n\nBackported from main (#16)", Some(16)),
+        ("feat: add a feature\n\n* fix: a squashed fix (#17)", None),
+        ("feat: add a feature (#18) to the script", None),
+    ];
+
+    for (message, expected) in messages {
+        // Execution step
+        let number = get_pull_request_number(message, &configuration)?;
+
+        // Asserts the pull request's number is found by the first matching strategy.
+        assert_eq!(number, expected, "{message}");
+    }
+
+    // Asserts only the title suffix is looked for by default, and the strategies are tried in order.
+    let default_configuration = PullRequestConfiguration::default();
+    assert_eq!(
+        get_pull_request_number("Merge pull request #12 from owner/feature", &default_configuration)?,
+        None
+    );
+    assert_eq!(
+        get_pull_request_number("Fix a bug (#3)\n\nPR-URL: #4", &configuration)?,
+        Some(3)
+    );
+
+    // Asserts an overflowing number is no reference, the next strategy being tried.
+    assert_eq!(
+        get_pull_request_number(
+            "Merge pull request #99999999999999999999999 from owner/feature\n\nPR-URL: #5",
+            &configuration
+        )?,
+        Some(5)
+    );
+    assert_eq!(
+        get_pull_request_number(
+            "feat: add a feature\n\nBackported from main (#99999999999999999999999)",
+            &configuration
+        )?,
+        None
+    );
+    Ok(())
+}
+
+// Creates a commit on top of `HEAD` in a fixture repository.
+fn commit(repository: &git2::Repository, message: &str) -> TestResult<Oid> {
+    let signature = Signature::now("Sofair Maintainers", "maintainers@sofair.io")?;
+    let tree = repository.find_tree(repository.index()?.write_tree()?)?;
+    let parents = match repository.head() {
+        Ok(head) => vec![head.peel_to_commit()?],
+        Err(_) => vec![],
+    };
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+
+    Ok(repository.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)?)
+}
+
+// Builds a fixture repository similar to the GitHub testbed repository.
+//
+// The history of the repository is :
+//  - Initial commit
+//  - Issue-to-solve-1 (#1), tagged as `v1.0.0`
+//  - Issue-to-solve-2 (#2), with 3 inner commits in its body
+//  - Issue-to-solve-3, which is malformed
+//
+// The returned hashes are the ones of `Issue-to-solve-1 (#1)` and `Issue-to-solve-2 (#2)`.
+fn fixture_repository(with_tag: bool) -> TestResult<(TempDir, Oid, Oid)> {
+    let tmp_dir = tempdir()?;
+    let repository = git2::Repository::init(tmp_dir.path())?;
+
+    commit(&repository, "Initial commit")?;
+    let first_pull_request = commit(&repository, "Issue-to-solve-1 (#1)\n\n* feat: a first feature\n")?;
+    if with_tag {
+        let signature = Signature::now("Sofair Maintainers", "maintainers@sofair.io")?;
+        let target = repository.find_object(first_pull_request, None)?;
+        repository.tag("v1.0.0", &target, &signature, "Release v1.0.0", false)?;
+    }
+    let second_pull_request = commit(
+        &repository,
+        "Issue-to-solve-2 (#2)\n\n* patch:some patch\n\n* feat(script): add a script\n\nwith a body\n\n\
+         * feat: add a feature\n\nCo-authored-by: Sofair Maintainers <maintainers@sofair.io>\n",
+    )?;
+    commit(&repository, "Issue-to-solve-3\n\n* feat: an ignored feature\n")?;
+
+    Ok((tmp_dir, first_pull_request, second_pull_request))
+}
+
+// Tests to retrieve the inner commit messages from the body of a squash commit.
+#[test]
+fn test_can_get_inner_messages_from_body() {
+    // Unit test preparation
+    let message = "Issue to solve (#2)\n\n* feat: add a feature\n\n* fix(script): a fix\n\nwith a body\n\n\
+                   Co-authored-by: Sofair Maintainers <maintainers@sofair.io>";
+
+    // Execution step
+    let inner_messages = LocalGitRepository::get_inner_messages_from_body(message);
+
+    // Asserts the inner messages are correct, without the pull request's name and the trailer.
+    assert_eq!(
+        inner_messages,
+        vec!["feat: add a feature", "fix(script): a fix\n\nwith a body"]
+    );
+    assert!(LocalGitRepository::get_inner_messages_from_body("Issue to solve (#2)").is_empty());
+}
+
+// Tests to retrieve the last tag of a local git repository.
+#[tokio::test]
+async fn test_can_get_last_tag_from_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let (tmp_dir, first_pull_request, _) = fixture_repository(true)?;
+    let (untagged_tmp_dir, _, _) = fixture_repository(false)?;
+
+    // Execution step
+    let response = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    }
+    .get_last_tag()
+    .await?;
+    let untagged_response = LocalGitRepository {
+        path: untagged_tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    }
+    .get_last_tag()
+    .await?;
+
+    // Asserts the name of the tag and its hash are ok.
+    assert_eq!(response.identifier, "v1.0.0");
+    assert_eq!(response.hash, first_pull_request.to_string());
+
+    // Asserts an empty tag is returned for a repository without tag.
+    assert_eq!(untagged_response.identifier, "");
+    assert_eq!(untagged_response.hash, "");
+    Ok(())
+}
+
+// Tests to retrieve the inner commits since the last tag of a local git repository.
+//
+// As `Issue-to-solve-1 (#1)` is linked to the last tag, it is ignored, and as `Issue-to-solve-3` is malformed,
+// its inner commits are ignored too.
+#[tokio::test]
+async fn test_can_get_inner_commits_from_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let (tmp_dir, _, second_pull_request) = fixture_repository(true)?;
+    let repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    // Execution step
+    let response = repository.get_inner_commits().await?;
+
+    // Asserts the inner commits are corrects and share the hash of their squash commit.
+    assert_eq!(response.len(), 3);
+    assert_eq!(response[0].message, "patch:some patch");
+    assert_eq!(response[1].message, "feat(script): add a script\n\nwith a body");
+    assert_eq!(response[2].message, "feat: add a feature");
+    assert_eq!(response[2].commit_type, "feat");
+    assert!(response
+        .iter()
+        .all(|commit| commit.hash == second_pull_request.to_string()));
+    Ok(())
+}
+
+// Tests to retrieve the inner commits of a local git repository without tag.
+#[tokio::test]
+async fn test_can_get_all_inner_commits_from_untagged_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let (tmp_dir, _, _) = fixture_repository(false)?;
+    let repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    // Execution step
+    let response = repository.get_inner_commits().await?;
+
+    // Asserts the whole history is analyzed, from the oldest commit to the newest.
+    assert_eq!(response.len(), 4);
+    assert_eq!(response[0].message, "feat: a first feature");
+    assert_eq!(response[3].message, "feat: add a feature");
+    Ok(())
+}
+
+// Tests to retrieve the tags and the changed files of a local git repository.
+//
+// The history of the repository is :
+//  - Issue-to-solve-1 (#1), changing `crates/first/lib.rs`, tagged as `first@v1.0.0` and `second@v1.0.0`
+//  - Issue-to-solve-2 (#2), changing `crates/second/lib.rs` and `README.md`
+#[tokio::test]
+async fn test_can_get_tags_and_files_from_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let repository = git2::Repository::init(tmp_dir.path())?;
+    let signature = Signature::now("Sofair Maintainers", "maintainers@sofair.io")?;
+    let mut index = repository.index()?;
+
+    std::fs::create_dir_all(tmp_dir.path().join("crates/first"))?;
+    std::fs::write(tmp_dir.path().join("crates/first/lib.rs"), "// first")?;
+    index.add_path(Path::new("crates/first/lib.rs"))?;
+    index.write()?;
+    let first_pull_request = commit(&repository, "Issue-to-solve-1 (#1)\n\n* feat(first): a first feature\n")?;
+    let target = repository.find_object(first_pull_request, None)?;
+    repository.tag("second@v1.0.0", &target, &signature, "Release second@v1.0.0", false)?;
+    repository.tag("first@v1.0.0", &target, &signature, "Release first@v1.0.0", false)?;
+
+    std::fs::create_dir_all(tmp_dir.path().join("crates/second"))?;
+    std::fs::write(tmp_dir.path().join("crates/second/lib.rs"), "// second")?;
+    std::fs::write(tmp_dir.path().join("README.md"), "# Readme")?;
+    index.add_path(Path::new("crates/second/lib.rs"))?;
+    index.add_path(Path::new("README.md"))?;
+    index.write()?;
+    commit(&repository, "Issue-to-solve-2 (#2)\n\n* fix: a fix\n")?;
+
+    let local_repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    // Execution step
+    let tags = local_repository.get_tags().await?;
+    let inner_commits = local_repository
+        .get_inner_commits_since(&RepositoryTag::default())
+        .await?;
+
+    // Asserts the tags of a same commit are all retrieved, sorted by name.
+    assert_eq!(
+        tags.iter().map(|tag| tag.identifier.as_str()).collect::<Vec<&str>>(),
+        vec!["first@v1.0.0", "second@v1.0.0"]
+    );
+    assert!(tags.iter().all(|tag| tag.hash == first_pull_request.to_string()));
+
+    // Asserts the inner commits share the files changed by their squash commit.
+    assert_eq!(inner_commits.len(), 2);
+    assert_eq!(inner_commits[0].files, vec!["crates/first/lib.rs"]);
+    assert_eq!(inner_commits[0].scope, "first");
+    assert_eq!(inner_commits[1].files, vec!["README.md", "crates/second/lib.rs"]);
+    Ok(())
+}
+
+// Tests to verify a tag is an ancestor of a branch of a local git repository.
+//
+// A `side` branch is forked from `Issue-to-solve-1 (#1)` and its commit is tagged as `v2.0.0`, while `HEAD`
+// stays on `Issue-to-solve-3`.
+#[tokio::test]
+async fn test_can_verify_ancestors_in_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let (tmp_dir, first_pull_request, second_pull_request) = fixture_repository(true)?;
+    let repository = git2::Repository::open(tmp_dir.path())?;
+    let signature = Signature::now("Sofair Maintainers", "maintainers@sofair.io")?;
+    let tree = repository.find_tree(repository.index()?.write_tree()?)?;
+    let fork = repository.find_commit(first_pull_request)?;
+    let side_commit = repository.commit(None, &signature, &signature, "feat: a side feature", &tree, &[&fork])?;
+    repository.branch("side", &repository.find_commit(side_commit)?, false)?;
+
+    let local_repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let tag = |identifier: &str, hash: Oid| RepositoryTag {
+        identifier: identifier.to_string(),
+        hash: hash.to_string(),
+    };
+
+    // Execution step
+    let first_on_head = local_repository
+        .is_ancestor(&tag("v1.0.0", first_pull_request), "")
+        .await?;
+    let side_on_head = local_repository.is_ancestor(&tag("v2.0.0", side_commit), "").await?;
+    let side_on_side = local_repository
+        .is_ancestor(&tag("v2.0.0", side_commit), "side")
+        .await?;
+    let second_on_side = local_repository
+        .is_ancestor(&tag("v1.1.0", second_pull_request), "side")
+        .await?;
+
+    // Asserts only the tags of the branch's history are ancestors, including the one of its head.
+    assert!(first_on_head);
+    assert!(!side_on_head);
+    assert!(side_on_side);
+    assert!(!second_on_side);
+    Ok(())
+}
+
+// Starts a stub HTTP server, answering the requests whose path starts with a route with the route's JSON body.
+//
+// The other requests are answered with a `404 Not Found`. The received requests are recorded with their headers
+// and their body, so that the tests can assert what was sent.
+async fn stub_server(routes: Vec<(&'static str, String)>) -> TestResult<(String, Arc<Mutex<Vec<String>>>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let base_url = format!("http://{}", listener.local_addr()?);
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded_requests = requests.clone();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            // Reads the request until its headers and its body are received
+            let mut request = vec![];
+            let mut buffer = [0; 4096];
+            loop {
+                let read = match stream.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => read,
+                };
+                request.extend_from_slice(&buffer[..read]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .and_then(|length| length.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= content_length {
+                        break;
+                    }
+                }
+            }
+            let request = String::from_utf8_lossy(&request).to_string();
+
+            let path = request.split_whitespace().nth(1).unwrap_or_default().to_string();
+            let (status, body) = match routes.iter().find(|(route, _)| path.starts_with(route)) {
+                Some((_, body)) => ("200 OK", body.clone()),
+                None => ("404 Not Found", r#"{"message": "Not Found"}"#.to_string()),
+            };
+            recorded_requests.lock().unwrap().push(request);
+
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    Ok((base_url, requests))
+}
+
+// Tests the GitHub repository sends its requests with the client it is given.
+//
+// The client is authenticated with a token, and sends its requests to a stub server with its own user-agent.
+#[tokio::test]
+async fn test_can_inject_github_client() -> TestResult<()> {
+    // Unit test preparation
+    let tags = r#"[{
+        "name": "v1.0.0",
+        "commit": { "sha": "ac84b3ba", "url": "https://api.github.com/repos/owner/repo/commits/ac84b3ba" },
+        "zipball_url": "https://api.github.com/repos/owner/repo/zipball/v1.0.0",
+        "tarball_url": "https://api.github.com/repos/owner/repo/tarball/v1.0.0",
+        "node_id": "MDM6UmVmcmVmcy90YWdzL3YxLjAuMA=="
+    }]"#;
+    let (base_url, requests) = stub_server(vec![("/api/v3/repos/owner/repo/tags", tags.to_string())]).await?;
+    let client = GithubClientBuilder::new()
+        .with_token("secret-token")
+        .with_base_url(&format!("{base_url}/api/v3"))
+        .with_user_agent("sleppa-tests")
+        .build()?;
+    let repository = GithubRepository {
+        owner: "owner".to_string(),
+        repo: "repo".to_string(),
+        client,
+        ..Default::default()
+    };
+
+    // Execution step
+    let response = repository.get_tags().await?;
+
+    // Asserts the tags are read from the stub server.
+    assert_eq!(
+        response,
+        vec![RepositoryTag {
+            identifier: "v1.0.0".to_string(),
+            hash: "ac84b3ba".to_string(),
+        }]
+    );
+
+    // Asserts the request is authenticated and sent with the user-agent.
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let request = requests[0].to_lowercase();
+    assert!(request.starts_with("get /api/v3/repos/owner/repo/tags?per_page=100 "));
+    assert!(request.contains("authorization: bearer secret-token"));
+    assert!(request.contains("user-agent: sleppa-tests"));
+    assert!(repository.client.is_authenticated());
+    Ok(())
+}
+
+// Tests the default branch of a GitHub repository is requested once, however many tags are compared to it.
+#[tokio::test]
+async fn test_can_cache_default_branch_of_github_repository() -> TestResult<()> {
+    // Unit test preparation
+    let routes = vec![
+        (
+            "/api/v3/repos/owner/repo/compare/",
+            r#"{"status": "ahead"}"#.to_string(),
+        ),
+        ("/api/v3/repos/owner/repo", r#"{"default_branch": "trunk"}"#.to_string()),
+    ];
+    let (base_url, requests) = stub_server(routes).await?;
+    let repository = GithubRepository {
+        owner: "owner".to_string(),
+        repo: "repo".to_string(),
+        client: GithubClientBuilder::new()
+            .with_base_url(&format!("{base_url}/api/v3"))
+            .build()?,
+        ..Default::default()
+    };
+    let tag = |hash: &str| RepositoryTag {
+        identifier: "".to_string(),
+        hash: hash.to_string(),
+    };
+
+    // Execution step
+    assert!(repository.is_ancestor(&tag("c1"), "").await?);
+    assert!(repository.is_ancestor(&tag("c2"), "").await?);
+
+    // Asserts the default branch is requested once and compared to each tag
+    assert_eq!(repository.get_default_branch().await?, "trunk");
+    let requests = requests.lock().unwrap();
+    let paths: Vec<&str> = requests
+        .iter()
+        .map(|request| request.split_whitespace().nth(1).unwrap_or_default())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/api/v3/repos/owner/repo",
+            "/api/v3/repos/owner/repo/compare/c1...trunk",
+            "/api/v3/repos/owner/repo/compare/c2...trunk",
+        ]
+    );
+    Ok(())
+}
+
+// Tests the commits brought by a merge are listed through their pull request only, from a stub GitHub server.
+//
+// The history of the default branch is :
+//  - `c1`, tagged as `v1.0.0`
+//  - `c2`, whose name references no pull request
+//  - `m1`, merging the pull request `#12` whose inner commit `b1` is listed between `c2` and `c1`
+#[tokio::test]
+async fn test_can_get_merged_commits_once_from_github_repository() -> TestResult<()> {
+    // Unit test preparation
+    let repo_commit = |sha: &str, message: &str, parents: &[&str]| -> String {
+        let parents: Vec<String> = parents
+            .iter()
+            .map(|parent| format!(r#"{{ "sha": "{parent}", "url": "https://api.github.com/commits/{parent}" }}"#))
+            .collect();
+        format!(
+            r#"{{
+                "url": "https://api.github.com/commits/{sha}",
+                "sha": "{sha}",
+                "node_id": "{sha}",
+                "html_url": "https://github.com/commit/{sha}",
+                "comments_url": "https://api.github.com/commits/{sha}/comments",
+                "commit": {{
+                    "url": "https://api.github.com/git/commits/{sha}",
+                    "author": null,
+                    "committer": null,
+                    "message": "{message}",
+                    "comment_count": 0,
+                    "tree": {{ "sha": "t1", "url": "https://api.github.com/git/trees/t1" }}
+                }},
+                "author": null,
+                "committer": null,
+                "parents": [{}]
+            }}"#,
+            parents.join(", ")
+        )
+    };
+    let routes = vec![
+        (
+            "/api/v3/repos/owner/repo/commits?",
+            format!(
+                "[{}, {}, {}, {}]",
+                repo_commit("m1", "Merge pull request #12 from owner/feature", &["c2", "b1"]),
+                repo_commit("c2", "fix: a direct fix", &["c1"]),
+                repo_commit("b1", "feat: a merged feature", &["c1"]),
+                repo_commit("c1", "First release", &[]),
+            ),
+        ),
+        (
+            "/api/v3/repos/owner/repo/commits/c2",
+            r#"{ "files": [{ "filename": "src/main.rs" }] }"#.to_string(),
+        ),
+        (
+            "/api/v3/repos/owner/repo/pulls/12/commits",
+            format!("[{}]", repo_commit("b1", "feat: a merged feature", &["c1"])),
+        ),
+        (
+            "/api/v3/repos/owner/repo/pulls/12/files",
+            r#"[{ "filename": "src/lib.rs" }]"#.to_string(),
+        ),
+    ];
+    let (base_url, _) = stub_server(routes).await?;
+    let repository = GithubRepository {
+        owner: "owner".to_string(),
+        repo: "repo".to_string(),
+        client: GithubClientBuilder::new()
+            .with_base_url(&format!("{base_url}/api/v3"))
+            .build()?,
+        pull_requests: PullRequestConfiguration {
+            strategies: vec![PullRequestStrategy::MergeSubject],
+            analyze_unreferenced: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let tag = |hash: &str| RepositoryTag {
+        identifier: "".to_string(),
+        hash: hash.to_string(),
+    };
+
+    // Execution step
+    let inner_commits = repository.get_inner_commits_between(&tag("c1"), &tag("")).await?;
+
+    // Asserts the merged commit is analyzed once, through its pull request, and the unreferenced commit itself.
+    let messages: Vec<&str> = inner_commits.iter().map(|commit| commit.message.as_str()).collect();
+    assert_eq!(messages, vec!["feat: a merged feature", "fix: a direct fix"]);
+    assert_eq!(inner_commits[0].files, vec!["src/lib.rs"]);
+    assert_eq!(inner_commits[1].files, vec!["src/main.rs"]);
+    Ok(())
+}
+
+// Tests to build the GitHub client from its credentials.
+#[tokio::test]
+async fn test_can_build_github_client() -> TestResult<()> {
+    // Execution step
+    let anonymous_client = GithubClientBuilder::new().build()?;
+    let token_client = GithubClientBuilder::new().with_token("secret-token").build()?;
+    let invalid_key = GithubClientBuilder::new().with_app(1, 2, "not a key").build();
+    let invalid_url = GithubClientBuilder::new().with_base_url("not a url").build();
+
+    // Asserts only the clients with credentials are authenticated.
+    assert!(!anonymous_client.is_authenticated());
+    assert!(token_client.is_authenticated());
+
+    // Asserts the incorrect private keys and base URLs are rejected.
+    assert!(matches!(invalid_key, Err(RepositoryError::InvalidCredentials(_))));
+    assert!(invalid_url.is_err());
+
+    // Asserts the secrets are not printed.
+    assert!(!format!("{:?}", GithubClientBuilder::new().with_token("secret-token")).contains("secret-token"));
+    Ok(())
+}
+
+// Tests to retrieve the merge request referenced by a GitLab commit message.
+#[test]
+fn test_can_get_merge_request_from_its_message() -> TestResult<()> {
+    // Unit test preparation
+    let message = "Merge branch 'feature' into 'main'\n\nAdd a feature\n\nSee merge request group/subgroup/project!42";
+
+    // Execution step
+    let merge_request = GitlabRepository::get_merge_request_from_its_message(message)?;
+
+    // Asserts the project and the identifier of the merge request are correct.
+    assert_eq!(merge_request, ("group/subgroup/project".to_string(), 42));
+    assert!(GitlabRepository::get_merge_request_from_its_message("feat: add a feature (#42)").is_err());
+    Ok(())
+}
+
+// Tests to retrieve the tags and the inner commits of a GitLab repository from a stub server.
+//
+// The history of the default branch is :
+//  - `c1`, tagged as `v1.0.0`
+//  - `c2`, which references no merge request
+//  - `c3`, the merge commit of the merge request `!42`, whose inner commits are `i1` and `i2`
+#[tokio::test]
+async fn test_can_get_inner_commits_from_gitlab_repository() -> TestResult<()> {
+    // Unit test preparation
+    let project = "/api/v4/projects/group%2Fproject";
+    let routes = vec![
+        (
+            "/api/v4/projects/group%2Fproject/repository/tags?per_page=100&page=1",
+            r#"[{ "name": "v1.0.0", "commit": { "id": "c1" } }]"#.to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/repository/commits?per_page=100&first_parent=true&page=1",
+            r#"[
+                { "id": "c3", "message": "Merge branch 'feature' into 'main'\n\nSee merge request group/project!42" },
+                { "id": "c2", "message": "chore: no merge request" },
+                { "id": "c1", "message": "Merge branch 'first' into 'main'\n\nSee merge request group/project!41" }
+            ]"#
+            .to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/merge_requests/42/commits?per_page=100&page=1",
+            r#"[{ "id": "i2", "message": "fix: a fix" }, { "id": "i1", "message": "feat(api): a feature" }]"#
+                .to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/merge_requests/42/diffs?per_page=100&page=1",
+            r#"[{ "old_path": "src/lib.rs", "new_path": "src/lib.rs" }]"#.to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/repository/merge_base",
+            r#"{ "id": "c1" }"#.to_string(),
+        ),
+        // The last pages are empty
+        (
+            "/api/v4/projects/group%2Fproject/repository/tags?per_page=100&page=2",
+            "[]".to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/merge_requests/42/commits?per_page=100&page=2",
+            "[]".to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/merge_requests/42/diffs?per_page=100&page=2",
+            "[]".to_string(),
+        ),
+    ];
+    let (base_url, requests) = stub_server(routes).await?;
+    let repository = GitlabRepository::new(&format!("{base_url}/api/v4"), "group/project").with_token("secret-token");
+
+    // Execution step
+    let tags = repository.get_tags().await?;
+    let is_ancestor = repository.is_ancestor(&tags[0], "main").await?;
+    let inner_commits = repository.get_inner_commits_since(&tags[0]).await?;
+
+    // Asserts the tags are read with their commit.
+    assert_eq!(
+        tags,
+        vec![RepositoryTag {
+            identifier: "v1.0.0".to_string(),
+            hash: "c1".to_string(),
+        }]
+    );
+    assert!(is_ancestor);
+
+    // Asserts the merge request is expanded into its inner commits, from the oldest to the newest.
+    assert_eq!(inner_commits.len(), 2);
+    assert_eq!(inner_commits[0].hash, "i1");
+    assert_eq!(inner_commits[0].scope, "api");
+    assert_eq!(inner_commits[1].message, "fix: a fix");
+    assert!(inner_commits.iter().all(|commit| commit.files == vec!["src/lib.rs"]));
+
+    // Asserts the requests are authenticated, and the commits are not read past the tag.
+    let requests = requests.lock().unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.to_lowercase().contains("private-token: secret-token")));
+    assert!(!requests
+        .iter()
+        .any(|request| request.contains(&format!("{project}/merge_requests/41"))));
+    Ok(())
+}
+
+// Tests to create a GitLab release with its notes on a stub server.
+#[tokio::test]
+async fn test_can_create_gitlab_release() -> TestResult<()> {
+    // Unit test preparation
+    let routes = vec![
+        (
+            "/api/v4/projects/group%2Fproject/releases",
+            r#"{ "tag_name": "v1.1.0" }"#.to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject",
+            r#"{ "default_branch": "trunk" }"#.to_string(),
+        ),
+    ];
+    let (base_url, requests) = stub_server(routes).await?;
+    let repository = GitlabRepository::new(&format!("{base_url}/api/v4"), "group/project").with_token("secret-token");
+
+    // Execution step
+    repository.create_release("v1.1.0", "", "## v1.1.0\n").await?;
+
+    // Asserts the release is created from the default branch, with the notes as its description.
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].starts_with("POST /api/v4/projects/group%2Fproject/releases "));
+    assert!(
+        requests[1].ends_with(r###"{"name":"v1.1.0","tag_name":"v1.1.0","ref":"trunk","description":"## v1.1.0\n"}"###)
+    );
+    Ok(())
+}
+
+// Tests to retrieve the tags and the inner commits of a Gitea repository from a stub server.
+//
+// The history of the default branch is :
+//  - `c1`, tagged as `v1.0.0`
+//  - `c2`, whose name references no pull request
+//  - `c3`, the squash commit of the pull request `#2`, whose inner commits are `i1` and `i2`
+#[tokio::test]
+async fn test_can_get_inner_commits_from_gitea_repository() -> TestResult<()> {
+    // Unit test preparation
+    let routes = vec![
+        (
+            "/api/v1/repos/owner/repo/tags?limit=50&page=1",
+            r#"[{ "name": "v1.0.0", "id": "t1", "commit": { "sha": "c1", "url": "" } }]"#.to_string(),
+        ),
+        (
+            "/api/v1/repos/owner/repo/commits?limit=50&stat=false&files=false&page=1",
+            r#"[
+                {
+                    "sha": "c3",
+                    "commit": { "message": "Issue to solve (#2)\n\n* feat(api): a feature\n* fix: a fix" },
+                    "parents": [{ "sha": "c2" }]
+                },
+                { "sha": "c2", "commit": { "message": "chore: no pull request" }, "parents": [{ "sha": "c1" }] },
+                { "sha": "c1", "commit": { "message": "First issue (#1)" }, "parents": [] }
+            ]"#
+            .to_string(),
+        ),
+        (
+            "/api/v1/repos/owner/repo/pulls/2/commits?limit=50&stat=false&files=false&page=1",
+            r#"[
+                { "sha": "i1", "commit": { "message": "feat(api): a feature" } },
+                { "sha": "i2", "commit": { "message": "fix: a fix" } }
+            ]"#
+            .to_string(),
+        ),
+        (
+            "/api/v1/repos/owner/repo/pulls/2/files?limit=50&page=1",
+            r#"[{ "filename": "src/lib.rs", "status": "changed" }]"#.to_string(),
+        ),
+        (
+            "/api/v1/repos/owner/repo/compare/main...c1",
+            r#"{ "total_commits": 0, "commits": [] }"#.to_string(),
+        ),
+        // The last pages are empty
+        ("/api/v1/repos/owner/repo/tags?limit=50&page=2", "[]".to_string()),
+        (
+            "/api/v1/repos/owner/repo/pulls/2/commits?limit=50&stat=false&files=false&page=2",
+            "[]".to_string(),
+        ),
+        (
+            "/api/v1/repos/owner/repo/pulls/2/files?limit=50&page=2",
+            "[]".to_string(),
+        ),
+    ];
+    let (base_url, requests) = stub_server(routes).await?;
+    let repository = GiteaRepository::new(&base_url, "owner", "repo").with_token("secret-token");
+
+    // Execution step
+    let tags = repository.get_tags().await?;
+    let is_ancestor = repository.is_ancestor(&tags[0], "main").await?;
+    let inner_commits = repository.get_inner_commits_since(&tags[0]).await?;
+
+    // Asserts the tags are read with their commit.
+    assert_eq!(
+        tags,
+        vec![RepositoryTag {
+            identifier: "v1.0.0".to_string(),
+            hash: "c1".to_string(),
+        }]
+    );
+    assert!(is_ancestor);
+
+    // Asserts the pull request is expanded into its inner commits, from the oldest to the newest.
+    assert_eq!(inner_commits.len(), 2);
+    assert_eq!(inner_commits[0].hash, "i1");
+    assert_eq!(inner_commits[0].scope, "api");
+    assert_eq!(inner_commits[1].message, "fix: a fix");
+    assert!(inner_commits.iter().all(|commit| commit.files == vec!["src/lib.rs"]));
+
+    // Asserts the requests are authenticated, and the commits are not read past the tag.
+    let requests = requests.lock().unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.to_lowercase().contains("authorization: token secret-token")));
+    assert!(!requests.iter().any(|request| request.contains("/pulls/1/")));
+    Ok(())
+}
+
+// Tests to create a Gitea pre-release with its notes on a stub server.
+#[tokio::test]
+async fn test_can_create_gitea_release() -> TestResult<()> {
+    // Unit test preparation
+    let routes = vec![(
+        "/api/v1/repos/owner/repo/releases",
+        r#"{ "id": 1, "tag_name": "v1.1.0-beta.1" }"#.to_string(),
+    )];
+    let (base_url, requests) = stub_server(routes).await?;
+    let repository = GiteaRepository::new(&format!("{base_url}/"), "owner", "repo").with_token("secret-token");
+
+    // Execution step
+    repository
+        .create_release("v1.1.0-beta.1", "next", "## v1.1.0-beta.1\n", true)
+        .await?;
+
+    // Asserts the release is created from the branch, with the notes as its body.
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with("POST /api/v1/repos/owner/repo/releases "));
+    assert!(requests[0].ends_with(
+        r###"{"tag_name":"v1.1.0-beta.1","target_commitish":"next","name":"v1.1.0-beta.1","body":"## v1.1.0-beta.1\n","prerelease":true}"###
+    ));
+    Ok(())
+}
+
+// Tests to retrieve the inner commits of merge commits and of unreferenced commits from a local git repository.
+//
+// The history of the repository is :
+//  - Initial commit
+//  - `fix: a direct fix`, referencing no pull request
+//  - Merge pull request #12 from owner/feature, merging `feat: a merged feature`
+#[tokio::test]
+async fn test_can_get_merged_and_unreferenced_commits_from_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let tmp_dir = tempdir()?;
+    let repository = git2::Repository::init(tmp_dir.path())?;
+    let signature = Signature::now("Sofair Maintainers", "maintainers@sofair.io")?;
+    let tree = repository.find_tree(repository.index()?.write_tree()?)?;
+
+    let initial = repository.find_commit(commit(&repository, "Initial commit")?)?;
+    let merged = repository.commit(
+        None,
+        &signature,
+        &signature,
+        "feat: a merged feature",
+        &tree,
+        &[&initial],
+    )?;
+    let direct_fix = repository.find_commit(commit(&repository, "fix: a direct fix")?)?;
+    let merge = repository.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Merge pull request #12 from owner/feature",
+        &tree,
+        &[&direct_fix, &repository.find_commit(merged)?],
+    )?;
+
+    let pull_requests = PullRequestConfiguration {
+        strategies: vec![PullRequestStrategy::TitleSuffix, PullRequestStrategy::MergeSubject],
+        analyze_unreferenced: true,
+        ..Default::default()
+    };
+    let local_repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        pull_requests,
+    };
+    let default_repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    // Execution step
+    let response = local_repository.get_inner_commits().await?;
+    let default_response = default_repository.get_inner_commits().await?;
+
+    // Asserts the unreferenced commits are analyzed themselves, and the merge commit is expanded into the
+    // commits it merged, sharing its hash.
+    let messages: Vec<&str> = response.iter().map(|commit| commit.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec!["Initial commit", "fix: a direct fix", "feat: a merged feature"]
+    );
+    assert_eq!(response[1].hash, direct_fix.id().to_string());
+    assert_eq!(response[2].hash, merge.to_string());
+    assert_eq!(response[2].commit_type, "feat");
+
+    // Asserts no commit is analyzed by default, as none has a pull request's title.
+    assert!(default_response.is_empty());
+    Ok(())
+}
+
+// Tests to retrieve the inner commits between two tags of a local git repository, and the date of a tag.
+//
+// The fixture repository is tagged as `v1.0.0` on `Issue-to-solve-1 (#1)`, and a `v1.1.0` tag is given on
+// `Issue-to-solve-2 (#2)`.
+#[tokio::test]
+async fn test_can_get_inner_commits_between_tags_from_local_repository() -> TestResult<()> {
+    // Unit test preparation
+    let (tmp_dir, first_pull_request, second_pull_request) = fixture_repository(true)?;
+    let repository = LocalGitRepository {
+        path: tmp_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+    let first_tag = RepositoryTag {
+        identifier: "v1.0.0".to_string(),
+        hash: first_pull_request.to_string(),
+    };
+    let second_tag = RepositoryTag {
+        identifier: "v1.1.0".to_string(),
+        hash: second_pull_request.to_string(),
+    };
+
+    // Execution step
+    let first_release = repository
+        .get_inner_commits_between(&RepositoryTag::default(), &first_tag)
+        .await?;
+    let second_release = repository.get_inner_commits_between(&first_tag, &second_tag).await?;
+    let date = repository.get_tag_date(&second_tag).await?;
+
+    // Asserts each release only has the commits since its previous tag, until its own tag.
+    assert_eq!(first_release.len(), 1);
+    assert_eq!(first_release[0].message, "feat: a first feature");
+    assert_eq!(second_release.len(), 3);
+    assert!(second_release
+        .iter()
+        .all(|commit| commit.hash == second_pull_request.to_string()));
+
+    // Asserts the date of the tag is the date of its commit.
+    let commit_time = git2::Repository::open(tmp_dir.path())?
+        .find_commit(second_pull_request)?
+        .time();
+    assert_eq!(date.unix_timestamp(), commit_time.seconds());
+    Ok(())
+}
+
+// Tests to retrieve the commits between two tags of a GitLab repository and the date of a tag from a stub server.
+//
+// The history of the tagged commit `c3` is `c3`, `c2` and `c1`, which is tagged too.
+#[tokio::test]
+async fn test_can_get_inner_commits_between_tags_from_gitlab_repository() -> TestResult<()> {
+    // Unit test preparation
+    let routes = vec![
+        (
+            "/api/v4/projects/group%2Fproject/repository/commits?per_page=100&first_parent=true&ref_name=c3&page=1",
+            r#"[
+                { "id": "c3", "message": "feat: a feature" },
+                { "id": "c2", "message": "fix: a fix" },
+                { "id": "c1", "message": "feat: a first feature" }
+            ]"#
+            .to_string(),
+        ),
+        (
+            "/api/v4/projects/group%2Fproject/repository/commits/c3",
+            r#"{ "id": "c3", "message": "feat: a feature", "committed_date": "2023-05-05T12:30:00.000+02:00" }"#
+                .to_string(),
+        ),
+    ];
+    let (base_url, _) = stub_server(routes).await?;
+    let repository = GitlabRepository::new(&format!("{base_url}/api/v4"), "group/project");
+    let oldest = RepositoryTag {
+        identifier: "v1.0.0".to_string(),
+        hash: "c1".to_string(),
+    };
+    let newest = RepositoryTag {
+        identifier: "v1.1.0".to_string(),
+        hash: "c3".to_string(),
+    };
+
+    // Execution step
+    let commits = repository.get_commits_between(&oldest.hash, &newest.hash).await?;
+    let date = repository.get_tag_date(&newest).await?;
+
+    // Asserts the commits are listed from the newest tag until the oldest one, excluded.
+    assert_eq!(
+        commits.iter().map(|commit| commit.hash.as_str()).collect::<Vec<&str>>(),
+        vec!["c3", "c2"]
+    );
 
+    // Asserts the date is the one the tag's commit was committed.
+    assert_eq!(date.to_string(), "2023-05-05 12:30:00.0 +02:00:00");
     Ok(())
 }
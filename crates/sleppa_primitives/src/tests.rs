@@ -0,0 +1,59 @@
+//! Unit tests
+//!
+//! This testing module implements the unit tests for testing the primitives.
+use super::*;
+
+// Tests the creation of a commit and the extraction of its header from the message.
+#[test]
+fn test_can_create_commit() {
+    // Unit test preparation
+    let hash = "1ebdf43e8950d8f9dace2e554be5d387267575ef";
+
+    // Execution step
+    let unscoped = Commit::new(hash, "feat: a new feature");
+    let scoped = Commit::new(hash, "fix(github): a fix");
+    let breaking = Commit::new(hash, "feat(api)!: drop v1");
+    let no_type = Commit::new(hash, "Issue to solve (#2)");
+
+    // Asserts the commit fields are correct
+    assert_eq!(
+        unscoped,
+        Commit {
+            hash: hash.to_string(),
+            message: "feat: a new feature".to_string(),
+            commit_type: "feat".to_string(),
+            scope: "".to_string(),
+            breaking: false,
+            description: "a new feature".to_string(),
+            body: "".to_string(),
+            footers: vec![],
+            files: vec![],
+            author: "".to_string(),
+        }
+    );
+    assert_eq!(scoped.commit_type, "fix");
+    assert_eq!(scoped.scope, "github");
+    assert_eq!(breaking.scope, "api");
+    assert_eq!(breaking.commit_type, "feat");
+    assert!(breaking.breaking);
+    assert_eq!(no_type.commit_type, "");
+    assert_eq!(no_type.description, "Issue to solve (#2)");
+}
+
+// Tests the creation of a commit whose message has a body and footers.
+#[test]
+fn test_can_create_commit_with_footers() {
+    // Execution step
+    let commit = Commit::new(
+        "1ebdf43e8950d8f9dace2e554be5d387267575ef",
+        "refac(api): rename the routes\n\nThe routes follow the REST naming.\n\nBREAKING CHANGE: the routes are renamed",
+    );
+
+    // Asserts the breaking change is detected from the footer
+    assert_eq!(commit.commit_type, "refac");
+    assert_eq!(commit.description, "rename the routes");
+    assert_eq!(commit.body, "The routes follow the REST naming.");
+    assert_eq!(commit.footers.len(), 1);
+    assert_eq!(commit.footers[0].value, "the routes are renamed");
+    assert!(commit.breaking);
+}
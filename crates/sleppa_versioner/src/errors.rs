@@ -13,6 +13,14 @@ pub enum VersionerError {
     #[error(transparent)]
     ParsingError(#[from] std::num::ParseIntError),
 
+    // Tag format not containing the `{version}` placeholder once
+    #[error("The tag format must contain '{{version}}' once. Found : {0}")]
+    IncorrectTagFormat(String),
+
+    // Pre-release channel lower than the one of the last pre-release of the same version
+    #[error("The channel {0} is lower than the one of the last tag {1}, the new tag wouldn't follow it.")]
+    LowerChannel(String, String),
+
     // No match found when capturing the number with the regex
     #[error("No match found for {0}.")]
     ErrorNoMatch(String),
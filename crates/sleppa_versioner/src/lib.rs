@@ -7,21 +7,66 @@
 //!  - major: adds 1 to the first digit and set 0 to others, e.g. from `3.2.1` -> `4.0.0`,
 //!  - minor: adds 1 to the second and set 0 to the third, e.g. from `3.2.1` -> `3.3.0`,
 //!  - patch: adds 1 to the third, e.g. from `3.2.1` -> `3.2.2`.
+//!
+//! A [Tag] can also be a pre-release of a channel, e.g. `v2.0.0-rc.3` for the third release of the `rc` channel.
+//! When releasing on a channel, the pre-release counter is incremented as long as the pre-release version already
+//! includes the release action, e.g. from `2.0.0-rc.3` -> `2.0.0-rc.4` for a minor. Otherwise, the version is
+//! incremented and the counter starts again, e.g. from `2.0.1-rc.3` -> `2.1.0-rc.1` for a minor.
+//! Releasing a stable version promotes the pre-release the same way, e.g. from `2.0.0-rc.3` -> `2.0.0`.
+//!
+//! Tags follow [SemVer 2.0](https://semver.org), including build metadata like `v2.0.0+build.5`, and are ordered
+//! by their SemVer precedence. The text surrounding the version is given by a [TagFormat], read from the
+//! `tag_format` field of the configuration file, e.g. `release-{version}` or `mycrate@{version}`.
+//!
+//! The last release tag of a repository is the highest of its tags matching the [TagFormat] whose commit is an
+//! ancestor of the released branch (see [sort_tags] and [find_last_tag]).
+//!
+//! As a [Plugin], it calculates the new tag during the `analyze_commits` step, from the release action found by
+//! a previous plugin.
 
-mod errors;
+pub mod errors;
 
+use async_trait::async_trait;
 use errors::{VersionerError, VersionerResult};
+use once_cell::sync::Lazy;
 use regex::Regex;
-use sleppa_configuration::ReleaseAction;
+use sleppa_configuration::{constants::VERSION_PLACEHOLDER, ReleaseAction};
+use sleppa_primitives::plugins::{
+    errors::{PluginError, PluginResult},
+    Plugin, ReleaseContext,
+};
+use sleppa_primitives::repositories::{errors::RepositoryResult, Repository, RepositoryTag};
+use std::cmp::Ordering;
+
+/// Regex grammar matching a version formed like `3.2.1-rc.1+build.5`, as suggested by SemVer 2.0.
+///
+/// This regex grammar defines named captured groups for major, minor and patch number, and for the pre-release and
+/// build identifiers. It is compiled once, as every tag of a repository is parsed when looking for the last one.
+static SEMVER_REGEX: Lazy<Regex> = Lazy::new(|| {
+    // The regex is built from a constant expression, hence it can't fail.
+    Regex::new(
+        r"^(?P<major>0|[1-9][0-9]*)\.(?P<minor>0|[1-9][0-9]*)\.(?P<patch>0|[1-9][0-9]*)(?:-(?P<pre_release>(?:0|[1-9][0-9]*|[0-9]*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9][0-9]*|[0-9]*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+(?P<build>[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$",
+    )
+    .unwrap()
+});
 
+/// Defines the versioner plugin
+///
+/// This plugin increments a [Tag] according to its [ReleaseAction], as a stable release or as a pre-release
+/// of its channel.
 pub struct VersionerPlugin {
     pub release_action: ReleaseAction,
+    /// The pre-release channel, e.g. `beta`, or `None` for a stable release
+    pub channel: Option<String>,
 }
 
 /// Defines a Tag and its fields
 ///
-/// A tag is defined like `v3.2.1` where `v{major}.{minor}.{patch}`
-#[derive(Debug, PartialEq)]
+/// A tag is defined like `v3.2.1` where `v{major}.{minor}.{patch}`, optionally followed by pre-release
+/// identifiers like `v3.2.1-rc.1` where `v{major}.{minor}.{patch}-{channel}.{counter}`, and by build metadata
+/// like `v3.2.1+build.5`. The `v` prefix is given by the [TagFormat] of the tag.
+/// The default tag is `v0.0.0`, used when a repository has not been released yet.
+#[derive(Debug, PartialEq, Eq, Default)]
 pub struct Tag {
     /// Major number defining a tag
     major: u64,
@@ -29,14 +74,88 @@ pub struct Tag {
     minor: u64,
     /// Patch number defining a tag
     patch: u64,
+    /// Dot separated pre-release identifiers, e.g. `["rc", "1"]`, empty for a stable version
+    pre_release: Vec<String>,
+    /// Dot separated build metadata identifiers, e.g. `["build", "5"]`
+    build: Vec<String>,
+    /// The format of the tag, surrounding the version
+    format: TagFormat,
+}
+
+/// Defines a tag format and its fields
+///
+/// A tag format is defined as a pattern containing the `{version}` placeholder, e.g. `v{version}`,
+/// `release-{version}` or `mycrate@{version}`. The default tag format is `v{version}`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TagFormat {
+    /// Text preceding the version, e.g. `release-`
+    prefix: String,
+    /// Text following the version
+    suffix: String,
 }
 
 impl VersionerPlugin {
     /// Calculates the new Tag for a given release action
     ///
-    /// This function takes an existing [Tag] and calculates the new tag for a given [ReleaseAction].
-    pub fn run(&self, tag: Tag) -> Tag {
-        tag.increment(&self.release_action)
+    /// This function takes an existing [Tag] and calculates the new tag for a given [ReleaseAction],
+    /// as a pre-release if a channel is set.
+    pub fn run(&self, tag: Tag) -> VersionerResult<Tag> {
+        match &self.channel {
+            Some(channel) => tag.increment_pre_release(&self.release_action, channel),
+            None => Ok(tag.increment(&self.release_action)),
+        }
+    }
+}
+
+impl Default for VersionerPlugin {
+    /// Creates a versioner plugin for a stable patch, the lowest release action.
+    ///
+    /// When the plugin runs in a release pipeline, the release action and the channel are replaced by the ones of
+    /// the release context.
+    fn default() -> Self {
+        VersionerPlugin {
+            release_action: ReleaseAction::Patch,
+            channel: None,
+        }
+    }
+}
+
+#[async_trait]
+impl Plugin for VersionerPlugin {
+    fn name(&self) -> &str {
+        "versioner"
+    }
+
+    /// Calculates the new tag from the last tag and the release action of the release context.
+    ///
+    /// Nothing is calculated if no release action has been found. If the repository has no tag yet,
+    /// the new tag is calculated from `v0.0.0`. The tags are formatted with the configured tag format, or with the
+    /// one of the released package in a monorepo.
+    /// If the released branch is mapped to a channel in the configuration,
+    /// the new tag is a pre-release of this channel.
+    async fn analyze_commits(&mut self, context: &mut ReleaseContext) -> PluginResult<()> {
+        self.release_action = match context.release_action {
+            Some(release_action) => release_action,
+            None => return Ok(()),
+        };
+        self.channel = context.configuration.branches.get(&context.branch).cloned();
+
+        let format = TagFormat::try_new(&context.configuration.tag_format(context.package.as_deref()))
+            .map_err(PluginError::failure)?;
+        let tag = if context.last_tag.identifier.is_empty() {
+            Tag {
+                format,
+                ..Default::default()
+            }
+        } else {
+            format
+                .parse(&context.last_tag.identifier)
+                .map_err(PluginError::failure)?
+        };
+        let new_tag = self.run(tag).map_err(PluginError::failure)?;
+        context.new_version = Some(new_tag.version());
+        context.new_tag = Some(new_tag.to_string());
+        Ok(())
     }
 }
 
@@ -45,12 +164,97 @@ impl TryFrom<&str> for Tag {
 
     /// Tries to convert from a tag as string to a tag as structure
     ///
-    /// This function tries to convert a given tag defined as string to a [Tag] defined as structure.
+    /// This function tries to convert a given tag defined as string, formed like `v3.2.1`, to a [Tag] defined
+    /// as structure. See [TagFormat::parse] for other tag formats.
     fn try_from(tag: &str) -> VersionerResult<Tag> {
-        // Creates the regex grammar to match a tag formed like `v3.2.1`.
-        // This regex grammar defines named captured groups for major, minor and patch number.
-        let regex = Regex::new("^v{1}(?P<major>[0-9]+).(?P<minor>[0-9]+).(?P<patch>[0-9]+)$")?;
-        let captured = match regex.captures(tag) {
+        TagFormat::default().parse(tag)
+    }
+}
+
+impl std::fmt::Display for Tag {
+    /// Prints the correct format for Tag e.g. "v3.2.1", "v3.2.1-rc.1+build.5" or "mycrate@3.2.1".
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.format.prefix, self.version(), self.format.suffix)
+    }
+}
+
+impl Ord for Tag {
+    /// Orders the tags following the SemVer 2.0 precedence.
+    ///
+    /// The major, minor and patch numbers are compared numerically, then a pre-release has a lower precedence than
+    /// its stable version, e.g. `1.0.0-rc.1 < 1.0.0`. The pre-release identifiers are compared one by one :
+    /// numerically if both are numeric, lexically otherwise, a numeric identifier being lower than an alphanumeric
+    /// one. A larger set of identifiers has a higher precedence if the preceding ones are equal,
+    /// e.g. `1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-alpha.beta < 1.0.0-beta < 1.0.0-beta.2 < 1.0.0-beta.11`.
+    ///
+    /// Build metadata and the tag format have no precedence: they are only compared to order equal versions.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.major
+            .cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch))
+            .then_with(|| match (self.pre_release.is_empty(), other.pre_release.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_identifiers(&self.pre_release, &other.pre_release),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+            .then_with(|| self.format.cmp(&other.format))
+    }
+}
+
+impl PartialOrd for Tag {
+    /// Orders the tags following the SemVer 2.0 precedence, see [Tag::cmp].
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares two sets of pre-release identifiers following the SemVer 2.0 precedence.
+fn compare_identifiers(identifiers: &[String], other_identifiers: &[String]) -> Ordering {
+    for (identifier, other_identifier) in identifiers.iter().zip(other_identifiers) {
+        let ordering = match (identifier.parse::<u64>(), other_identifier.parse::<u64>()) {
+            (Ok(number), Ok(other_number)) => number.cmp(&other_number),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => identifier.cmp(other_identifier),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    identifiers.len().cmp(&other_identifiers.len())
+}
+
+impl TagFormat {
+    /// Creates a tag format from a pattern containing the `{version}` placeholder once, e.g. `release-{version}`.
+    pub fn try_new(pattern: &str) -> VersionerResult<Self> {
+        match pattern.split_once(VERSION_PLACEHOLDER) {
+            Some((prefix, suffix)) if !suffix.contains(VERSION_PLACEHOLDER) => Ok(TagFormat {
+                prefix: prefix.to_string(),
+                suffix: suffix.to_string(),
+            }),
+            _ => Err(VersionerError::IncorrectTagFormat(pattern.to_string())),
+        }
+    }
+
+    /// Tries to convert from a tag as string to a tag as structure, according to the tag format
+    ///
+    /// Once the prefix and the suffix of the format are removed, the version must follow the
+    /// [SemVer 2.0 grammar](https://semver.org/#backusnaur-form-grammar-for-valid-semver-versions), e.g.
+    /// `3.2.1`, `3.2.1-rc.1` or `3.2.1-rc.1+build.5`.
+    pub fn parse(&self, tag: &str) -> VersionerResult<Tag> {
+        let version = match tag
+            .strip_prefix(self.prefix.as_str())
+            .and_then(|version| version.strip_suffix(self.suffix.as_str()))
+        {
+            Some(version) => version,
+            None => return Err(VersionerError::ErrorNoMatch("tag format".to_string())),
+        };
+
+        // Matches the version with the SemVer grammar, the prefix and the suffix being stripped as plain text
+        let captured = match SEMVER_REGEX.captures(version) {
             Some(captured) => captured,
             None => return Err(VersionerError::ErrorNoMatch("regex".to_string())),
         };
@@ -71,25 +275,85 @@ impl TryFrom<&str> for Tag {
             None => return Err(VersionerError::ErrorNoMatch("patch number".to_string())),
         };
 
+        // Splits the optional identifiers
+        let identifiers = |name: &str| match captured.name(name) {
+            Some(identifiers) => identifiers.as_str().split('.').map(|id| id.to_string()).collect(),
+            None => vec![],
+        };
+
         // Parses the captured groups from char to u64
         let tag = Tag {
             major: major.parse::<u64>()?,
             minor: minor.parse::<u64>()?,
             patch: patch.parse::<u64>()?,
+            pre_release: identifiers("pre_release"),
+            build: identifiers("build"),
+            format: self.clone(),
         };
 
         Ok(tag)
     }
 }
 
-impl std::fmt::Display for Tag {
-    /// Prints the correct format for Tag e.g. "v3.2.1".
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+/// Sorts the release tags of a repository by SemVer precedence, from the highest to the lowest.
+///
+/// The tags not matching the tag format are left out, e.g. the tags of other packages in a monorepo. A stable
+/// release follows the last stable tag, hence the pre-release tags are left out without channel. A pre-release
+/// follows the last tag of any channel, so that the new tag sorts above it (see [Tag::increment_pre_release]).
+pub fn sort_tags(tags: Vec<RepositoryTag>, format: &TagFormat, channel: Option<&str>) -> Vec<RepositoryTag> {
+    let mut release_tags: Vec<(Tag, RepositoryTag)> = tags
+        .into_iter()
+        .filter_map(|tag| format.parse(&tag.identifier).ok().map(|parsed| (parsed, tag)))
+        .filter(|(parsed, _)| parsed.pre_release.is_empty() || channel.is_some())
+        .collect();
+
+    release_tags.sort_by(|(parsed, _), (other_parsed, _)| other_parsed.cmp(parsed));
+    release_tags.into_iter().map(|(_, tag)| tag).collect()
+}
+
+/// Finds the last release tag of a branch among the tags of a repository.
+///
+/// The tags are sorted by SemVer precedence (see [sort_tags]), and the highest one whose commit is an ancestor of
+/// the branch is returned, or the default tag if the branch has no release yet. The branch's head is the one of the
+/// default branch if the branch is empty.
+pub async fn find_last_tag(
+    repository: &(dyn Repository + Send + Sync),
+    tags: Vec<RepositoryTag>,
+    format: &TagFormat,
+    channel: Option<&str>,
+    branch: &str,
+) -> RepositoryResult<RepositoryTag> {
+    for tag in sort_tags(tags, format, channel) {
+        if repository.is_ancestor(&tag, branch).await? {
+            return Ok(tag);
+        }
+    }
+    Ok(RepositoryTag::default())
+}
+
+impl Default for TagFormat {
+    /// Creates the default tag format `v{version}`, e.g. `v3.2.1`.
+    fn default() -> Self {
+        TagFormat {
+            prefix: "v".to_string(),
+            suffix: "".to_string(),
+        }
     }
 }
 
 impl Tag {
+    /// Returns the version of the tag without its format, e.g. `3.2.1-rc.1` for the tag `v3.2.1-rc.1`.
+    pub fn version(&self) -> String {
+        let mut version = format!("{}.{}.{}", self.major, self.minor, self.patch);
+        if !self.pre_release.is_empty() {
+            version = format!("{version}-{}", self.pre_release.join("."));
+        }
+        if !self.build.is_empty() {
+            version = format!("{version}+{}", self.build.join("."));
+        }
+        version
+    }
+
     /// Increments the tag according to the release action
     ///
     /// A [Tag] is composed of 3 digits, e.g. `v3.2.1`. According to a [ReleaseAction], these digits
@@ -97,12 +361,23 @@ impl Tag {
     ///  - 1 to the first digit and set 0 to others for major, e.g. from `3.2.1` -> `4.0.0`,
     ///  - 1 to the second and set 0 to the third for minor, e.g. from `3.2.1` -> `3.3.0`,
     ///  - 1 to the third for patch, e.g. from `3.2.1` -> `3.2.2`.
+    ///
+    /// The build metadata are dropped and the tag format is kept.
+    /// A pre-release whose version already includes the release action is promoted to its stable version,
+    /// e.g. from `3.0.0-rc.1` -> `3.0.0` for a minor.
     pub fn increment(&self, release_action: &ReleaseAction) -> Self {
         let mut tag = Tag {
             major: self.major,
             minor: self.minor,
             patch: self.patch,
+            pre_release: vec![],
+            build: vec![],
+            format: self.format.clone(),
         };
+        if self.includes(release_action) {
+            return tag;
+        }
+
         match release_action {
             ReleaseAction::Major => {
                 tag.major += 1;
@@ -121,6 +396,49 @@ impl Tag {
             }
         }
     }
+
+    /// Increments the tag as a pre-release of a channel according to the release action
+    ///
+    /// If the tag is a pre-release whose version already includes the release action, only the pre-release
+    /// counter is incremented, e.g. from `3.0.0-rc.1` -> `3.0.0-rc.2`, or restarted when changing of channel,
+    /// e.g. from `3.0.0-beta.2` -> `3.0.0-rc.1`. Otherwise, the version is incremented like a stable release and
+    /// the counter starts at 1, e.g. from `3.2.1` -> `3.3.0-rc.1` for a minor.
+    ///
+    /// The new tag must follow the tag by SemVer precedence, hence switching to a lower channel of the same version
+    /// is refused, e.g. from `3.0.0-rc.3` to the `beta` channel.
+    pub fn increment_pre_release(&self, release_action: &ReleaseAction, channel: &str) -> VersionerResult<Self> {
+        let mut tag = self.increment(release_action);
+
+        let counter = match self.pre_release.as_slice() {
+            [current, identifiers @ ..] if self.includes(release_action) => match channel.cmp(current.as_str()) {
+                Ordering::Equal => identifiers
+                    .first()
+                    .and_then(|counter| counter.parse::<u64>().ok())
+                    .map_or(1, |counter| counter + 1),
+                Ordering::Greater => 1,
+                Ordering::Less => return Err(VersionerError::LowerChannel(channel.to_string(), self.to_string())),
+            },
+            _ => 1,
+        };
+        tag.pre_release = vec![channel.to_string(), counter.to_string()];
+        Ok(tag)
+    }
+
+    /// Verifies if the tag is a pre-release whose version already includes the release action
+    ///
+    /// The pre-release of a major version only can include a major release action, e.g. `3.0.0-rc.1`,
+    /// whereas every pre-release includes a patch release action.
+    fn includes(&self, release_action: &ReleaseAction) -> bool {
+        if self.pre_release.is_empty() {
+            return false;
+        }
+
+        match release_action {
+            ReleaseAction::Major => self.minor == 0 && self.patch == 0,
+            ReleaseAction::Minor => self.patch == 0,
+            ReleaseAction::Patch => true,
+        }
+    }
 }
 
 #[cfg(test)]
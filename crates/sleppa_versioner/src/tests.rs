@@ -2,6 +2,10 @@
 //!
 //! This testing module implements the unit tests for versioning.
 use super::{errors::*, *};
+use sleppa_configuration::MonorepoConfiguration;
+use sleppa_primitives::repositories::RepositoryTag;
+use sleppa_primitives::Commit;
+use time::OffsetDateTime;
 
 // Tests the conversion of a string tag into a [Tag] structure
 #[test]
@@ -19,7 +23,10 @@ fn test_can_try_into() -> TestResult<()> {
         Tag {
             major: 3,
             minor: 2,
-            patch: 1
+            patch: 1,
+            pre_release: vec![],
+            build: vec![],
+            format: TagFormat::default(),
         }
     );
 
@@ -37,6 +44,9 @@ fn test_can_increment() {
         major: 3,
         minor: 2,
         patch: 1,
+        pre_release: vec![],
+        build: vec![],
+        format: TagFormat::default(),
     };
 
     // Execution step
@@ -51,6 +61,9 @@ fn test_can_increment() {
             major: 4,
             minor: 0,
             patch: 0,
+            pre_release: vec![],
+            build: vec![],
+            format: TagFormat::default(),
         }
     );
 
@@ -60,6 +73,9 @@ fn test_can_increment() {
             major: 3,
             minor: 3,
             patch: 0,
+            pre_release: vec![],
+            build: vec![],
+            format: TagFormat::default(),
         }
     );
 
@@ -69,6 +85,344 @@ fn test_can_increment() {
             major: 3,
             minor: 2,
             patch: 2,
+            pre_release: vec![],
+            build: vec![],
+            format: TagFormat::default(),
         }
     );
 }
+
+// Tests the new tag is calculated from the release context
+#[tokio::test]
+async fn test_can_analyze_commits() -> TestResult<()> {
+    // Unit test preparation
+    let mut context = ReleaseContext {
+        last_tag: RepositoryTag {
+            identifier: "v3.2.1".to_string(),
+            hash: "".to_string(),
+        },
+        release_action: Some(ReleaseAction::Minor),
+        ..Default::default()
+    };
+    let mut no_release_context = ReleaseContext::default();
+    let mut pre_release_context = ReleaseContext {
+        branch: "next".to_string(),
+        release_action: Some(ReleaseAction::Major),
+        ..Default::default()
+    };
+    pre_release_context
+        .configuration
+        .branches
+        .insert("next".to_string(), "beta".to_string());
+    let mut package_context = ReleaseContext {
+        package: Some("sleppa_versioner".to_string()),
+        last_tag: RepositoryTag {
+            identifier: "sleppa_versioner@v0.1.0".to_string(),
+            hash: "".to_string(),
+        },
+        release_action: Some(ReleaseAction::Minor),
+        ..Default::default()
+    };
+    package_context.configuration.monorepo = Some(MonorepoConfiguration {
+        tag_format: "{package}@v{version}".to_string(),
+        scopes: Default::default(),
+    });
+
+    // Execution step
+    VersionerPlugin::default().analyze_commits(&mut context).await?;
+    VersionerPlugin::default()
+        .analyze_commits(&mut pre_release_context)
+        .await?;
+    VersionerPlugin::default().analyze_commits(&mut package_context).await?;
+    VersionerPlugin::default()
+        .analyze_commits(&mut no_release_context)
+        .await?;
+
+    // Asserts the new tag is set only when a release is needed
+    assert_eq!(context.new_tag, Some("v3.3.0".to_string()));
+    assert_eq!(context.new_version, Some("3.3.0".to_string()));
+    assert_eq!(no_release_context.new_tag, None);
+    assert_eq!(pre_release_context.new_tag, Some("v1.0.0-beta.1".to_string()));
+    assert_eq!(package_context.new_tag, Some("sleppa_versioner@v0.2.0".to_string()));
+    assert_eq!(package_context.new_version, Some("0.2.0".to_string()));
+
+    Ok(())
+}
+
+// Tests the conversion of a pre-release tag into a [Tag] structure and back
+#[test]
+fn test_can_try_into_pre_release() -> TestResult<()> {
+    // Execution step
+    let tag = Tag::try_from("v2.0.0-rc.3")?;
+
+    // Asserts the pre-release identifiers are kept
+    assert_eq!(
+        tag,
+        Tag {
+            major: 2,
+            minor: 0,
+            patch: 0,
+            pre_release: vec!["rc".to_string(), "3".to_string()],
+            build: vec![],
+            format: TagFormat::default(),
+        }
+    );
+    assert_eq!(tag.to_string(), "v2.0.0-rc.3");
+
+    // Asserts incorrect pre-release identifiers are rejected
+    assert!(Tag::try_from("v2.0.0-rc..3").is_err());
+
+    Ok(())
+}
+
+// Tests a Tag's incrementation on a pre-release channel and its promotion to a stable version
+#[test]
+fn test_can_increment_pre_release() -> TestResult<()> {
+    // Unit test preparation
+    let stable = Tag::try_from("v3.2.1")?;
+    let rc = Tag::try_from("v4.0.0-rc.3")?;
+    let beta = Tag::try_from("v4.0.0-beta.2")?;
+    let patch_rc = Tag::try_from("v3.2.2-rc.1")?;
+
+    // Asserts a stable tag starts a pre-release with the incremented version
+    assert_eq!(
+        stable.increment_pre_release(&ReleaseAction::Minor, "beta")?.to_string(),
+        "v3.3.0-beta.1"
+    );
+
+    // Asserts the counter is incremented when the pre-release version includes the release action
+    assert_eq!(
+        rc.increment_pre_release(&ReleaseAction::Major, "rc")?.to_string(),
+        "v4.0.0-rc.4"
+    );
+    assert_eq!(
+        rc.increment_pre_release(&ReleaseAction::Patch, "rc")?.to_string(),
+        "v4.0.0-rc.4"
+    );
+
+    // Asserts the counter starts again when changing to a higher channel or incrementing the version
+    assert_eq!(
+        beta.increment_pre_release(&ReleaseAction::Minor, "rc")?.to_string(),
+        "v4.0.0-rc.1"
+    );
+    assert_eq!(
+        patch_rc.increment_pre_release(&ReleaseAction::Minor, "rc")?.to_string(),
+        "v3.3.0-rc.1"
+    );
+
+    // Asserts changing to a lower channel of the same version is refused, as the new tag wouldn't follow the last one
+    assert!(matches!(
+        rc.increment_pre_release(&ReleaseAction::Minor, "beta"),
+        Err(VersionerError::LowerChannel(_, _))
+    ));
+
+    // Asserts changing to a lower channel is allowed when the version is incremented
+    assert_eq!(
+        patch_rc
+            .increment_pre_release(&ReleaseAction::Minor, "beta")?
+            .to_string(),
+        "v3.3.0-beta.1"
+    );
+
+    // Asserts a pre-release is promoted to its stable version, unless it doesn't include the release action
+    assert_eq!(rc.increment(&ReleaseAction::Minor).to_string(), "v4.0.0");
+    assert_eq!(patch_rc.increment(&ReleaseAction::Patch).to_string(), "v3.2.2");
+    assert_eq!(patch_rc.increment(&ReleaseAction::Major).to_string(), "v4.0.0");
+
+    Ok(())
+}
+
+// Tests the conversion of tags with build metadata and custom formats, and their round-trip
+#[test]
+fn test_can_parse_tag_format() -> TestResult<()> {
+    // Unit test preparation
+    let release_format = TagFormat::try_new("release-{version}")?;
+    let crate_format = TagFormat::try_new("mycrate@{version}")?;
+
+    // Execution step
+    let build_tag = Tag::try_from("v1.0.0-alpha.1+build.001")?;
+    let release_tag = release_format.parse("release-3.2.1")?;
+    let crate_tag = crate_format.parse("mycrate@3.2.1-rc.1")?;
+
+    // Asserts the build metadata are kept and the tags are printed back with their format
+    assert_eq!(build_tag.build, vec!["build".to_string(), "001".to_string()]);
+    assert_eq!(build_tag.to_string(), "v1.0.0-alpha.1+build.001");
+    assert_eq!(release_tag.to_string(), "release-3.2.1");
+    assert_eq!(crate_tag.to_string(), "mycrate@3.2.1-rc.1");
+
+    // Asserts the incremented tags keep their format and drop the build metadata
+    assert_eq!(build_tag.increment(&ReleaseAction::Patch).to_string(), "v1.0.0");
+    assert_eq!(
+        release_tag.increment(&ReleaseAction::Minor).to_string(),
+        "release-3.3.0"
+    );
+
+    // Asserts versions not following SemVer 2.0 or the tag format are rejected
+    for incorrect_tag in [
+        "v3.2",
+        "v3a2b1",
+        "v03.2.1",
+        "v3.2.1-rc.01",
+        "v3.2.1-",
+        "v3.2.1+",
+        "3.2.1",
+    ] {
+        assert!(
+            Tag::try_from(incorrect_tag).is_err(),
+            "{incorrect_tag} must be rejected"
+        );
+    }
+    assert!(release_format.parse("v3.2.1").is_err());
+    assert!(TagFormat::try_new("release").is_err());
+    assert!(TagFormat::try_new("{version}-{version}").is_err());
+
+    Ok(())
+}
+
+// Tests the tags are ordered following the SemVer 2.0 precedence
+#[test]
+fn test_can_order_tags() -> TestResult<()> {
+    // Unit test preparation
+    // Tags given in the SemVer 2.0 specification, from the lowest to the highest precedence
+    let tags = [
+        "v1.0.0-alpha",
+        "v1.0.0-alpha.1",
+        "v1.0.0-alpha.beta",
+        "v1.0.0-beta",
+        "v1.0.0-beta.2",
+        "v1.0.0-beta.11",
+        "v1.0.0-rc.1",
+        "v1.0.0",
+        "v1.0.1",
+        "v1.2.0",
+        "v1.10.0",
+        "v2.0.0",
+    ];
+
+    // Execution step
+    let mut sorted_tags = tags
+        .iter()
+        .rev()
+        .map(|tag| Tag::try_from(*tag))
+        .collect::<VersionerResult<Vec<Tag>>>()?;
+    sorted_tags.sort();
+
+    // Asserts the tags are sorted by precedence
+    assert_eq!(
+        sorted_tags.iter().map(|tag| tag.to_string()).collect::<Vec<String>>(),
+        tags
+    );
+
+    // Asserts the build metadata have no precedence
+    assert!(Tag::try_from("v1.0.0+build.1")? < Tag::try_from("v1.0.1")?);
+    assert!(Tag::try_from("v1.0.0-rc.1+build.9")? < Tag::try_from("v1.0.0")?);
+
+    Ok(())
+}
+
+// Tests the release tags are sorted by precedence, leaving out the other formats and the pre-releases of a stable release
+#[test]
+fn test_can_sort_tags() -> TestResult<()> {
+    // Unit test preparation
+    let tag = |identifier: &str| RepositoryTag {
+        identifier: identifier.to_string(),
+        hash: format!("{identifier}-hash"),
+    };
+    // Tags listed as returned by a repository, i.e. by creation date rather than by precedence
+    let tags = vec![
+        tag("v1.10.0"),
+        tag("v2.0.0-beta.2"),
+        tag("other@v5.0.0"),
+        tag("v1.9.0"),
+        tag("v2.0.0-rc.1"),
+        tag("nightly"),
+        tag("v1.2.0"),
+    ];
+    let format = TagFormat::default();
+
+    // Execution step
+    let stable_tags = sort_tags(tags.clone(), &format, None);
+    let beta_tags = sort_tags(tags, &format, Some("beta"));
+
+    // Asserts a stable release only follows stable tags
+    assert_eq!(stable_tags, vec![tag("v1.10.0"), tag("v1.9.0"), tag("v1.2.0")]);
+
+    // Asserts a pre-release follows the tags of every channel, or the stable ones
+    assert_eq!(
+        beta_tags,
+        vec![
+            tag("v2.0.0-rc.1"),
+            tag("v2.0.0-beta.2"),
+            tag("v1.10.0"),
+            tag("v1.9.0"),
+            tag("v1.2.0")
+        ]
+    );
+
+    Ok(())
+}
+
+// A repository whose tags are listed in a given order, the tags of a `next` branch not being ancestors of `main`.
+struct BranchesRepository {
+    tags: Vec<RepositoryTag>,
+}
+
+#[async_trait]
+impl Repository for BranchesRepository {
+    async fn get_last_tag(&self) -> RepositoryResult<RepositoryTag> {
+        Ok(self.tags.first().cloned().unwrap_or_default())
+    }
+
+    async fn get_tags(&self) -> RepositoryResult<Vec<RepositoryTag>> {
+        Ok(self.tags.clone())
+    }
+
+    async fn is_ancestor(&self, tag: &RepositoryTag, branch: &str) -> RepositoryResult<bool> {
+        Ok(branch == "next" || !tag.hash.starts_with("next"))
+    }
+
+    async fn get_inner_commits_between(
+        &self,
+        _oldest: &RepositoryTag,
+        _newest: &RepositoryTag,
+    ) -> RepositoryResult<Vec<Commit>> {
+        Ok(vec![])
+    }
+
+    async fn get_tag_date(&self, _tag: &RepositoryTag) -> RepositoryResult<OffsetDateTime> {
+        Ok(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+// Tests the last tag of a branch is the highest by SemVer precedence among its ancestors, whatever the listing order.
+#[tokio::test]
+async fn test_can_find_last_tag() -> TestResult<()> {
+    // Unit test preparation
+    let tag = |identifier: &str, hash: &str| RepositoryTag {
+        identifier: identifier.to_string(),
+        hash: hash.to_string(),
+    };
+    let repository = BranchesRepository {
+        tags: vec![
+            tag("v1.2.0", "main2"),
+            tag("v1.10.0", "next1"),
+            tag("v1.9.0", "main3"),
+            tag("v1.10.0-beta.1", "main4"),
+            tag("v0.1.0", "main1"),
+        ],
+    };
+    let format = TagFormat::default();
+
+    // Execution step
+    let tags = repository.get_tags().await?;
+    let main_tag = find_last_tag(&repository, tags.clone(), &format, None, "main").await?;
+    let next_tag = find_last_tag(&repository, tags, &format, None, "next").await?;
+    let untagged = find_last_tag(&repository, vec![], &format, None, "main").await?;
+
+    // Asserts the highest ancestor is found, the tags of other branches and the pre-releases being left out
+    assert_eq!(main_tag, tag("v1.9.0", "main3"));
+    assert_eq!(next_tag, tag("v1.10.0", "next1"));
+    assert_eq!(untagged, RepositoryTag::default());
+
+    Ok(())
+}